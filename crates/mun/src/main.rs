@@ -1,14 +1,14 @@
 #[macro_use]
 extern crate failure;
 
-use std::cell::RefCell;
 use std::env;
-use std::rc::Rc;
+use std::sync::Arc;
 use std::time::Duration;
 
 use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
-use mun_compiler::{Config, DisplayColor, PathOrInline, Target};
-use mun_runtime::{invoke_fn, ReturnTypeReflection, Runtime, RuntimeBuilder};
+use mun_compiler::{Config, DebugDump, DisplayColor, OutputType, PathOrInline, Target};
+use mun_runtime::{invoke_fn, ReloadEvent, ReturnTypeReflection, Runtime, RuntimeBuilder};
+use parking_lot::RwLock;
 
 fn main() -> Result<(), failure::Error> {
     let matches = App::new("mun")
@@ -20,8 +20,7 @@ fn main() -> Result<(), failure::Error> {
             SubCommand::with_name("build")
                 .arg(
                     Arg::with_name("INPUT")
-                        .help("Sets the input file to use")
-                        .required(true)
+                        .help("Sets the input file to use. If omitted, the package described by the nearest `mun.toml` is built")
                         .index(1),
                 )
                 .arg(Arg::with_name("watch").long("watch").help(
@@ -33,7 +32,7 @@ fn main() -> Result<(), failure::Error> {
                         .short("O")
                         .long("opt-level")
                         .takes_value(true)
-                        .help("optimize with possible levels 0-3"),
+                        .help("optimize with possible levels 0-3, s (size) or z (minimal size)"),
                 )
                 .arg(
                     Arg::with_name("target")
@@ -48,8 +47,56 @@ fn main() -> Result<(), failure::Error> {
                         .possible_values(&["enable", "auto", "disable"])
                         .help("color text in terminal"),
                 )
+                .arg(
+                    Arg::with_name("emit")
+                        .long("emit")
+                        .takes_value(true)
+                        .use_delimiter(true)
+                        .multiple(true)
+                        .possible_values(&["llvm-ir", "llvm-bc", "asm", "obj", "lib"])
+                        .help("comma separated list of artifacts to write next to the assembly"),
+                )
+                .arg(
+                    Arg::with_name("debug")
+                        .short("g")
+                        .help("generate debug information"),
+                )
+                .arg(
+                    Arg::with_name("timings")
+                        .long("timings")
+                        .help("report the time spent in each compiler pass as a table and as a Chrome trace next to the assembly"),
+                )
                 .about("Compiles a local Mun file into a module"),
         )
+        .subcommand(
+            SubCommand::with_name("daemon")
+                .arg(
+                    Arg::with_name("listen")
+                        .long("listen")
+                        .takes_value(true)
+                        .help("the address to accept TCP connections on, e.g. 127.0.0.1:7878 (defaults to stdio)"),
+                )
+                .about("Runs a build server for the package that contains the current directory"),
+        )
+        .subcommand(
+            SubCommand::with_name("new")
+                .arg(
+                    Arg::with_name("PATH")
+                        .help("The directory to create the package in")
+                        .required(true)
+                        .index(1),
+                )
+                .about("Creates a new Mun package"),
+        )
+        .subcommand(
+            SubCommand::with_name("init")
+                .arg(
+                    Arg::with_name("PATH")
+                        .help("The directory of the package (defaults to the current directory)")
+                        .index(1),
+                )
+                .about("Creates a new Mun package in an existing directory"),
+        )
         .subcommand(
             SubCommand::with_name("start")
                 .arg(
@@ -71,19 +118,72 @@ fn main() -> Result<(), failure::Error> {
                         .help("how much to delay received filesystem events (in ms). This allows bundling of identical events, e.g. when several writes to the same file are detected. A high delay will make hot reloading less responsive. (defaults to 10 ms)"),
                 ),
         )
+        .subcommand(
+            SubCommand::with_name("fix")
+                .arg(
+                    Arg::with_name("INPUT")
+                        .help("Sets the input file to fix")
+                        .required(true)
+                        .index(1),
+                )
+                .about("Applies machine-applicable suggestions of diagnostics to a Mun file"),
+        )
+        .subcommand(
+            SubCommand::with_name("debug")
+                .arg(
+                    Arg::with_name("DUMP")
+                        .help("The data structure to print")
+                        .required(true)
+                        .possible_values(&["syntax-tree", "hir", "type-table"])
+                        .index(1),
+                )
+                .arg(
+                    Arg::with_name("INPUT")
+                        .help("Sets the input file to use")
+                        .required(true)
+                        .index(2),
+                )
+                .about("Prints a data structure of the compiler for a Mun file, e.g. to attach to bug reports"),
+        )
+        .subcommand(
+            SubCommand::with_name("explain")
+                .arg(
+                    Arg::with_name("CODE")
+                        .help("The diagnostic code to explain, e.g. E0005")
+                        .required(true)
+                        .index(1),
+                )
+                .about("Provides a detailed explanation of a diagnostic code"),
+        )
         .get_matches();
 
     match matches.subcommand() {
         ("build", Some(matches)) => build(matches)?,
+        ("daemon", Some(matches)) => daemon(matches)?,
+        ("new", Some(matches)) => new(matches)?,
+        ("init", Some(matches)) => init(matches)?,
         ("start", Some(matches)) => start(matches)?,
+        ("fix", Some(matches)) => fix(matches)?,
+        ("debug", Some(matches)) => debug(matches)?,
+        ("explain", Some(matches)) => explain(matches)?,
         _ => unreachable!(),
     }
 
     Ok(())
 }
 
-/// Build the source file specified
+/// Build the source file specified, or the package that contains the current directory
 fn build(matches: &ArgMatches) -> Result<(), failure::Error> {
+    if matches.value_of("INPUT").is_none() {
+        let package = mun_compiler::Package::discover(env::current_dir()?)?;
+        let config = config(matches, package.config()?)?;
+        return if matches.is_present("watch") {
+            mun_compiler_daemon::watch_package(&package, config)
+        } else {
+            mun_compiler::compile_package(&package, config).map(|_| {})
+        };
+    }
+
     let options = compiler_options(matches)?;
     if matches.is_present("watch") {
         mun_compiler_daemon::main(options)
@@ -92,11 +192,37 @@ fn build(matches: &ArgMatches) -> Result<(), failure::Error> {
     }
 }
 
+/// Runs a build server for the package that contains the current directory
+fn daemon(matches: &ArgMatches) -> Result<(), failure::Error> {
+    let package = mun_compiler::Package::discover(env::current_dir()?)?;
+    let config = package.config()?;
+    mun_compiler_daemon::serve(&package, config, matches.value_of("listen"))
+}
+
+/// Creates a new package in the specified directory
+fn new(matches: &ArgMatches) -> Result<(), failure::Error> {
+    let path = matches.value_of("PATH").unwrap(); // Safe because its a required arg
+    mun_compiler::new_package(path.as_ref())?;
+    println!("Created package `{}`", path);
+    Ok(())
+}
+
+/// Creates a new package in an existing directory
+fn init(matches: &ArgMatches) -> Result<(), failure::Error> {
+    let path = match matches.value_of("PATH") {
+        Some(path) => path.into(),
+        None => env::current_dir()?,
+    };
+    mun_compiler::init_package(&path)?;
+    println!("Created package in `{}`", path.display());
+    Ok(())
+}
+
 /// Starts the runtime with the specified library and invokes function `entry`.
 fn start(matches: &ArgMatches) -> Result<(), failure::Error> {
     let runtime = runtime(matches)?;
 
-    let borrowed = runtime.borrow();
+    let borrowed = runtime.read();
     let entry_point = matches.value_of("entry").unwrap_or("main");
     let fn_definition = borrowed
         .get_function_definition(entry_point)
@@ -137,13 +263,53 @@ fn start(matches: &ArgMatches) -> Result<(), failure::Error> {
     }
 }
 
+/// Applies all machine-applicable suggestions to the source file specified
+fn fix(matches: &ArgMatches) -> Result<(), failure::Error> {
+    let options = mun_compiler::CompilerOptions::with_path(matches.value_of("INPUT").unwrap()); // Safe because its a required arg
+    let applied = mun_compiler::fix(options)?;
+    println!("Applied {} suggestion(s)", applied);
+    Ok(())
+}
+
+/// Prints a data structure of the compiler for the source file specified
+fn debug(matches: &ArgMatches) -> Result<(), failure::Error> {
+    let dump = matches
+        .value_of("DUMP")
+        .unwrap() // Safe because its a required arg
+        .parse::<DebugDump>()
+        .map_err(failure::err_msg)?;
+    let options = mun_compiler::CompilerOptions::with_path(matches.value_of("INPUT").unwrap()); // Safe because its a required arg
+    print!("{}", mun_compiler::debug_dump(options, dump)?);
+    Ok(())
+}
+
+/// Prints the long-form explanation of the specified diagnostic code.
+fn explain(matches: &ArgMatches) -> Result<(), failure::Error> {
+    let code = matches.value_of("CODE").unwrap(); // Safe because its a required arg
+    let explanation = mun_compiler::explain(code)
+        .ok_or_else(|| format_err!("'{}' is not a valid diagnostic code", code))?;
+    println!("{}", explanation);
+    Ok(())
+}
+
 fn compiler_options(matches: &ArgMatches) -> Result<mun_compiler::CompilerOptions, failure::Error> {
+    Ok(mun_compiler::CompilerOptions {
+        input: PathOrInline::Path(matches.value_of("INPUT").unwrap().into()), // Safe because `build` checks its presence
+        config: config(matches, Config::default())?,
+    })
+}
+
+/// Applies the compiler settings specified on the command line to `config`.
+fn config(matches: &ArgMatches, config: Config) -> Result<Config, failure::Error> {
     let optimization_lvl = match matches.value_of("opt-level") {
-        Some("0") => mun_compiler::OptimizationLevel::None,
-        Some("1") => mun_compiler::OptimizationLevel::Less,
-        None | Some("2") => mun_compiler::OptimizationLevel::Default,
-        Some("3") => mun_compiler::OptimizationLevel::Aggressive,
-        _ => return Err(format_err!("Only optimization levels 0-3 are supported")),
+        Some(value) => mun_compiler::parse_opt_level(value)
+            .ok_or_else(|| format_err!("Only optimization levels 0-3, s and z are supported"))?,
+        None => config.optimization_lvl,
+    };
+
+    let target = match matches.value_of("target") {
+        Some(target) => Target::search(target)?,
+        None => config.target,
     };
 
     let display_color = matches
@@ -157,20 +323,28 @@ fn compiler_options(matches: &ArgMatches) -> Result<mun_compiler::CompilerOption
         })
         .unwrap_or(DisplayColor::Auto);
 
-    Ok(mun_compiler::CompilerOptions {
-        input: PathOrInline::Path(matches.value_of("INPUT").unwrap().into()), // Safe because its a required arg
-        config: Config {
-            target: matches
-                .value_of("target")
-                .map_or_else(Target::host_target, Target::search)?,
-            optimization_lvl,
-            out_dir: None,
-            display_color,
-        },
+    let emit = matches
+        .values_of("emit")
+        .map(|values| {
+            values
+                .map(|value| value.parse::<OutputType>().map_err(failure::err_msg))
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .transpose()?
+        .unwrap_or_default();
+
+    Ok(Config {
+        target,
+        optimization_lvl,
+        out_dir: config.out_dir,
+        display_color,
+        emit,
+        debug_info: matches.is_present("debug"),
+        timings: matches.is_present("timings"),
     })
 }
 
-fn runtime(matches: &ArgMatches) -> Result<Rc<RefCell<Runtime>>, failure::Error> {
+fn runtime(matches: &ArgMatches) -> Result<Arc<RwLock<Runtime>>, failure::Error> {
     let builder = RuntimeBuilder::new(
         matches.value_of("LIBRARY").unwrap(), // Safe because its a required arg
     );
@@ -182,5 +356,25 @@ fn runtime(matches: &ArgMatches) -> Result<Rc<RefCell<Runtime>>, failure::Error>
         builder
     };
 
-    builder.spawn()
+    let runtime = builder.spawn()?;
+    runtime.write().subscribe(|event| match event {
+        ReloadEvent::AssemblyReloaded { path } => {
+            println!("Successfully reloaded assembly: '{}'", path.display())
+        }
+        ReloadEvent::ReloadFailed { path, error } => eprintln!(
+            "An error occurred while reloading assembly '{}': {}",
+            path.display(),
+            error
+        ),
+        ReloadEvent::LossyConversion { conversion, .. } => eprintln!(
+            "warning: field `{}::{}` was converted from `{}` to `{}` with loss of information ({:?})",
+            conversion.type_name,
+            conversion.field_name,
+            conversion.old_type,
+            conversion.new_type,
+            conversion.policy
+        ),
+        _ => (),
+    });
+    Ok(runtime)
 }
@@ -276,6 +276,9 @@ pub struct StructInfo {
     pub field_types: *const *const TypeInfo,
     #[doc = " Struct fields' offsets"]
     pub field_offsets: *const u16,
+    #[doc = " Struct fields' previous names, from which their values are migrated when hot reloading."]
+    #[doc = " Either null if no field was renamed, or null for each field that was not renamed."]
+    pub field_previous_names: *const *const ::std::os::raw::c_char,
     #[doc = " Number of fields"]
     pub num_fields: u16,
     #[doc = " Struct memory kind"]
@@ -285,7 +288,7 @@ pub struct StructInfo {
 fn bindgen_test_layout_StructInfo() {
     assert_eq!(
         ::std::mem::size_of::<StructInfo>(),
-        32usize,
+        40usize,
         concat!("Size of: ", stringify!(StructInfo))
     );
     assert_eq!(
@@ -324,8 +327,18 @@ fn bindgen_test_layout_StructInfo() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<StructInfo>())).num_fields as *const _ as usize },
+        unsafe { &(*(::std::ptr::null::<StructInfo>())).field_previous_names as *const _ as usize },
         24usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(StructInfo),
+            "::",
+            stringify!(field_previous_names)
+        )
+    );
+    assert_eq!(
+        unsafe { &(*(::std::ptr::null::<StructInfo>())).num_fields as *const _ as usize },
+        32usize,
         concat!(
             "Offset of field: ",
             stringify!(StructInfo),
@@ -335,7 +348,7 @@ fn bindgen_test_layout_StructInfo() {
     );
     assert_eq!(
         unsafe { &(*(::std::ptr::null::<StructInfo>())).memory_kind as *const _ as usize },
-        26usize,
+        34usize,
         concat!(
             "Offset of field: ",
             stringify!(StructInfo),
@@ -192,6 +192,23 @@ impl StructInfo {
         }
     }
 
+    /// Returns the struct's fields' previous names, from which their values are migrated when hot
+    /// reloading. Fields that were not renamed have no previous name.
+    pub fn field_previous_names(&self) -> impl Iterator<Item = Option<&str>> {
+        let previous_names = if self.num_fields == 0 || self.field_previous_names.is_null() {
+            &[]
+        } else {
+            unsafe { slice::from_raw_parts(self.field_previous_names, self.num_fields as usize) }
+        };
+
+        (0..self.num_fields as usize).map(move |idx| {
+            previous_names
+                .get(idx)
+                .filter(|n| !n.is_null())
+                .map(|n| unsafe { str::from_utf8_unchecked(CStr::from_ptr(*n).to_bytes()) })
+        })
+    }
+
     /// Returns the index of the field matching the specified `field_name`.
     pub fn find_field_index(
         type_name: &str,
@@ -542,6 +559,7 @@ mod tests {
             field_names: field_names.as_ptr(),
             field_types: field_types.as_ptr().cast::<*const TypeInfo>(),
             field_offsets: field_offsets.as_ptr(),
+            field_previous_names: ptr::null(),
             num_fields: field_names.len() as u16,
             memory_kind,
         }
@@ -558,6 +576,7 @@ mod tests {
         assert_eq!(struct_info.field_names().count(), 0);
         assert_eq!(struct_info.field_types(), field_types);
         assert_eq!(struct_info.field_offsets(), field_offsets);
+        assert_eq!(struct_info.field_previous_names().count(), 0);
     }
 
     #[test]
@@ -577,6 +596,31 @@ mod tests {
         }
         assert_eq!(struct_info.field_types(), field_types);
         assert_eq!(struct_info.field_offsets(), field_offsets);
+        assert_eq!(
+            struct_info.field_previous_names().collect::<Vec<_>>(),
+            vec![None]
+        );
+    }
+
+    #[test]
+    fn test_struct_info_field_previous_names() {
+        let field_name = CString::new(FAKE_FIELD_NAME).expect("Invalid fake field name.");
+        let previous_name = CString::new("previous").expect("Invalid fake field name.");
+        let type_name = CString::new(FAKE_TYPE_NAME).expect("Invalid fake type name.");
+        let type_info = fake_type_info(&type_name, TypeGroup::FundamentalTypes, 1, 1);
+
+        let field_names = &[field_name.as_ptr(), field_name.as_ptr()];
+        let field_types = &[&type_info, &type_info];
+        let field_offsets = &[0, 1];
+        let previous_names = &[previous_name.as_ptr(), ptr::null()];
+        let mut struct_info =
+            fake_struct_info(field_names, field_types, field_offsets, Default::default());
+        struct_info.field_previous_names = previous_names.as_ptr();
+
+        assert_eq!(
+            struct_info.field_previous_names().collect::<Vec<_>>(),
+            vec![Some("previous"), None]
+        );
     }
 
     #[test]
@@ -1,23 +1,28 @@
 use inkwell::context::Context;
 use crate::code_gen::linker::LinkerError;
-use crate::CodegenContext;
+use crate::ir::file_group;
+use crate::optimization::{add_vectorization_passes, create_pass_manager_builder};
+use crate::{CodegenContext, OptimizationLevel};
 use failure::Fail;
 use hir::{FileId, RelativePathBuf};
 use inkwell::{
     module::{Linkage, Module},
-    passes::{PassManager, PassManagerBuilder},
+    passes::PassManager,
     targets::{TargetTriple, CodeModel, FileType, InitializationConfig, RelocMode, Target, TargetMachine},
     types::StructType,
     values::{BasicValue, GlobalValue, IntValue, PointerValue, UnnamedAddress},
-    AddressSpace, OptimizationLevel,
+    AddressSpace,
 };
 use mun_target::spec;
 use std::io::{self, Write};
 use std::{
+    fmt,
     path::{Path, PathBuf},
+    str::FromStr,
     sync::Arc,
+    time::{Duration, Instant},
 };
-use tempfile::NamedTempFile;
+use tempfile::TempDir;
 
 mod linker;
 pub mod symbols;
@@ -36,6 +41,8 @@ enum CodeGenerationError {
     CouldNotCreateObjectFile(io::Error),
     #[fail(display = "error generating machine code")]
     CodeGenerationError(String),
+    #[fail(display = "error writing {} to {}: {}", 0, 1, 2)]
+    CouldNotWriteOutput(OutputType, String, String),
 }
 
 impl From<LinkerError> for CodeGenerationError {
@@ -44,48 +51,227 @@ impl From<LinkerError> for CodeGenerationError {
     }
 }
 
-pub struct ObjectFile {
+/// The kinds of artifacts that can be emitted by the compiler.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum OutputType {
+    /// Textual LLVM IR (`.ll`)
+    LlvmIr,
+    /// LLVM bitcode (`.bc`)
+    LlvmBitcode,
+    /// Assembly of the target (`.s`)
+    Assembly,
+    /// An object file of the target (`.o`)
+    Object,
+    /// A Mun assembly that can be loaded by the runtime (`.munlib`)
+    Library,
+}
+
+impl OutputType {
+    /// Returns the file extension used for this type of output.
+    pub fn extension(self) -> &'static str {
+        match self {
+            OutputType::LlvmIr => "ll",
+            OutputType::LlvmBitcode => "bc",
+            OutputType::Assembly => "s",
+            OutputType::Object => "o",
+            OutputType::Library => "munlib",
+        }
+    }
+}
+
+impl fmt::Display for OutputType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            OutputType::LlvmIr => "llvm-ir",
+            OutputType::LlvmBitcode => "llvm-bc",
+            OutputType::Assembly => "asm",
+            OutputType::Object => "obj",
+            OutputType::Library => "lib",
+        })
+    }
+}
+
+impl FromStr for OutputType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "llvm-ir" => Ok(OutputType::LlvmIr),
+            "llvm-bc" => Ok(OutputType::LlvmBitcode),
+            "asm" => Ok(OutputType::Assembly),
+            "obj" => Ok(OutputType::Object),
+            "lib" => Ok(OutputType::Library),
+            _ => Err(format!("unknown output type: {}", s)),
+        }
+    }
+}
+
+/// Statistics about the generation of an assembly.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CodegenStats {
+    /// The number of functions for which IR was generated
+    pub generated_functions: usize,
+    /// The number of functions whose IR was reused from a previous compilation
+    pub reused_functions: usize,
+}
+
+/// The time spent in a single pass of the compiler.
+#[derive(Clone, Debug)]
+pub struct PassTiming {
+    /// The name of the pass, e.g. `IR generation`
+    pub name: &'static str,
+    /// The moment at which the pass started
+    pub start: Instant,
+    /// How long the pass took
+    pub duration: Duration,
+}
+
+impl PassTiming {
+    /// Runs the pass `f` and returns its result together with the time it took.
+    pub fn measure<T, F: FnOnce() -> T>(name: &'static str, f: F) -> (T, PassTiming) {
+        let start = Instant::now();
+        let result = f();
+        let timing = PassTiming {
+            name,
+            start,
+            duration: start.elapsed(),
+        };
+        (result, timing)
+    }
+}
+
+/// The result of code generation for a single assembly. Next to the object file it retains the
+/// LLVM module and target machine from which it was generated, so that other artifacts (e.g. LLVM
+/// IR or assembly) can be written for inspection.
+pub struct ObjectFile<'ink> {
     target: spec::Target,
+    target_machine: TargetMachine,
     src_path: RelativePathBuf,
-    obj_file: NamedTempFile,
+    module: Arc<Module<'ink>>,
+    /// The temporary directory that contains the object file
+    _obj_dir: TempDir,
+    obj_path: PathBuf,
+    debug_info: bool,
+    stats: CodegenStats,
+    timings: Vec<PassTiming>,
 }
 
-impl ObjectFile {
+impl<'ink> ObjectFile<'ink> {
     pub fn new(
         target: &spec::Target,
-        target_machine: &TargetMachine,
+        target_machine: TargetMachine,
         src_path: RelativePathBuf,
-        module: Arc<inkwell::module::Module>,
+        module: Arc<Module<'ink>>,
+        debug_info: bool,
     ) -> Result<Self, failure::Error> {
-        let obj = target_machine
-            .write_to_memory_buffer(&module, FileType::Object)
-            .map_err(|e| CodeGenerationError::CodeGenerationError(e.to_string()))?;
-
-        let mut obj_file = tempfile::NamedTempFile::new()
-            .map_err(CodeGenerationError::CouldNotCreateObjectFile)?;
-        obj_file
-            .write(obj.as_slice())
+        let (obj, timing) = PassTiming::measure("machine code generation", || {
+            target_machine.write_to_memory_buffer(&module, FileType::Object)
+        });
+        let obj = obj.map_err(|e| CodeGenerationError::CodeGenerationError(e.to_string()))?;
+
+        // The object file is named after the source file rather than given a random name, because
+        // linkers may embed the name of the object in the shared object.
+        let obj_dir = tempfile::tempdir().map_err(CodeGenerationError::CouldNotCreateObjectFile)?;
+        let obj_path = output_path(&src_path, Some(obj_dir.path()), OutputType::Object);
+        std::fs::File::create(&obj_path)
+            .and_then(|mut obj_file| obj_file.write_all(obj.as_slice()))
             .map_err(CodeGenerationError::CouldNotCreateObjectFile)?;
 
         Ok(Self {
             target: target.clone(),
+            target_machine,
             src_path,
-            obj_file,
+            module,
+            _obj_dir: obj_dir,
+            obj_path,
+            debug_info,
+            stats: CodegenStats::default(),
+            timings: vec![timing],
         })
     }
 
+    /// Returns statistics about the generation of the object file.
+    pub fn stats(&self) -> CodegenStats {
+        self.stats
+    }
+
+    /// Returns how long the passes took that generated the object file, in the order in which
+    /// they ran. Linking is not included, because it happens when the object file is written.
+    pub fn timings(&self) -> &[PassTiming] {
+        &self.timings
+    }
+
+    /// Writes an artifact of the specified type to the output directory. The name of the artifact
+    /// is derived from the source file. Returns the path of the written file.
+    pub fn write(
+        &self,
+        output_type: OutputType,
+        out_dir: Option<&Path>,
+    ) -> Result<PathBuf, failure::Error> {
+        let output_path = output_path(&self.src_path, out_dir, output_type);
+        let write_error = |e: String| {
+            CodeGenerationError::CouldNotWriteOutput(
+                output_type,
+                output_path.display().to_string(),
+                e,
+            )
+        };
+
+        match output_type {
+            OutputType::LlvmIr => self
+                .module
+                .print_to_file(&output_path)
+                .map_err(|e| write_error(e.to_string()))?,
+            OutputType::LlvmBitcode => {
+                if !self.module.write_bitcode_to_path(&output_path) {
+                    return Err(write_error("could not write bitcode".to_owned()).into());
+                }
+            }
+            OutputType::Assembly => self
+                .target_machine
+                .write_to_file(&self.module, FileType::Assembly, &output_path)
+                .map_err(|e| write_error(e.to_string()))?,
+            OutputType::Object => {
+                std::fs::copy(&self.obj_path, &output_path)
+                    .map_err(|e| write_error(e.to_string()))?;
+            }
+            OutputType::Library => {
+                self.link(&output_path)?;
+            }
+        }
+
+        Ok(output_path)
+    }
+
     pub fn into_shared_object(self, out_dir: Option<&Path>) -> Result<PathBuf, failure::Error> {
+        let output_path = output_path(&self.src_path, out_dir, OutputType::Library);
+        self.link(&output_path)?;
+        Ok(output_path)
+    }
+
+    /// Links the object file into a shared object and returns its contents. The shared object is
+    /// linked in a temporary directory, so no files are written to the output directory.
+    pub fn into_shared_object_bytes(self) -> Result<Vec<u8>, failure::Error> {
+        let temp_dir = tempfile::tempdir()?;
+        let output_path = output_path(&self.src_path, Some(temp_dir.path()), OutputType::Library);
+        self.link(&output_path)?;
+        Ok(std::fs::read(&output_path)?)
+    }
+
+    /// Links the object file into a shared object at the specified location.
+    fn link(&self, output_path: &Path) -> Result<(), failure::Error> {
         // Construct a linker for the target
         let mut linker = linker::create_with_target(&self.target);
-        linker.add_object(self.obj_file.path())?;
-
-        let output_path = assembly_output_path(&self.src_path, out_dir);
+        linker.add_object(&self.obj_path)?;
+        if self.debug_info {
+            linker.keep_debug_info();
+        }
 
         // Link the object
         linker.build_shared_object(&output_path)?;
         linker.finalize()?;
 
-        Ok(output_path)
+        Ok(())
     }
 }
 
@@ -94,13 +280,16 @@ pub struct ModuleBuilder<'a, 'ink, D: hir::HirDatabase> {
     context: &'ink Context,
     pub(crate) db: &'a mut CodegenContext<'ink, D>,
     file_id: FileId,
+    output_name: RelativePathBuf,
+    dependencies: Vec<String>,
     _target: inkwell::targets::Target,
     target_machine: inkwell::targets::TargetMachine,
     assembly_module: Arc<inkwell::module::Module<'ink>>,
 }
 
 impl<'a, 'ink, D: hir::HirDatabase> ModuleBuilder<'a, 'ink, D> {
-    /// Constructs module for the given `hir::FileId` at the specified output file location.
+    /// Constructs module for the given `hir::FileId` at the specified output file location. All
+    /// files that share a source root with the file are compiled into the same assembly.
     pub fn new(context: &'ink Context, db: &'a mut CodegenContext<'ink, D>, file_id: FileId) -> Result<Self, failure::Error> {
         let target = db.hir_db().target();
 
@@ -124,67 +313,118 @@ impl<'a, 'ink, D: hir::HirDatabase> ModuleBuilder<'a, 'ink, D> {
                 &target_triple,
                 &target.options.cpu,
                 &target.options.features,
-                db.optimization_lvl(),
+                db.optimization_lvl().llvm_level(),
                 RelocMode::PIC,
                 CodeModel::Default,
             )
             .ok_or(CodeGenerationError::CouldNotCreateTargetMachine)?;
 
+        let output_name = db.hir_db().file_relative_path(file_id);
+
         Ok(Self {
             context,
             db,
             file_id,
+            output_name,
+            dependencies: Vec::new(),
             _target: llvm_target,
             target_machine,
             assembly_module,
         })
     }
 
+    /// Sets the name from which the filenames of the generated artifacts are derived, e.g. the
+    /// name of a package. Defaults to the relative path of the file.
+    pub fn with_output_name<P: Into<RelativePathBuf>>(mut self, output_name: P) -> Self {
+        self.output_name = output_name.into();
+        self
+    }
+
+    /// Sets the paths of the assemblies that the assembly depends on. The runtime loads these
+    /// before the assembly itself. Relative paths are resolved relative to the directory that
+    /// contains the assembly.
+    pub fn with_dependencies<I: IntoIterator<Item = String>>(mut self, dependencies: I) -> Self {
+        self.dependencies = dependencies.into_iter().collect();
+        self
+    }
+
     /// Constructs an object file.
-    pub fn build(self) -> Result<ObjectFile, failure::Error> {
+    pub fn build(mut self) -> Result<ObjectFile<'ink>, failure::Error> {
+        let files = file_group::group_files(self.db.hir_db(), self.file_id);
+
+        let (stats, ir_timing) = PassTiming::measure("IR generation", || self.gen_ir(files));
+        let stats = stats?;
+
+        // Optimize the assembly module
+        let ((), optimization_timing) = PassTiming::measure("LLVM optimization", || {
+            optimize_module(&self.assembly_module, self.db.optimization_lvl())
+        });
+
+        // Debug print the IR
+        //println!("{}", assembly_module.print_to_string().to_string());
+
+        let mut obj_file = ObjectFile::new(
+            &self.db.hir_db().target(),
+            self.target_machine,
+            self.output_name,
+            self.assembly_module,
+            self.db.debug_info(),
+        )?;
+        obj_file.stats = stats;
+        obj_file
+            .timings
+            .splice(0..0, vec![ir_timing, optimization_timing]);
+        Ok(obj_file)
+    }
+
+    /// Generates the IR of all files of the assembly and its symbols in the assembly module.
+    fn gen_ir(&mut self, files: Vec<FileId>) -> Result<CodegenStats, failure::Error> {
         let group_ir = self.db.group_ir(self.context, self.file_id);
-        let file = self.db.file_ir(self.context, self.file_id);
 
         // Clone the LLVM modules so that we can modify it without modifying the cached value.
         self.assembly_module
             .link_in_module(group_ir.llvm_module.clone())
             .map_err(|e| CodeGenerationError::ModuleLinkerError(e.to_string()))?;
 
-        self.assembly_module
-            .link_in_module(file.llvm_module.clone())
-            .map_err(|e| CodeGenerationError::ModuleLinkerError(e.to_string()))?;
+        // The files are iterated in a fixed order, so the functions of the assembly are always
+        // emitted in the same order
+        let mut api = Vec::new();
+        let mut stats = CodegenStats::default();
+        for file_id in files {
+            let file = self.db.file_ir(self.context, file_id);
+            self.assembly_module
+                .link_in_module(file.llvm_module.clone())
+                .map_err(|e| CodeGenerationError::ModuleLinkerError(e.to_string()))?;
+            api.extend_from_slice(&file.api);
+            stats.generated_functions += file.generated_functions;
+            stats.reused_functions += file.reused_functions;
+        }
 
         // Generate the `get_info` method.
         symbols::gen_reflection_ir(
             self.context,
             self.db,
             &self.assembly_module,
-            &file.api,
+            &api,
             &group_ir.dispatch_table,
             &group_ir.type_table,
+            &self.dependencies,
         );
 
-        // Optimize the assembly module
-        optimize_module(&self.assembly_module, self.db.optimization_lvl());
-
-        // Debug print the IR
-        //println!("{}", assembly_module.print_to_string().to_string());
-
-        ObjectFile::new(
-            &self.db.hir_db().target(),
-            &self.target_machine,
-            self.db.hir_db().file_relative_path(self.file_id),
-            self.assembly_module,
-        )
+        Ok(stats)
     }
 }
 
-/// Computes the output path for the assembly of the specified file.
-fn assembly_output_path(src_path: &RelativePathBuf, out_dir: Option<&Path>) -> PathBuf {
+/// Computes the output path for an artifact of the specified file.
+fn output_path(
+    src_path: &RelativePathBuf,
+    out_dir: Option<&Path>,
+    output_type: OutputType,
+) -> PathBuf {
     let original_filename = Path::new(src_path.file_name().unwrap());
 
-    // Add the `munlib` suffix to the original filename
-    let output_file_name = original_filename.with_extension("munlib");
+    // Add the suffix of the output type (e.g. `munlib`) to the original filename
+    let output_file_name = original_filename.with_extension(output_type.extension());
 
     // If there is an out dir specified, prepend the output directory
     if let Some(out_dir) = out_dir {
@@ -197,11 +437,11 @@ fn assembly_output_path(src_path: &RelativePathBuf, out_dir: Option<&Path>) -> P
 /// Optimizes the specified LLVM `Module` using the default passes for the given
 /// `OptimizationLevel`.
 fn optimize_module<'ink>(module: &'ink Module, optimization_lvl: OptimizationLevel) {
-    let pass_builder = PassManagerBuilder::create();
-    pass_builder.set_optimization_level(optimization_lvl);
+    let pass_builder = create_pass_manager_builder(optimization_lvl);
 
     let module_pass_manager = PassManager::create(());
     pass_builder.populate_module_pass_manager(&module_pass_manager);
+    add_vectorization_passes(&module_pass_manager, optimization_lvl);
     module_pass_manager.run_on(module);
 }
 
@@ -36,6 +36,11 @@ pub fn create_with_target(target: &spec::Target) -> Box<dyn Linker> {
 
 pub trait Linker {
     fn add_object(&mut self, path: &Path) -> Result<(), LinkerError>;
+    /// Instructs the linker to retain the debug information of the objects. Most linkers store it
+    /// in the shared object, so that the shared object remains self-contained when it is copied to
+    /// another location. On macOS, the objects are stored next to the shared object instead, with
+    /// its filename and an `.o` extension, e.g. `main.munlib.o`.
+    fn keep_debug_info(&mut self);
     fn build_shared_object(&mut self, path: &Path) -> Result<(), LinkerError>;
     fn finalize(&mut self) -> Result<(), LinkerError>;
 }
@@ -62,6 +67,10 @@ impl Linker for LdLinker {
         Ok(())
     }
 
+    fn keep_debug_info(&mut self) {
+        // ELF linkers copy the DWARF sections into the output by default
+    }
+
     fn build_shared_object(&mut self, path: &Path) -> Result<(), LinkerError> {
         let path_str = path
             .to_str()
@@ -70,6 +79,9 @@ impl Linker for LdLinker {
         // Link as dynamic library
         self.args.push("--shared".to_owned());
 
+        // Do not embed a build ID, so that identical inputs produce identical output
+        self.args.push("--build-id=none".to_owned());
+
         // Specify output path
         self.args.push("-o".to_owned());
         self.args.push(path_str.to_owned());
@@ -86,26 +98,33 @@ impl Linker for LdLinker {
 
 struct Ld64Linker {
     args: Vec<String>,
+    objects: Vec<PathBuf>,
+    keep_objects: bool,
 }
 
 impl Ld64Linker {
     fn new(target: &spec::Target) -> Self {
         Ld64Linker {
             args: vec![format!("-arch {}", &target.arch)],
+            objects: Vec::new(),
+            keep_objects: false,
         }
     }
 }
 
 impl Linker for Ld64Linker {
     fn add_object(&mut self, path: &Path) -> Result<(), LinkerError> {
-        let path_str = path
-            .to_str()
-            .ok_or_else(|| LinkerError::PathError(path.to_owned()))?
-            .to_owned();
-        self.args.push(path_str);
+        self.objects.push(path.to_owned());
         Ok(())
     }
 
+    fn keep_debug_info(&mut self) {
+        // The Mach-O linker does not copy the DWARF sections into the output, but only stores a
+        // debug map that refers to the object files. The objects are therefore stored next to the
+        // shared object, where debuggers can find them.
+        self.keep_objects = true;
+    }
+
     fn build_shared_object(&mut self, path: &Path) -> Result<(), LinkerError> {
         let path_str = path
             .to_str()
@@ -117,6 +136,33 @@ impl Linker for Ld64Linker {
             .to_str()
             .ok_or_else(|| LinkerError::PathError(path.to_owned()))?;
 
+        for (index, object) in self.objects.iter().enumerate() {
+            let object = if self.keep_objects {
+                // The kept objects are named after the shared object, so they do not clash with
+                // the object files that are emitted next to it, e.g. with `--emit=obj`
+                let kept_object = if self.objects.len() == 1 {
+                    path.with_file_name(format!("{}.o", filename_str))
+                } else {
+                    path.with_file_name(format!("{}.{}.o", filename_str, index))
+                };
+                std::fs::copy(object, &kept_object).map_err(|e| {
+                    LinkerError::LinkError(format!(
+                        "could not copy object file to {}: {}",
+                        kept_object.display(),
+                        e
+                    ))
+                })?;
+                kept_object
+            } else {
+                object.clone()
+            };
+            let object_str = object
+                .to_str()
+                .ok_or_else(|| LinkerError::PathError(object.clone()))?
+                .to_owned();
+            self.args.push(object_str);
+        }
+
         // Link as dynamic library
         self.args.push("-dylib".to_owned());
         self.args.push("-lsystem".to_owned());
@@ -162,6 +208,12 @@ impl Linker for MsvcLinker {
         Ok(())
     }
 
+    fn keep_debug_info(&mut self) {
+        // Embed the DWARF sections in the DLL instead of writing a PDB next to it. A PDB would be
+        // locked by debuggers, which prevents hot reloading.
+        self.args.push("/DEBUG:DWARF".to_owned());
+    }
+
     fn build_shared_object(&mut self, path: &Path) -> Result<(), LinkerError> {
         let dll_path_str = path
             .to_str()
@@ -173,6 +225,10 @@ impl Linker for MsvcLinker {
 
         self.args.push("/DLL".to_owned());
         self.args.push("/NOENTRY".to_owned());
+
+        // Replace the timestamps in the headers by a hash of the output, so that identical inputs
+        // produce identical output
+        self.args.push("/Brepro".to_owned());
         self.args.push("/EXPORT:get_info".to_owned());
         self.args.push("/EXPORT:set_allocator_handle".to_owned());
         self.args.push(format!("/IMPLIB:{}", dll_lib_path_str));
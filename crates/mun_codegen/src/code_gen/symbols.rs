@@ -1,5 +1,5 @@
 use inkwell::context::Context;
-use crate::code_gen::{gen_global, gen_struct_ptr_array, intern_string};
+use crate::code_gen::{gen_global, gen_string_array, gen_struct_ptr_array, intern_string};
 use crate::ir::{
     abi_types::{gen_abi_types, AbiTypes},
     dispatch_table::{DispatchTable, DispatchableFunction},
@@ -15,7 +15,6 @@ use inkwell::{
     values::{GlobalValue, PointerValue, StructValue},
     AddressSpace,
 };
-use std::collections::HashSet;
 
 /// Construct a `MunFunctionPrototype` struct for the specified HIR function.
 fn gen_prototype_from_function<'a, 'ink: 'a, D: hir::HirDatabase>(
@@ -255,9 +254,10 @@ pub(super) fn gen_reflection_ir<'a, 'ink, 'b, D: hir::HirDatabase>(
     context: &'ink Context,
     db: &mut CodegenContext<'ink, D>,
     module: &'a Module<'ink>,
-    api: &HashSet<hir::Function>,
+    api: &[hir::Function],
     dispatch_table: &DispatchTable,
     type_table: &'b TypeTable<'ink>,
+    dependencies: &[String],
 ) {
     // Get all the types
     let abi_types = gen_abi_types(&context);
@@ -290,7 +290,7 @@ pub(super) fn gen_reflection_ir<'a, 'ink, 'b, D: hir::HirDatabase>(
     let dispatch_table = gen_dispatch_table(context, module, &abi_types, dispatch_table);
 
     // Construct the actual `get_info` function
-    gen_get_info_fn(context, db, module, &abi_types, module_info, dispatch_table);
+    gen_get_info_fn(context, db, module, &abi_types, module_info, dispatch_table, dependencies);
     gen_set_allocator_handle_fn(&context, module);
 }
 
@@ -302,9 +302,9 @@ fn gen_get_info_fn<'a, 'ink, D: hir::HirDatabase>(
     abi_types: &AbiTypes<'ink>,
     module_info: StructValue<'ink>,
     dispatch_table: StructValue<'ink>,
+    dependencies: &[String],
 ) {
     let target = db.hir_db().target();
-    let str_type = context.i8_type().ptr_type(AddressSpace::Const);
 
     // Construct the return type of the `get_info` method. Depending on the C ABI this is either the
     // `MunAssemblyInfo` struct or void. On windows the return argument is passed back to the caller
@@ -366,11 +366,18 @@ fn gen_get_info_fn<'a, 'ink, D: hir::HirDatabase>(
     builder.build_store(dispatch_table_addr, dispatch_table);
     builder.build_store(
         dependencies_addr,
-        str_type.ptr_type(AddressSpace::Const).const_null(),
+        gen_string_array(
+            context,
+            module,
+            dependencies.iter().cloned(),
+            "dependency_path",
+        ),
     );
     builder.build_store(
         num_dependencies_addr,
-        context.i32_type().const_int(0 as u64, false),
+        context
+            .i32_type()
+            .const_int(dependencies.len() as u64, false),
     );
 
     // Construct the return statement of the function.
@@ -6,39 +6,53 @@ use inkwell::context::Context;
 use mun_target::spec::Target;
 use mun_target::abi::TargetDataLayout;
 use crate::{
-    ir::{file::FileIR, file_group::FileGroupIR},
+    ir::{
+        file::{FileIR, FunctionIR},
+        file_group::FileGroupIR,
+    },
     type_info::TypeInfo,
-    CodeGenParams,
+    CodeGenParams, OptimizationLevel,
 };
 use inkwell::{
     targets::TargetData,
     types::{AnyTypeEnum, StructType},
-    OptimizationLevel,
 };
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 #[derive(Debug)]
 pub struct CodegenContext<'ink, D: hir::HirDatabase> {
     optimization_lvl: OptimizationLevel,
+    debug_info: bool,
+    source_dir: Option<PathBuf>,
     hir_db: D,
     target: Target,
     target_data: Arc<TargetData>,
     target_data_layout: TargetDataLayout,
 
-    struct_cache: HashMap<hir::Struct, (Vec<hir::Ty>, StructType<'ink>)>
+    struct_cache: HashMap<hir::Struct, (Vec<hir::Ty>, StructType<'ink>)>,
+
+    /// The IR of the functions of every file, retained to be reused by subsequent compilations
+    function_ir_cache: HashMap<hir::FileId, FunctionIRCache<'ink>>,
 }
 
+/// The IR of the functions of a single file, and the wrappers of these functions.
+pub(crate) type FunctionIRCache<'ink> = HashMap<(hir::Function, CodeGenParams), FunctionIR<'ink>>;
+
 impl<'ink, D: hir::HirDatabase> CodegenContext<'ink, D> {
     pub fn new(hir_db: D) -> CodegenContext<'ink, D> {
         let target = hir_db.target();
         let target_data_layout = hir_db.target_data_layout().as_ref().clone();
         CodegenContext {
             optimization_lvl: OptimizationLevel::None,
+            debug_info: false,
+            source_dir: None,
             target_data: Arc::new(TargetData::create(&target.data_layout)),
             target,
             target_data_layout,
             hir_db,
             struct_cache: HashMap::new(),
+            function_ir_cache: HashMap::new(),
         }
     }
 
@@ -58,6 +72,25 @@ impl<'ink, D: hir::HirDatabase> CodegenContext<'ink, D> {
         self.optimization_lvl = optimization_lvl;
     }
 
+    /// Returns true if DWARF debug information should be generated.
+    pub fn debug_info(&self) -> bool {
+        self.debug_info
+    }
+
+    pub fn set_debug_info(&mut self, debug_info: bool) {
+        self.debug_info = debug_info;
+    }
+
+    /// Returns the directory that contains the source files, used to locate the sources from the
+    /// debug information.
+    pub fn source_dir(&self) -> Option<&Path> {
+        self.source_dir.as_deref()
+    }
+
+    pub fn set_source_dir(&mut self, source_dir: Option<PathBuf>) {
+        self.source_dir = source_dir;
+    }
+
     pub fn target(&self) -> &Target {
         &self.target
     }
@@ -110,9 +143,27 @@ impl<'ink, D: hir::HirDatabase> CodegenContext<'ink, D> {
         crate::ir::file::ir_query(context, self, file)
     }
 
+    /// Removes the IR of the functions of the specified file from the cache.
+    pub(crate) fn take_function_ir(&mut self, file: hir::FileId) -> FunctionIRCache<'ink> {
+        self.function_ir_cache.remove(&file).unwrap_or_default()
+    }
+
+    /// Stores the IR of the functions of the specified file, so it can be reused when the file is
+    /// compiled again.
+    pub(crate) fn set_function_ir(&mut self, file: hir::FileId, cache: FunctionIRCache<'ink>) {
+        self.function_ir_cache.insert(file, cache);
+    }
+
     pub fn type_info(&mut self, context: &'ink Context, ty: hir::Ty) -> TypeInfo {
         crate::ir::ty::type_info_query(context, self, ty)
     }
+
+    /// Returns a textual representation of the type table that is emitted in the assembly of the
+    /// specified file.
+    pub fn type_table_debug_dump(&mut self, context: &'ink Context, file: hir::FileId) -> String {
+        let group_ir = self.group_ir(context, file);
+        group_ir.type_table.debug_dump(context, self)
+    }
 }
 
 // /// The `CodegenContext` enables caching of intermediate in the process of LLVM IR generation. It uses
@@ -87,6 +87,7 @@ pub(crate) fn gen_abi_types(context: &Context) -> AbiTypes {
             str_type.ptr_type(AddressSpace::Const).into(), // field_names
             type_info_ptr_type.ptr_type(AddressSpace::Const).into(), // field_types
             context.i16_type().ptr_type(AddressSpace::Const).into(), // field_offsets
+            str_type.ptr_type(AddressSpace::Const).into(), // field_previous_names
             context.i16_type().into(),                     // num_fields
             context.i8_type().into(),                      // memory_kind
         ],
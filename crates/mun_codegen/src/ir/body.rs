@@ -1,11 +1,14 @@
 use inkwell::context::Context;
 use crate::intrinsics;
 use crate::{
-    ir::{dispatch_table::DispatchTable, try_convert_any_to_basic, type_table::TypeTable},
+    ir::{
+        debug_info::DebugInfo, dispatch_table::DispatchTable, try_convert_any_to_basic,
+        type_table::TypeTable,
+    },
     CodeGenParams, CodegenContext,
 };
 use hir::{
-    ArenaId, ArithOp, BinaryOp, Body, CmpOp, Expr, ExprId, HirDisplay, InferenceResult, Literal,
+    ArenaId, ArithOp, BinaryOp, Body, BodySourceMap, CmpOp, Expr, ExprId, HirDisplay, InferenceResult, Literal,
     LogicOp, Name, Ordering, Pat, PatId, Path, Resolution, Resolver, Statement, TypeCtor, UnaryOp,
 };
 use inkwell::{
@@ -17,6 +20,7 @@ use std::{collections::HashMap, sync::Arc};
 
 use hir::ResolveBitness;
 use inkwell::basic_block::BasicBlock;
+use inkwell::debug_info::{AsDIScope, DILocation, DIScope};
 use inkwell::values::{AggregateValueEnum, GlobalValue, PointerValue};
 
 struct LoopInfo<'ink> {
@@ -27,6 +31,23 @@ struct LoopInfo<'ink> {
     exit_block: BasicBlock<'ink>,
 }
 
+/// The state required to generate debug information for a single function body.
+struct FunctionDebugInfo<'ink, 'b> {
+    info: &'b DebugInfo<'ink>,
+    source_map: Arc<BodySourceMap>,
+    /// The stack of lexical scopes, the innermost scope is last.
+    scopes: Vec<DIScope<'ink>>,
+    /// The debug location attached to the instructions that are currently generated.
+    location: DILocation<'ink>,
+}
+
+impl<'ink, 'b> FunctionDebugInfo<'ink, 'b> {
+    /// Returns the innermost lexical scope.
+    fn scope(&self) -> DIScope<'ink> {
+        *self.scopes.last().expect("a function always has a scope")
+    }
+}
+
 #[derive(Clone)]
 pub(crate) struct ExternalGlobals<'ink> {
     pub alloc_handle: Option<GlobalValue<'ink>>,
@@ -51,9 +72,11 @@ pub(crate) struct BodyIrGenerator<'ink, 'a, 'b, D: hir::HirDatabase> {
     hir_function: hir::Function,
     params: CodeGenParams,
     external_globals: ExternalGlobals<'ink>,
+    debug_info: Option<FunctionDebugInfo<'ink, 'b>>,
 }
 
 impl<'ink, 'a, 'b, D: hir::HirDatabase> BodyIrGenerator<'ink, 'a, 'b, D> {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         context: &'ink Context,
         db: &'a mut CodegenContext<'ink, D>,
@@ -63,6 +86,7 @@ impl<'ink, 'a, 'b, D: hir::HirDatabase> BodyIrGenerator<'ink, 'a, 'b, D> {
         type_table: &'b TypeTable<'ink>,
         params: CodeGenParams,
         external_globals: ExternalGlobals<'ink>,
+        debug_info: Option<&'b DebugInfo<'ink>>,
     ) -> Self {
         let (hir_function, ir_function) = function;
 
@@ -75,6 +99,24 @@ impl<'ink, 'a, 'b, D: hir::HirDatabase> BodyIrGenerator<'ink, 'a, 'b, D> {
         let body_ir = context.append_basic_block(ir_function, "body");
         builder.position_at_end(body_ir);
 
+        // Attach the debug information of the function and start at the function's definition
+        let debug_info = debug_info.map(|info| {
+            let subprogram = info.gen_subprogram(context, db, hir_function, ir_function);
+            let scope = subprogram.as_debug_info_scope();
+            let location = info.location(
+                context,
+                hir_function.source_range(db.hir_db()).range.start(),
+                scope,
+            );
+            builder.set_current_debug_location(context, location);
+            FunctionDebugInfo {
+                info,
+                source_map: hir_function.body_source_map(db.hir_db()),
+                scopes: vec![scope],
+                location,
+            }
+        });
+
         BodyIrGenerator {
             context,
             db,
@@ -92,6 +134,7 @@ impl<'ink, 'a, 'b, D: hir::HirDatabase> BodyIrGenerator<'ink, 'a, 'b, D> {
             hir_function,
             params,
             external_globals,
+            debug_info,
         }
     }
 
@@ -109,6 +152,7 @@ impl<'ink, 'a, 'b, D: hir::HirDatabase> BodyIrGenerator<'ink, 'a, 'b, D> {
                     let builder = self.new_alloca_builder();
                     let param_ptr = builder.build_alloca(param.get_type(), &name);
                     builder.build_store(param_ptr, param);
+                    self.declare_debug_variable(*pat, &name, Some(i as u32 + 1), param_ptr);
                     self.pat_to_local.insert(*pat, param_ptr);
                     self.pat_to_name.insert(*pat, name);
                 }
@@ -202,6 +246,14 @@ impl<'ink, 'a, 'b, D: hir::HirDatabase> BodyIrGenerator<'ink, 'a, 'b, D> {
     /// Generates IR for the specified expression. Dependending on the type of expression an IR
     /// value is returned.
     fn gen_expr(&mut self, expr: ExprId) -> Option<inkwell::values::BasicValueEnum<'ink>> {
+        let parent_location = self.enter_debug_location(expr);
+        let value = self.gen_expr_kind(expr);
+        self.restore_debug_location(parent_location);
+        value
+    }
+
+    /// Generates IR for the specified expression, based on its kind.
+    fn gen_expr_kind(&mut self, expr: ExprId) -> Option<inkwell::values::BasicValueEnum<'ink>> {
         let body = self.body.clone();
         match &body[expr] {
             Expr::Block {
@@ -470,7 +522,19 @@ impl<'ink, 'a, 'b, D: hir::HirDatabase> BodyIrGenerator<'ink, 'a, 'b, D> {
     /// Generates IR for the specified block expression.
     fn gen_block(
         &mut self,
-        _tgt_expr: ExprId,
+        tgt_expr: ExprId,
+        statements: &[Statement],
+        tail: Option<ExprId>,
+    ) -> Option<BasicValueEnum<'ink>> {
+        self.push_debug_scope(tgt_expr);
+        let value = self.gen_block_statements(statements, tail);
+        self.pop_debug_scope();
+        value
+    }
+
+    /// Generates IR for the statements and the tail expression of a block.
+    fn gen_block_statements(
+        &mut self,
         statements: &[Statement],
         tail: Option<ExprId>,
     ) -> Option<BasicValueEnum<'ink>> {
@@ -507,6 +571,75 @@ impl<'ink, 'a, 'b, D: hir::HirDatabase> BodyIrGenerator<'ink, 'a, 'b, D> {
         temp_builder
     }
 
+    /// Sets the debug location of subsequently generated instructions to the start of `expr`.
+    /// Returns the previous debug location, which should be restored after the expression has
+    /// been generated.
+    fn enter_debug_location(&mut self, expr: ExprId) -> Option<DILocation<'ink>> {
+        let debug_info = self.debug_info.as_mut()?;
+        let range = debug_info.source_map.expr_range(expr)?;
+        let location = debug_info
+            .info
+            .location(self.context, range.start(), debug_info.scope());
+        self.builder
+            .set_current_debug_location(self.context, location);
+        Some(std::mem::replace(&mut debug_info.location, location))
+    }
+
+    /// Restores a debug location returned by `enter_debug_location`.
+    fn restore_debug_location(&mut self, location: Option<DILocation<'ink>>) {
+        if let (Some(debug_info), Some(location)) = (self.debug_info.as_mut(), location) {
+            self.builder
+                .set_current_debug_location(self.context, location);
+            debug_info.location = location;
+        }
+    }
+
+    /// Opens a lexical scope for the block expression `expr`. Every call must be matched with a
+    /// call to `pop_debug_scope`.
+    fn push_debug_scope(&mut self, expr: ExprId) {
+        if let Some(debug_info) = self.debug_info.as_mut() {
+            let scope = match debug_info.source_map.expr_range(expr) {
+                Some(range) => debug_info
+                    .info
+                    .lexical_block(debug_info.scope(), range.start()),
+                None => debug_info.scope(),
+            };
+            debug_info.scopes.push(scope);
+        }
+    }
+
+    /// Closes the innermost lexical scope.
+    fn pop_debug_scope(&mut self) {
+        if let Some(debug_info) = self.debug_info.as_mut() {
+            debug_info.scopes.pop();
+        }
+    }
+
+    /// Emits the debug information of a local variable or parameter that is bound by `pat` and
+    /// stored at `storage`.
+    fn declare_debug_variable(
+        &mut self,
+        pat: PatId,
+        name: &str,
+        arg_no: Option<u32>,
+        storage: PointerValue<'ink>,
+    ) {
+        if let Some(debug_info) = self.debug_info.as_ref() {
+            if let Some(range) = debug_info.source_map.pat_range(pat) {
+                debug_info.info.declare_variable(
+                    self.context,
+                    self.db,
+                    debug_info.scope(),
+                    name,
+                    arg_no,
+                    self.infer[pat].clone(),
+                    range.start(),
+                    storage,
+                );
+            }
+        }
+    }
+
     /// Generate IR for a let statement: `let a:int = 3`
     fn gen_let_statement(&mut self, pat: PatId, initializer: Option<ExprId>) {
         let initializer = initializer.and_then(|expr| self.gen_expr(expr));
@@ -524,6 +657,7 @@ impl<'ink, 'a, 'b, D: hir::HirDatabase> BodyIrGenerator<'ink, 'a, 'b, D> {
                 ))
                 .expect("expected basic type");
                 let ptr = builder.build_alloca(ty, &name.to_string());
+                self.declare_debug_variable(pat, &name.to_string(), None, ptr);
                 self.pat_to_local.insert(pat, ptr);
                 self.pat_to_name.insert(pat, name.to_string());
                 if !(pat_ty.is_empty() || pat_ty.is_never()) {
@@ -1032,7 +1166,10 @@ impl<'ink, 'a, 'b, D: hir::HirDatabase> BodyIrGenerator<'ink, 'a, 'b, D> {
 
     /// Generates IR for a function call.
     fn gen_call(&mut self, function: hir::Function, args: &[BasicValueEnum<'ink>]) -> CallSiteValue<'ink> {
-        if self.dispatch_table.contains(function) && self.should_use_dispatch_table() {
+        // Functions that are not defined in this file, i.e. the functions of the other files of
+        // the assembly and of other assemblies, can only be called through the dispatch table.
+        let is_local = self.function_map.contains_key(&function);
+        if self.dispatch_table.contains(function) && (self.should_use_dispatch_table() || !is_local) {
             let ptr_value = self.dispatch_table.gen_function_lookup(
                 self.db,
                 self.external_globals.dispatch_table,
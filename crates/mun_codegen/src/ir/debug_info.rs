@@ -0,0 +1,441 @@
+//! Generates DWARF debug information for the IR of a file. Debug information maps the generated
+//! machine code back to the Mun source code, which enables debuggers to set breakpoints, step
+//! through code, and inspect local variables.
+
+use crate::{ir::try_convert_any_to_basic, CodeGenParams, CodegenContext};
+use hir::{line_index::LineIndex, FileId, HirDisplay, Signedness, TextUnit, Ty, TypeCtor};
+use inkwell::{
+    basic_block::BasicBlock,
+    context::Context,
+    debug_info::{
+        AsDIScope, DIBasicType, DICompileUnit, DIFile, DIFlags, DIFlagsConstants, DILocation,
+        DIScope, DISubprogram, DIType, DWARFEmissionKind, DWARFSourceLanguage, DebugInfoBuilder,
+    },
+    module::{FlagBehavior, Module},
+    types::BasicTypeEnum,
+    values::{FunctionValue, InstructionValue, PointerValue},
+    AddressSpace,
+};
+use std::{cell::RefCell, collections::HashMap, path::Path, sync::Arc};
+
+/// The version of the debug metadata that is emitted.
+const DEBUG_METADATA_VERSION: u64 = 3;
+
+/// The DWARF version that is emitted.
+const DWARF_VERSION: u64 = 4;
+
+/// DWARF type encodings as defined by the DWARF standard (`DW_ATE_*`).
+const DW_ATE_BOOLEAN: u32 = 0x02;
+const DW_ATE_FLOAT: u32 = 0x04;
+const DW_ATE_SIGNED: u32 = 0x05;
+const DW_ATE_UNSIGNED: u32 = 0x08;
+
+/// The debug information builder for a single file.
+pub(crate) struct DebugInfo<'ink> {
+    builder: DebugInfoBuilder<'ink>,
+    compile_unit: DICompileUnit<'ink>,
+    line_index: Arc<LineIndex>,
+    types: RefCell<HashMap<Ty, Option<DIType<'ink>>>>,
+}
+
+impl<'ink> DebugInfo<'ink> {
+    /// Constructs the debug information builder for the specified file and adds the required
+    /// module flags to `module`.
+    pub fn new<D: hir::HirDatabase>(
+        context: &'ink Context,
+        db: &CodegenContext<'ink, D>,
+        module: &Module<'ink>,
+        file_id: FileId,
+    ) -> Self {
+        let i32_type = context.i32_type();
+        module.add_basic_value_flag(
+            "Debug Info Version",
+            FlagBehavior::Warning,
+            i32_type.const_int(DEBUG_METADATA_VERSION, false),
+        );
+        module.add_basic_value_flag(
+            "Dwarf Version",
+            FlagBehavior::Warning,
+            i32_type.const_int(DWARF_VERSION, false),
+        );
+
+        let filename = db.hir_db().file_relative_path(file_id);
+        let directory = db
+            .source_dir()
+            .and_then(Path::to_str)
+            .unwrap_or_default()
+            .to_owned();
+        let (builder, compile_unit) = module.create_debug_info_builder(
+            true,
+            DWARFSourceLanguage::C,
+            filename.as_str(),
+            &directory,
+            concat!("mun ", env!("CARGO_PKG_VERSION")),
+            db.optimization_lvl() != crate::OptimizationLevel::None,
+            "",
+            0,
+            "",
+            DWARFEmissionKind::Full,
+            0,
+            false,
+            false,
+        );
+
+        DebugInfo {
+            builder,
+            compile_unit,
+            line_index: db.hir_db().line_index(file_id),
+            types: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the file that contains all the debug information.
+    pub fn file(&self) -> DIFile<'ink> {
+        self.compile_unit.get_file()
+    }
+
+    /// Returns the one-based line and column of the specified offset in the file.
+    pub fn line_col(&self, offset: TextUnit) -> (u32, u32) {
+        let line_col = self.line_index.line_col(offset);
+        (line_col.line + 1, line_col.col + 1)
+    }
+
+    /// Constructs a debug location at the specified offset in the file.
+    pub fn location(
+        &self,
+        context: &'ink Context,
+        offset: TextUnit,
+        scope: DIScope<'ink>,
+    ) -> DILocation<'ink> {
+        let (line, column) = self.line_col(offset);
+        self.builder
+            .create_debug_location(context, line, column, scope, None)
+    }
+
+    /// Constructs a lexical block, e.g. for a block expression, at the specified offset.
+    pub fn lexical_block(&self, parent: DIScope<'ink>, offset: TextUnit) -> DIScope<'ink> {
+        let (line, column) = self.line_col(offset);
+        self.builder
+            .create_lexical_block(parent, self.file(), line, column)
+            .as_debug_info_scope()
+    }
+
+    /// Constructs the debug information of a function and attaches it to `fn_value`.
+    pub fn gen_subprogram<D: hir::HirDatabase>(
+        &self,
+        context: &'ink Context,
+        db: &mut CodegenContext<'ink, D>,
+        hir_function: hir::Function,
+        fn_value: FunctionValue<'ink>,
+    ) -> DISubprogram<'ink> {
+        let fn_sig = hir_function
+            .ty(db.hir_db())
+            .callable_sig(db.hir_db())
+            .unwrap();
+        let return_type = self.ty(context, db, fn_sig.ret().clone());
+        let param_types = fn_sig
+            .params()
+            .iter()
+            .filter_map(|ty| self.ty(context, db, ty.clone()))
+            .collect::<Vec<_>>();
+        let subroutine_type = self.builder.create_subroutine_type(
+            self.file(),
+            return_type,
+            &param_types,
+            DIFlags::ZERO,
+        );
+
+        let (line, _) = self.line_col(hir_function.source_range(db.hir_db()).range.start());
+        // The symbols of private functions are prefixed with the path of their file, so they are
+        // described by their linkage name.
+        let symbol_name = fn_value.get_name().to_string_lossy();
+        let (name, linkage_name, flags) =
+            if hir_function.visibility(db.hir_db()) == hir::Visibility::Private {
+                (
+                    hir_function.name(db.hir_db()).to_string(),
+                    Some(&*symbol_name),
+                    DIFlags::PRIVATE,
+                )
+            } else {
+                (symbol_name.to_string(), None, DIFlags::PUBLIC)
+            };
+        let subprogram = self.builder.create_function(
+            self.compile_unit.as_debug_info_scope(),
+            &name,
+            linkage_name,
+            self.file(),
+            line,
+            subroutine_type,
+            hir_function.visibility(db.hir_db()) == hir::Visibility::Private,
+            true,
+            line,
+            flags,
+            db.optimization_lvl() != crate::OptimizationLevel::None,
+        );
+        fn_value.set_subprogram(subprogram);
+        subprogram
+    }
+
+    /// Declares a local variable or parameter that is stored at `storage`. The declaration is
+    /// inserted right after the `alloca` instruction of the storage. If `arg_no` is specified the
+    /// variable is declared as the `arg_no`-th (one-based) parameter of the function.
+    #[allow(clippy::too_many_arguments)]
+    pub fn declare_variable<D: hir::HirDatabase>(
+        &self,
+        context: &'ink Context,
+        db: &mut CodegenContext<'ink, D>,
+        scope: DIScope<'ink>,
+        name: &str,
+        arg_no: Option<u32>,
+        ty: Ty,
+        offset: TextUnit,
+        storage: PointerValue<'ink>,
+    ) {
+        let di_type = match self.ty(context, db, ty) {
+            Some(di_type) => di_type,
+            None => return,
+        };
+
+        let (line, _) = self.line_col(offset);
+        let variable = match arg_no {
+            Some(arg_no) => self.builder.create_parameter_variable(
+                scope,
+                name,
+                arg_no,
+                self.file(),
+                line,
+                di_type,
+                true,
+                DIFlags::ZERO,
+            ),
+            None => self.builder.create_auto_variable(
+                scope,
+                name,
+                self.file(),
+                line,
+                di_type,
+                true,
+                DIFlags::ZERO,
+                0,
+            ),
+        };
+
+        let location = self.location(context, offset, scope);
+        let alloca = storage
+            .as_instruction()
+            .expect("storage of a variable must be an alloca");
+        match alloca.get_next_instruction() {
+            Some(next) => {
+                self.builder.insert_declare_before_instruction(
+                    storage,
+                    Some(variable),
+                    None,
+                    location,
+                    next,
+                );
+            }
+            None => {
+                self.builder.insert_declare_at_end(
+                    storage,
+                    Some(variable),
+                    None,
+                    location,
+                    alloca_block(alloca),
+                );
+            }
+        }
+    }
+
+    /// Returns the debug type of the specified type, or `None` if the type has no runtime
+    /// representation (e.g. the empty type).
+    pub fn ty<D: hir::HirDatabase>(
+        &self,
+        context: &'ink Context,
+        db: &mut CodegenContext<'ink, D>,
+        ty: Ty,
+    ) -> Option<DIType<'ink>> {
+        if let Some(di_type) = self.types.borrow().get(&ty) {
+            return *di_type;
+        }
+
+        let di_type = self.gen_ty(context, db, ty.clone());
+        self.types.borrow_mut().insert(ty, di_type);
+        di_type
+    }
+
+    fn gen_ty<D: hir::HirDatabase>(
+        &self,
+        context: &'ink Context,
+        db: &mut CodegenContext<'ink, D>,
+        ty: Ty,
+    ) -> Option<DIType<'ink>> {
+        let encoding = match &ty {
+            Ty::Apply(app) => match app.ctor {
+                TypeCtor::Bool => DW_ATE_BOOLEAN,
+                TypeCtor::Float(_) => DW_ATE_FLOAT,
+                TypeCtor::Int(ity) if ity.signedness == Signedness::Signed => DW_ATE_SIGNED,
+                TypeCtor::Int(_) => DW_ATE_UNSIGNED,
+                TypeCtor::Struct(s) => return Some(self.gen_struct_ty(context, db, s)),
+                _ => return None,
+            },
+            _ => return None,
+        };
+
+        let name = ty.display(db.hir_db()).to_string();
+        let ir_type = basic_ir_type(context, db, ty);
+        let size_in_bits = db.target_data().get_store_size(&ir_type) * 8;
+        self.builder
+            .create_basic_type(&name, size_in_bits, encoding, DIFlags::ZERO)
+            .ok()
+            .map(DIBasicType::as_type)
+    }
+
+    /// Generates the debug type of a struct. Garbage collected structs are represented as a
+    /// pointer to a pointer to the struct, the same way they are represented in the IR.
+    fn gen_struct_ty<D: hir::HirDatabase>(
+        &self,
+        context: &'ink Context,
+        db: &mut CodegenContext<'ink, D>,
+        hir_struct: hir::Struct,
+    ) -> DIType<'ink> {
+        let name = hir_struct.name(db.hir_db()).to_string();
+        let (line, _) = self.line_col(hir_struct.source_range(db.hir_db()).range.start());
+        let struct_ir_type = db.struct_ty(context, hir_struct);
+        let target_data = db.target_data();
+        let scope = self.compile_unit.as_debug_info_scope();
+
+        // Insert a forward declaration to support recursive structs
+        let forward_decl = self
+            .builder
+            .create_struct_type(
+                scope,
+                &name,
+                self.file(),
+                line,
+                0,
+                0,
+                DIFlags::FWD_DECL,
+                None,
+                &[],
+                0,
+                None,
+                &name,
+            )
+            .as_type();
+        let struct_ty = hir_struct.ty(db.hir_db());
+        let forward_decl = self.wrap_struct_ty(db, hir_struct, forward_decl);
+        self.types
+            .borrow_mut()
+            .insert(struct_ty.clone(), Some(forward_decl));
+
+        let fields = hir_struct.fields(db.hir_db());
+        let elements = fields
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, field)| {
+                let field_ty = field.ty(db.hir_db());
+                let di_type = self.ty(context, db, field_ty.clone())?;
+                let ir_type = basic_ir_type(context, db, field_ty);
+                Some(
+                    self.builder
+                        .create_member_type(
+                            scope,
+                            &field.name(db.hir_db()).to_string(),
+                            self.file(),
+                            line,
+                            target_data.get_bit_size(&ir_type),
+                            target_data.get_abi_alignment(&ir_type) * 8,
+                            target_data.offset_of_element(&struct_ir_type, idx as u32)? * 8,
+                            DIFlags::PUBLIC,
+                            di_type,
+                        )
+                        .as_type(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let di_type = self
+            .builder
+            .create_struct_type(
+                scope,
+                &name,
+                self.file(),
+                line,
+                target_data.get_bit_size(&struct_ir_type),
+                target_data.get_abi_alignment(&struct_ir_type) * 8,
+                DIFlags::PUBLIC,
+                None,
+                &elements,
+                0,
+                None,
+                &name,
+            )
+            .as_type();
+        let di_type = self.wrap_struct_ty(db, hir_struct, di_type);
+        self.types.borrow_mut().insert(struct_ty, Some(di_type));
+        di_type
+    }
+
+    /// Wraps the debug type of a garbage collected struct in the pointers that make up its handle.
+    fn wrap_struct_ty<D: hir::HirDatabase>(
+        &self,
+        db: &CodegenContext<'ink, D>,
+        hir_struct: hir::Struct,
+        di_type: DIType<'ink>,
+    ) -> DIType<'ink> {
+        if hir_struct.data(db.hir_db()).memory_kind != hir::StructMemoryKind::GC {
+            return di_type;
+        }
+
+        let pointer_size = u64::from(db.target_data().get_pointer_byte_size(None)) * 8;
+        let pointer_align = pointer_size as u32;
+        let name = hir_struct.name(db.hir_db()).to_string();
+        let ptr = self
+            .builder
+            .create_pointer_type(
+                &format!("*{}", name),
+                di_type,
+                pointer_size,
+                pointer_align,
+                AddressSpace::Generic,
+            )
+            .as_type();
+        self.builder
+            .create_pointer_type(
+                &format!("**{}", name),
+                ptr,
+                pointer_size,
+                pointer_align,
+                AddressSpace::Generic,
+            )
+            .as_type()
+    }
+
+    /// Resolves all temporary debug information. Must be called before the module is verified.
+    pub fn finalize(&self) {
+        self.builder.finalize();
+    }
+}
+
+/// Returns the basic block that contains the specified instruction.
+fn alloca_block(instruction: InstructionValue) -> BasicBlock {
+    instruction
+        .get_parent()
+        .expect("an alloca must be part of a basic block")
+}
+
+/// Returns the IR type of the specified type as used in function bodies.
+fn basic_ir_type<'ink, D: hir::HirDatabase>(
+    context: &'ink Context,
+    db: &mut CodegenContext<'ink, D>,
+    ty: Ty,
+) -> BasicTypeEnum<'ink> {
+    try_convert_any_to_basic(db.type_ir(
+        context,
+        ty,
+        CodeGenParams {
+            make_marshallable: false,
+        },
+    ))
+    .expect("expected basic type")
+}
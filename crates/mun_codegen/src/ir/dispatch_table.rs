@@ -148,9 +148,22 @@ impl<'ink> DispatchTable<'ink> {
     }
 }
 
+/// Returns true if the body of `function` is not part of the assembly built from `source_root`,
+/// either because it is an `extern` function or because it is defined by a dependency. Calls to
+/// such functions are always routed through the dispatch table, which is filled in by the runtime.
+pub(crate) fn is_external_fn<D: hir::HirDatabase>(
+    db: &D,
+    function: hir::Function,
+    source_root: hir::SourceRootId,
+) -> bool {
+    function.is_extern(db) || db.file_source_root(function.module(db).file_id()) != source_root
+}
+
 /// A struct that can be used to build the dispatch table from HIR.
 pub(crate) struct DispatchTableBuilder<'ink, 'a> {
     context: &'ink Context,
+    // The source root of the assembly for which the dispatch table is built
+    source_root: hir::SourceRootId,
     // The module in which all values live
     module: &'a Module<'ink>,
     // The target for which to create the dispatch table
@@ -179,9 +192,11 @@ impl<'ink, 'a> DispatchTableBuilder<'ink, 'a> {
         db: &mut CodegenContext<'ink, D>,
         module: &'a Module<'ink>,
         intrinsics: &BTreeMap<FunctionPrototype, FunctionType<'ink>>,
+        source_root: hir::SourceRootId,
     ) -> Self {
         let mut table = DispatchTableBuilder {
             context,
+            source_root,
             target: db.target_data(),
             module,
             function_to_idx: Default::default(),
@@ -221,21 +236,29 @@ impl<'ink, 'a> DispatchTableBuilder<'ink, 'a> {
         }
     }
 
-    /// Collects call expression from the given expression and sub expressions.
-    fn collect_expr<D: hir::HirDatabase>(&mut self, db: &mut CodegenContext<'ink, D>, expr_id: ExprId, body: &Arc<Body>, infer: &InferenceResult) {
+    /// Collects call expression from the given expression and sub expressions. If `local_file` is
+    /// specified, calls to functions that are defined in that file are not collected.
+    fn collect_expr<D: hir::HirDatabase>(&mut self, db: &mut CodegenContext<'ink, D>, expr_id: ExprId, body: &Arc<Body>, infer: &InferenceResult, local_file: Option<hir::FileId>) {
         let expr = &body[expr_id];
 
         // If this expression is a call, store it in the dispatch table
         if let Expr::Call { callee, .. } = expr {
             match infer[*callee].as_callable_def() {
-                Some(hir::CallableDef::Function(def)) => self.collect_fn_def(db, def),
+                Some(hir::CallableDef::Function(def)) => {
+                    let is_local = local_file.map_or(false, |file_id| {
+                        !def.is_extern(db.hir_db()) && def.module(db.hir_db()).file_id() == file_id
+                    });
+                    if !is_local {
+                        self.collect_fn_def(db, def)
+                    }
+                }
                 Some(hir::CallableDef::Struct(_)) => (),
                 None => panic!("expected a callable expression"),
             }
         }
 
         // Recurse further
-        expr.walk_child_exprs(|expr_id| self.collect_expr(db, expr_id, body, infer))
+        expr.walk_child_exprs(|expr_id| self.collect_expr(db, expr_id, body, infer, local_file))
     }
 
     /// Collects function call expression from the given expression.
@@ -289,7 +312,15 @@ impl<'ink, 'a> DispatchTableBuilder<'ink, 'a> {
     /// Collect all the call expressions from the specified body with the given type inference
     /// result.
     pub fn collect_body<D: hir::HirDatabase>(&mut self, db: &mut CodegenContext<'ink, D>, body: &Arc<Body>, infer: &InferenceResult) {
-        self.collect_expr(db, body.body_expr(), body, infer);
+        self.collect_expr(db, body.body_expr(), body, infer, None);
+    }
+
+    /// Collect only the calls to functions that are not defined in `file_id` from the specified
+    /// body. Functions that are not exposed call the functions of their own file directly, but
+    /// the functions of other files and external functions can only be reached through the
+    /// dispatch table.
+    pub fn collect_non_local_calls<D: hir::HirDatabase>(&mut self, db: &mut CodegenContext<'ink, D>, file_id: hir::FileId, body: &Arc<Body>, infer: &InferenceResult) {
+        self.collect_expr(db, body.body_expr(), body, infer, Some(file_id));
     }
 
     /// Builds the final DispatchTable with all *called* functions from within the module
@@ -319,7 +350,7 @@ impl<'ink, 'a> DispatchTableBuilder<'ink, 'a> {
                     match entry.function.hir {
                         // Case external function: Convert to typed null for the given function
                         None => function_type.const_null(),
-                        Some(f) if f.is_extern(db.hir_db()) => function_type.const_null(),
+                        Some(f) if is_external_fn(db.hir_db(), f, self.source_root) => function_type.const_null(),
                         // Case mun function: Get the function location as the initializer
                         Some(f) => function::gen_signature(
                             self.context,
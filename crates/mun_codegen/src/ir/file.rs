@@ -1,10 +1,20 @@
-use inkwell::context::Context;
 use super::body::ExternalGlobals;
+use super::debug_info::DebugInfo;
+use super::dispatch_table::FunctionPrototype;
+use super::file_group::FileGroupIR;
+use super::intrinsics::{self, IntrinsicsMap};
 use crate::ir::{function, type_table::TypeTable};
-use crate::{CodeGenParams, CodegenContext};
-use hir::{FileId, ModuleDef};
+use crate::type_info::TypeInfo;
+use crate::{CodeGenParams, CodegenContext, OptimizationLevel};
+use hir::{
+    line_index::LineCol, Body, Expr, FileId, FnSig, InferenceResult, ModuleDef, Name,
+    RelativePathBuf,
+};
+use inkwell::context::Context;
 use inkwell::module::Module;
-use std::collections::{BTreeMap, HashMap, HashSet};
+use inkwell::values::FunctionValue;
+use std::collections::HashMap;
+use std::path::PathBuf;
 use std::sync::Arc;
 
 /// The IR generated for a single source file.
@@ -14,50 +24,417 @@ pub struct FileIR<'ink> {
     pub file_id: FileId,
     /// The LLVM module that contains the IR
     pub llvm_module: Module<'ink>,
-    /// The `hir::Function`s that constitute the file's API.
-    pub api: HashSet<hir::Function>,
+    /// The `hir::Function`s that constitute the file's API, in order of definition.
+    pub api: Vec<hir::Function>,
+    /// The number of functions for which IR was generated
+    pub generated_functions: usize,
+    /// The number of functions whose IR was reused from a previous compilation
+    pub reused_functions: usize,
+}
+
+/// The IR of a single function (or the wrapper of a function), generated in a module of its own so
+/// it can be reused by subsequent compilations.
+#[derive(Debug)]
+pub(crate) struct FunctionIR<'ink> {
+    /// The inputs from which the IR was generated
+    inputs: FunctionInputs,
+    /// The LLVM module that contains the function and declarations of everything it references
+    llvm_module: Module<'ink>,
+}
+
+/// The inputs from which the IR of a function is generated. As long as these are unchanged, the
+/// previously generated IR of the function is still valid.
+#[derive(Debug, PartialEq, Eq)]
+struct FunctionInputs {
+    name: Name,
+    body: Arc<Body>,
+    infer: Arc<InferenceResult>,
+    layout: Arc<FileLayout>,
+    /// The signatures of the function and of the functions it calls
+    signatures: Vec<(Name, FnSig)>,
+    /// The memory kinds and fields of the structs that the function uses
+    structs: Vec<(Name, hir::StructMemoryKind, Vec<(Name, hir::Ty)>)>,
+    /// The entries of the dispatch table that the function uses, and their indices
+    dispatch_table: Vec<(usize, FunctionPrototype)>,
+    /// The entries of the type table that the function uses, and their indices
+    type_table: Vec<(usize, TypeInfo)>,
+    /// The source text of the function and the positions of the function and of the structs it
+    /// uses, which determine the locations in its debug information. Only set if debug
+    /// information is generated.
+    debug_source: Option<(String, Vec<LineCol>)>,
+}
+
+impl FunctionInputs {
+    fn new<'ink, D: hir::HirDatabase>(
+        context: &'ink Context,
+        db: &mut CodegenContext<'ink, D>,
+        group_ir: &FileGroupIR<'ink>,
+        layout: &Arc<FileLayout>,
+        hir_function: hir::Function,
+        params: &CodeGenParams,
+        callees: &[hir::Function],
+    ) -> Self {
+        let body = hir_function.body(db.hir_db());
+        let infer = hir_function.infer(db.hir_db());
+
+        let signatures = std::iter::once(hir_function)
+            .chain(callees.iter().cloned())
+            .map(|f| {
+                let hir_db = db.hir_db();
+                (f.name(hir_db), f.ty(hir_db).callable_sig(hir_db).unwrap())
+            })
+            .collect::<Vec<_>>();
+
+        // The structs whose layout is used by the function, including the structs that are nested
+        // in them.
+        let mut structs = Vec::new();
+        let types = body
+            .exprs()
+            .map(|(expr_id, _)| &infer[expr_id])
+            .chain(body.pats().map(|(pat_id, _)| &infer[pat_id]))
+            .chain(
+                signatures
+                    .iter()
+                    .flat_map(|(_, sig)| sig.params().iter().chain(std::iter::once(sig.ret()))),
+            );
+        for ty in types {
+            collect_structs(db.hir_db(), ty, &mut structs);
+        }
+
+        let mut used_intrinsics = IntrinsicsMap::new();
+        let mut needs_alloc = false;
+        if params.make_marshallable {
+            intrinsics::collect_wrapper_body(context, db, &mut used_intrinsics, &mut needs_alloc);
+        } else {
+            intrinsics::collect_fn_body(
+                context,
+                db,
+                &mut used_intrinsics,
+                &mut needs_alloc,
+                &body,
+                &infer,
+            );
+        }
+        let dispatch_table = group_ir
+            .dispatch_table
+            .entries()
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| match entry.hir {
+                Some(f) => callees.contains(&f),
+                None => used_intrinsics.contains_key(&entry.prototype),
+            })
+            .map(|(index, entry)| (index, entry.prototype.clone()))
+            .collect();
+
+        let type_infos = structs
+            .iter()
+            .map(|s| {
+                let ty = s.ty(db.hir_db());
+                db.type_info(context, ty)
+            })
+            .collect::<Vec<_>>();
+        let type_table = group_ir
+            .type_table
+            .type_infos()
+            .enumerate()
+            .filter(|(_, type_info)| type_infos.contains(type_info))
+            .map(|(index, type_info)| (index, type_info.clone()))
+            .collect();
+
+        let hir_db = db.hir_db();
+        let debug_source = if db.debug_info() {
+            let range = hir_function.source_range(hir_db);
+            let line_index = hir_db.line_index(range.file_id);
+            let text = hir_db.file_text(range.file_id);
+            let positions = std::iter::once(range.range.start())
+                .chain(structs.iter().map(|s| s.source_range(hir_db).range.start()))
+                .map(|offset| line_index.line_col(offset))
+                .collect();
+            Some((
+                text[range.range.start().to_usize()..range.range.end().to_usize()].to_owned(),
+                positions,
+            ))
+        } else {
+            None
+        };
+
+        FunctionInputs {
+            name: hir_function.name(hir_db),
+            body,
+            infer,
+            layout: layout.clone(),
+            signatures,
+            structs: structs
+                .into_iter()
+                .map(|s| {
+                    (
+                        s.name(hir_db),
+                        s.data(hir_db).memory_kind.clone(),
+                        s.fields(hir_db)
+                            .into_iter()
+                            .map(|field| (field.name(hir_db), field.ty(hir_db)))
+                            .collect(),
+                    )
+                })
+                .collect(),
+            dispatch_table,
+            type_table,
+            debug_source,
+        }
+    }
 }
 
-/// Generates IR for the specified file.
-pub(crate) fn ir_query<'a, 'ink, D: hir::HirDatabase>(context: &'ink Context, db: &'a mut CodegenContext<'ink, D>, file_id: FileId) -> Arc<FileIR<'ink>> {
-    let llvm_module = context
-        .create_module(db.hir_db().file_relative_path(file_id).as_str());
+/// Adds the structs that are referenced by `ty`, and the structs that are nested in those, to
+/// `structs`.
+fn collect_structs<D: hir::HirDatabase>(db: &D, ty: &hir::Ty, structs: &mut Vec<hir::Struct>) {
+    if let hir::Ty::Apply(app) = ty {
+        if let hir::TypeCtor::Struct(s) = app.ctor {
+            if !structs.contains(&s) {
+                structs.push(s);
+                for field in s.fields(db) {
+                    collect_structs(db, &field.ty(db), structs);
+                }
+            }
+        }
+        for ty in app.parameters.iter() {
+            collect_structs(db, ty, structs);
+        }
+    }
+}
+
+/// Returns the functions that are called by the body of `hir_function`, in order of their first
+/// call.
+fn called_functions<D: hir::HirDatabase>(
+    db: &D,
+    hir_function: hir::Function,
+) -> Vec<hir::Function> {
+    let body = hir_function.body(db);
+    let infer = hir_function.infer(db);
+    let mut callees = Vec::new();
+    for (_, expr) in body.exprs() {
+        if let Expr::Call { callee, .. } = expr {
+            if let Some(hir::CallableDef::Function(f)) = infer[*callee].as_callable_def() {
+                if !callees.contains(&f) {
+                    callees.push(f);
+                }
+            }
+        }
+    }
+    callees
+}
+
+/// The properties of a file that affect the IR of all functions in the file.
+#[derive(Debug, PartialEq, Eq)]
+struct FileLayout {
+    file_path: RelativePathBuf,
+    optimization_lvl: OptimizationLevel,
+    debug_info: bool,
+    source_dir: Option<PathBuf>,
+}
+
+impl FileLayout {
+    fn new<D: hir::HirDatabase>(db: &CodegenContext<D>, file_id: FileId) -> Self {
+        FileLayout {
+            file_path: db.hir_db().file_relative_path(file_id),
+            optimization_lvl: db.optimization_lvl(),
+            debug_info: db.debug_info(),
+            source_dir: db.source_dir().map(ToOwned::to_owned),
+        }
+    }
+}
+
+/// Generates IR for the specified file. The IR of functions whose inputs did not change since the
+/// previous compilation of the file is reused.
+pub(crate) fn ir_query<'a, 'ink, D: hir::HirDatabase>(
+    context: &'ink Context,
+    db: &'a mut CodegenContext<'ink, D>,
+    file_id: FileId,
+) -> Arc<FileIR<'ink>> {
+    let llvm_module = context.create_module(db.hir_db().file_relative_path(file_id).as_str());
 
     let group_ir = db.group_ir(context, file_id);
 
-    // Generate all exposed function and wrapper function signatures.
-    // Use a `BTreeMap` to guarantee deterministically ordered output.ures
-    let mut functions = HashMap::new();
-    let mut wrapper_functions = BTreeMap::new();
+    // Collect all exposed functions and the functions that require a wrapper, in order of
+    // definition to guarantee deterministically ordered output.
+    let mut functions = Vec::new();
+    let mut wrapper_functions = Vec::new();
     for def in db.hir_db().module_data(file_id).definitions() {
         if let ModuleDef::Function(f) = def {
             if !f.is_extern(db.hir_db()) {
-                let fun = function::gen_signature(
-                    context,
-                    db,
-                    *f,
-                    &llvm_module,
-                    CodeGenParams {
-                        make_marshallable: false,
-                    },
-                );
-                functions.insert(*f, fun);
+                functions.push(*f);
 
                 let fn_sig = f.ty(db.hir_db()).callable_sig(db.hir_db()).unwrap();
-                if !f.data(db.hir_db()).visibility().is_private() && !fn_sig.marshallable(db.hir_db()) {
-                    let wrapper_fun = function::gen_signature(
+                if !f.data(db.hir_db()).visibility().is_private()
+                    && !fn_sig.marshallable(db.hir_db())
+                {
+                    wrapper_functions.push(*f);
+                }
+            }
+        }
+    }
+
+    let layout = Arc::new(FileLayout::new(db, file_id));
+    let function_params = CodeGenParams {
+        make_marshallable: false,
+    };
+    let wrapper_params = CodeGenParams {
+        make_marshallable: true,
+    };
+
+    let fn_params = functions.iter().map(|f| (*f, function_params.clone()));
+    let wrapper_fn_params = wrapper_functions
+        .iter()
+        .map(|f| (*f, wrapper_params.clone()));
+    let all_fn_params = fn_params.chain(wrapper_fn_params).collect::<Vec<_>>();
+
+    // Reuse the IR of all functions whose inputs are unchanged and generate the others.
+    let mut previous = db.take_function_ir(file_id);
+    let mut cache = HashMap::new();
+    let mut generated_functions = 0;
+    let mut reused_functions = 0;
+    for (hir_function, params) in all_fn_params {
+        let callees = called_functions(db.hir_db(), hir_function);
+        let inputs = FunctionInputs::new(
+            context,
+            db,
+            &group_ir,
+            &layout,
+            hir_function,
+            &params,
+            &callees,
+        );
+        let key = (hir_function, params);
+
+        let function_ir = match previous.remove(&key) {
+            Some(function_ir) if function_ir.inputs == inputs => {
+                reused_functions += 1;
+                function_ir
+            }
+            _ => {
+                generated_functions += 1;
+
+                // Only the functions that are used by the function are declared in its module, so
+                // the module does not depend on the other functions of the file.
+                let declared_functions = functions
+                    .iter()
+                    .filter(|f| **f == hir_function || callees.contains(f))
+                    .cloned()
+                    .collect::<Vec<_>>();
+                FunctionIR {
+                    inputs,
+                    llvm_module: gen_function_module(
                         context,
                         db,
-                        *f,
-                        &llvm_module,
-                        CodeGenParams {
-                            make_marshallable: true,
-                        },
-                    );
-                    wrapper_functions.insert(*f, wrapper_fun);
+                        file_id,
+                        &group_ir,
+                        &declared_functions,
+                        key.0,
+                        key.1.clone(),
+                    ),
                 }
             }
-        }
+        };
+
+        // Clone the LLVM module so the cached module is not consumed by linking.
+        llvm_module
+            .link_in_module(function_ir.llvm_module.clone())
+            .expect("function modules of a file can always be linked");
+        cache.insert(key, function_ir);
+    }
+    db.set_function_ir(file_id, cache);
+
+    Arc::new(FileIR {
+        file_id,
+        llvm_module,
+        api: file_api(db, &functions),
+        generated_functions,
+        reused_functions,
+    })
+}
+
+/// Returns the functions of the file that constitute its API, i.e. its non-private functions.
+fn file_api<D: hir::HirDatabase>(
+    db: &CodegenContext<D>,
+    functions: &[hir::Function],
+) -> Vec<hir::Function> {
+    functions
+        .iter()
+        .filter(|f| f.visibility(db.hir_db()) != hir::Visibility::Private)
+        .cloned()
+        .collect()
+}
+
+/// Generates a module that contains the IR of a single function of the file, or of its wrapper if
+/// `params` requires the function to be marshallable. The `declared_functions` of the file are
+/// declared in the module so the function can call them.
+///
+/// The dispatch table and type table are declared with their current types. When the module is
+/// reused after entries were added to the tables, the linker casts the declarations to the new
+/// types, which is valid because the entries that the function uses keep their indices.
+fn gen_function_module<'ink, D: hir::HirDatabase>(
+    context: &'ink Context,
+    db: &mut CodegenContext<'ink, D>,
+    file_id: FileId,
+    group_ir: &FileGroupIR<'ink>,
+    declared_functions: &[hir::Function],
+    hir_function: hir::Function,
+    params: CodeGenParams,
+) -> Module<'ink> {
+    let name = hir_function.name(db.hir_db());
+    let llvm_module = context.create_module(&format!(
+        "{}::{}",
+        db.hir_db().file_relative_path(file_id),
+        name
+    ));
+
+    // Every function module describes its debug information in a compile unit of its own, so the
+    // IR of a function can be reused with debug information. The compile units of a file are
+    // merged when the modules are linked together.
+    let debug_info = if db.debug_info() {
+        Some(DebugInfo::new(context, db, &llvm_module, file_id))
+    } else {
+        None
+    };
+
+    gen_functions(
+        context,
+        db,
+        &llvm_module,
+        group_ir,
+        declared_functions,
+        &[(hir_function, params)],
+        debug_info.as_ref(),
+    );
+
+    llvm_module
+}
+
+/// Generates the IR of the specified functions, or of their wrappers if their `CodeGenParams`
+/// require them to be marshallable, in `llvm_module`. The `declared_functions` of the file are
+/// declared in the module so the functions can call them.
+fn gen_functions<'ink, D: hir::HirDatabase>(
+    context: &'ink Context,
+    db: &mut CodegenContext<'ink, D>,
+    llvm_module: &Module<'ink>,
+    group_ir: &FileGroupIR<'ink>,
+    declared_functions: &[hir::Function],
+    functions_to_generate: &[(hir::Function, CodeGenParams)],
+    debug_info: Option<&DebugInfo<'ink>>,
+) {
+    // Generate the signatures of all declared functions.
+    let mut functions: HashMap<hir::Function, FunctionValue<'ink>> = HashMap::new();
+    for f in declared_functions {
+        let fun = function::gen_signature(
+            context,
+            db,
+            *f,
+            llvm_module,
+            CodeGenParams {
+                make_marshallable: false,
+            },
+        );
+        functions.insert(*f, fun);
     }
 
     let external_globals = {
@@ -81,45 +458,47 @@ pub(crate) fn ir_query<'a, 'ink, D: hir::HirDatabase>(context: &'ink Context, db
     };
 
     // Construct requirements for generating the bodies
-    let fn_pass_manager = function::create_pass_manager(&llvm_module, db.optimization_lvl());
+    let fn_pass_manager = function::create_pass_manager(llvm_module, db.optimization_lvl());
 
-    // Generate the function bodies
-    for (hir_function, llvm_function) in functions.iter() {
-        function::gen_body(
-            context,
-            db,
-            (*hir_function, *llvm_function),
-            &functions,
-            &group_ir.dispatch_table,
-            &group_ir.type_table,
-            external_globals.clone(),
-        );
-        fn_pass_manager.run_on(llvm_function);
+    let mut llvm_functions = Vec::with_capacity(functions_to_generate.len());
+    for (hir_function, params) in functions_to_generate.iter().cloned() {
+        let llvm_function = if params.make_marshallable {
+            let wrapper_fun =
+                function::gen_signature(context, db, hir_function, llvm_module, params);
+            function::gen_wrapper_body(
+                context,
+                db,
+                (hir_function, wrapper_fun),
+                &functions,
+                &group_ir.dispatch_table,
+                &group_ir.type_table,
+                external_globals.clone(),
+                debug_info,
+            );
+            wrapper_fun
+        } else {
+            let fun = functions[&hir_function];
+            function::gen_body(
+                context,
+                db,
+                (hir_function, fun),
+                &functions,
+                &group_ir.dispatch_table,
+                &group_ir.type_table,
+                external_globals.clone(),
+                debug_info,
+            );
+            fun
+        };
+        llvm_functions.push(llvm_function);
     }
 
-    for (hir_function, llvm_function) in wrapper_functions.iter() {
-        function::gen_wrapper_body(
-            context,
-            db,
-            (*hir_function, *llvm_function),
-            &functions,
-            &group_ir.dispatch_table,
-            &group_ir.type_table,
-            external_globals.clone(),
-        );
-        fn_pass_manager.run_on(llvm_function);
+    // The debug information has to be complete before the functions are optimized
+    if let Some(debug_info) = debug_info {
+        debug_info.finalize();
     }
 
-    // Filter private methods
-    let api: HashSet<hir::Function> = functions
-        .keys()
-        .filter(|f| f.visibility(db.hir_db()) != hir::Visibility::Private)
-        .cloned()
-        .collect();
-
-    Arc::new(FileIR {
-        file_id,
-        llvm_module,
-        api,
-    })
+    for llvm_function in llvm_functions {
+        fn_pass_manager.run_on(&llvm_function);
+    }
 }
@@ -3,7 +3,7 @@ use crate::CodegenContext;
 use super::{
     abi_types::{gen_abi_types, AbiTypes},
     adt,
-    dispatch_table::{DispatchTable, DispatchTableBuilder},
+    dispatch_table::{is_external_fn, DispatchTable, DispatchTableBuilder},
     intrinsics,
     type_table::{TypeTable, TypeTableBuilder},
 };
@@ -27,18 +27,44 @@ pub struct FileGroupIR<'ink> {
     pub(crate) allocator_handle_type: Option<PointerType<'ink>>,
 }
 
-/// Generates IR that is shared among the group's files.
-/// TODO: Currently, a group always consists of a single file. Need to add support for multiple
-/// files using something like `FileGroupId`.
+/// Returns the files of the group that contains `file_id`. A group consists of all files that share
+/// a source root, ordered by their relative path to guarantee deterministic output. Unlike their
+/// `FileId`s, the paths do not depend on the order in which the files were added to the database.
+pub(crate) fn group_files<D: hir::HirDatabase>(db: &D, file_id: hir::FileId) -> Vec<hir::FileId> {
+    source_root_files(db, db.file_source_root(file_id))
+}
+
+/// Returns the files of all source roots that `source_root` depends on, in order of the
+/// dependencies and ordered by their relative path within each dependency.
+pub(crate) fn dependency_files<D: hir::HirDatabase>(db: &D, source_root: hir::SourceRootId) -> Vec<hir::FileId> {
+    db.source_root(source_root)
+        .dependencies()
+        .iter()
+        .flat_map(|dependency| source_root_files(db, *dependency))
+        .collect()
+}
+
+/// Returns the files of `source_root`, ordered by their relative path.
+fn source_root_files<D: hir::HirDatabase>(db: &D, source_root: hir::SourceRootId) -> Vec<hir::FileId> {
+    let mut files = db.source_root(source_root).files().collect::<Vec<_>>();
+    files.sort_by_key(|file_id| db.file_relative_path(*file_id));
+    files
+}
+
+/// Generates IR that is shared among the files of the group that contains `file_id`.
 pub(crate) fn ir_query<'ink, D: hir::HirDatabase>(context: &'ink Context, db: &mut CodegenContext<'ink, D>, file_id: hir::FileId) -> Arc<FileGroupIR<'ink>> {
     let llvm_module = context.create_module("group_name");
+    let definitions = group_files(db.hir_db(), file_id)
+        .into_iter()
+        .flat_map(|file_id| db.hir_db().module_data(file_id).definitions().to_vec())
+        .collect::<Vec<_>>();
 
     // Use a `BTreeMap` to guarantee deterministically ordered output.
     let mut intrinsics_map = BTreeMap::new();
     let mut needs_alloc = false;
 
     // Collect all intrinsic functions, wrapper function, and generate struct declarations.
-    for def in db.hir_db().module_data(file_id).definitions() {
+    for def in definitions.iter() {
         match def {
             ModuleDef::Function(f) if !f.is_extern(db.hir_db()) => {
                 intrinsics::collect_fn_body(
@@ -63,14 +89,32 @@ pub(crate) fn ir_query<'ink, D: hir::HirDatabase>(context: &'ink Context, db: &m
         }
     }
 
-    // Collect all exposed functions' bodies.
-    let mut dispatch_table_builder = DispatchTableBuilder::new(context, db, &llvm_module, &intrinsics_map);
-    for def in db.hir_db().module_data(file_id).definitions() {
+    // Structs defined by dependencies can be used by this assembly, so their layout must be known.
+    let source_root = db.hir_db().file_source_root(file_id);
+    let dependency_structs = dependency_files(db.hir_db(), source_root)
+        .into_iter()
+        .flat_map(|file_id| db.hir_db().module_data(file_id).definitions().to_vec())
+        .filter_map(|def| match def {
+            ModuleDef::Struct(s) => Some(s),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    for s in dependency_structs {
+        adt::gen_struct_decl(context, db, s);
+    }
+
+    // Collect all exposed functions' bodies and all calls to functions outside of their file.
+    let mut dispatch_table_builder = DispatchTableBuilder::new(context, db, &llvm_module, &intrinsics_map, source_root);
+    for def in definitions.iter() {
         if let ModuleDef::Function(f) = def {
-            if !f.data(db.hir_db()).visibility().is_private() && !f.is_extern(db.hir_db()) {
+            if !f.is_extern(db.hir_db()) {
                 let body = f.body(db.hir_db());
                 let infer = f.infer(db.hir_db());
-                dispatch_table_builder.collect_body(db, &body, &infer);
+                if !f.data(db.hir_db()).visibility().is_private() {
+                    dispatch_table_builder.collect_body(db, &body, &infer);
+                } else {
+                    dispatch_table_builder.collect_non_local_calls(db, f.module(db.hir_db()).file_id(), &body, &infer);
+                }
             }
         }
     }
@@ -88,7 +132,7 @@ pub(crate) fn ir_query<'ink, D: hir::HirDatabase>(context: &'ink Context, db: &m
     );
 
     // Collect all used types
-    for def in db.hir_db().module_data(file_id).definitions() {
+    for def in definitions.iter() {
         match def {
             ModuleDef::Struct(s) => {
                 type_table_builder.collect_struct(db, *s);
@@ -100,6 +144,15 @@ pub(crate) fn ir_query<'ink, D: hir::HirDatabase>(context: &'ink Context, db: &m
         }
     }
 
+    // Collect the types used by the signatures of functions defined by dependencies
+    for entry in dispatch_table.entries().iter() {
+        if let Some(f) = entry.hir {
+            if !f.is_extern(db.hir_db()) && is_external_fn(db.hir_db(), f, source_root) {
+                type_table_builder.collect_fn(db, f);
+            }
+        }
+    }
+
     let type_table = type_table_builder.build(db);
 
     // Create the allocator handle global value
@@ -2,10 +2,12 @@ use inkwell::context::Context;
 use crate::ir::{body::BodyIrGenerator, dispatch_table::DispatchTable, type_table::TypeTable};
 use crate::values::FunctionValue;
 use crate::{CodeGenParams, CodegenContext, Module, OptimizationLevel};
-use inkwell::passes::{PassManager, PassManagerBuilder};
+use crate::optimization::create_pass_manager_builder;
+use inkwell::passes::PassManager;
 use inkwell::types::AnyTypeEnum;
 
 use super::body::ExternalGlobals;
+use super::debug_info::DebugInfo;
 use std::collections::HashMap;
 
 /// Constructs a PassManager to optimize functions for the given optimization level.
@@ -13,8 +15,7 @@ pub(crate) fn create_pass_manager<'ink>(
     module: &Module<'ink>,
     optimization_lvl: OptimizationLevel,
 ) -> PassManager<FunctionValue<'ink>> {
-    let pass_builder = PassManagerBuilder::create();
-    pass_builder.set_optimization_level(optimization_lvl);
+    let pass_builder = create_pass_manager_builder(optimization_lvl);
 
     let function_pass_manager = PassManager::create(module);
     pass_builder.populate_function_pass_manager(&function_pass_manager);
@@ -35,9 +36,15 @@ pub(crate) fn gen_signature<'ink, D: hir::HirDatabase>(
     params: CodeGenParams,
 ) -> FunctionValue<'ink> {
     let name = {
-        let name = f.name(db.hir_db()).to_string();
+        let hir_db = db.hir_db();
+        let name = f.name(hir_db).to_string();
         if params.make_marshallable {
             format!("{}_wrapper", name)
+        } else if f.visibility(hir_db).is_private() {
+            // The private functions of different files may have the same name, so their symbols
+            // are prefixed with the path of their file.
+            let file_id = f.module(hir_db).file_id();
+            format!("{}::{}", hir_db.file_relative_path(file_id), name)
         } else {
             name
         }
@@ -50,7 +57,9 @@ pub(crate) fn gen_signature<'ink, D: hir::HirDatabase>(
     }
 }
 
-/// Generates the body of a `hir::Function` for an associated `FunctionValue`.
+/// Generates the body of a `hir::Function` for an associated `FunctionValue`. If `debug_info` is
+/// specified, debug information is generated for the function as well.
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn gen_body<'ink, 'a, 'b, D: hir::HirDatabase>(
     context: &'ink Context,
     db: &mut CodegenContext<'ink, D>,
@@ -59,6 +68,7 @@ pub(crate) fn gen_body<'ink, 'a, 'b, D: hir::HirDatabase>(
     dispatch_table: &'b DispatchTable<'ink>,
     type_table: &'b TypeTable<'ink>,
     external_globals: ExternalGlobals<'ink>,
+    debug_info: Option<&'b DebugInfo<'ink>>,
 ) {
     let mut code_gen = BodyIrGenerator::new(
         context,
@@ -71,6 +81,7 @@ pub(crate) fn gen_body<'ink, 'a, 'b, D: hir::HirDatabase>(
             make_marshallable: false,
         },
         external_globals,
+        debug_info,
     );
 
     code_gen.gen_fn_body();
@@ -78,6 +89,7 @@ pub(crate) fn gen_body<'ink, 'a, 'b, D: hir::HirDatabase>(
 
 /// Generates the body of a wrapper around `hir::Function` for its associated
 /// `FunctionValue`
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn gen_wrapper_body<'ink, 'a, 'b, D: hir::HirDatabase>(
     context: &'ink Context,
     db: &mut CodegenContext<'ink, D>,
@@ -86,6 +98,7 @@ pub(crate) fn gen_wrapper_body<'ink, 'a, 'b, D: hir::HirDatabase>(
     dispatch_table: &'b DispatchTable<'ink>,
     type_table: &'b TypeTable<'ink>,
     external_globals: ExternalGlobals<'ink>,
+    debug_info: Option<&'b DebugInfo<'ink>>,
 ) {
     let mut code_gen = BodyIrGenerator::new(
         context,
@@ -98,6 +111,7 @@ pub(crate) fn gen_wrapper_body<'ink, 'a, 'b, D: hir::HirDatabase>(
             make_marshallable: true,
         },
         external_globals,
+        debug_info,
     );
 
     code_gen.gen_fn_wrapper();
@@ -17,6 +17,7 @@ use inkwell::{
     AddressSpace,
 };
 use std::collections::{BTreeSet, HashMap};
+use std::fmt::Write;
 use std::{convert::TryInto, mem, sync::Arc};
 
 /// A type table in IR is a list of pointers to unique type information that are used to generate
@@ -70,6 +71,13 @@ impl<'ink> TypeTable<'ink> {
         self.entries.len()
     }
 
+    /// Returns the types in the `TypeTable`, ordered by their index.
+    pub(crate) fn type_infos(&self) -> impl Iterator<Item = &TypeInfo> {
+        let mut type_infos = self.type_info_to_index.iter().collect::<Vec<_>>();
+        type_infos.sort_by_key(|(_, index)| **index);
+        type_infos.into_iter().map(|(type_info, _)| type_info)
+    }
+
     /// Returns whether the type table is empty.
     pub fn is_empty(&self) -> bool {
         self.entries.is_empty()
@@ -79,6 +87,58 @@ impl<'ink> TypeTable<'ink> {
     pub fn ty(&self) -> ArrayType<'ink> {
         self.table_type
     }
+
+    /// Returns a textual representation of the types in the `TypeTable`, ordered by their index.
+    /// Next to the information that is emitted for each type, the fields of structs are listed.
+    pub fn debug_dump<D: hir::HirDatabase>(
+        &self,
+        context: &'ink Context,
+        db: &mut CodegenContext<'ink, D>,
+    ) -> String {
+        let target_data = db.target_data();
+        let mut buf = String::new();
+        for (index, type_info) in self.type_infos().enumerate() {
+            let guid = type_info
+                .guid
+                .b
+                .iter()
+                .map(|byte| format!("{:02x}", byte))
+                .collect::<String>();
+            writeln!(buf, "{}: {}", index, type_info.name).unwrap();
+            writeln!(buf, "    guid: {}", guid).unwrap();
+            writeln!(
+                buf,
+                "    size: {} bits, {} bytes stored, {} bytes allocated, aligned to {} bytes",
+                type_info.size.bit_size,
+                type_info.size.store_size,
+                type_info.size.alloc_size,
+                type_info.size.alignment
+            )
+            .unwrap();
+
+            if let TypeGroup::StructTypes(s) = type_info.group {
+                let memory_kind = match s.data(db.hir_db()).memory_kind {
+                    hir::StructMemoryKind::GC => "gc",
+                    hir::StructMemoryKind::Value => "value",
+                };
+                writeln!(buf, "    struct({})", memory_kind).unwrap();
+
+                let struct_ir = db.struct_ty(context, s);
+                for (idx, field) in s.fields(db.hir_db()).into_iter().enumerate() {
+                    let field_type_info = db.type_info(context, field.ty(db.hir_db()));
+                    writeln!(
+                        buf,
+                        "        {}: {} at offset {}",
+                        field.name(db.hir_db()),
+                        field_type_info.name,
+                        target_data.offset_of_element(&struct_ir, idx as u32).unwrap()
+                    )
+                    .unwrap();
+                }
+            }
+        }
+        buf
+    }
 }
 
 /// Used to build a `TypeTable` from HIR.
@@ -234,12 +294,8 @@ impl<'a , 'ink: 'a> TypeTableBuilder<'ink, 'a> {
     ) -> StructValue {
         let name = hir_struct.name(db.hir_db()).to_string();
         let fields = hir_struct.fields(db.hir_db());
-        println!("computed fields inside gen_struct_info: {}", fields.len());
         let struct_ir = db.struct_ty(self.context, hir_struct);
 
-        // This is because the struct is generated with opaque_struct_type
-        println!("llvm ir has {} fields", struct_ir.count_fields());
-
         let field_names = gen_string_array(
             &self.context,
             self.module,
@@ -279,10 +335,35 @@ impl<'a , 'ink: 'a> TypeTableBuilder<'ink, 'a> {
             &format!("struct_info::<{}>::field_offsets", name),
         );
 
+        // The previous names are only stored if any of the fields was renamed
+        let previous_names: Vec<Option<hir::Name>> = fields
+            .iter()
+            .map(|field| field.migrate_from(db.hir_db()))
+            .collect();
+        let str_type = self.context.i8_type().ptr_type(AddressSpace::Const);
+        let field_previous_names = if previous_names.iter().all(Option::is_none) {
+            str_type.ptr_type(AddressSpace::Const).const_null()
+        } else {
+            let previous_names = previous_names
+                .iter()
+                .map(|previous_name| match previous_name {
+                    Some(previous_name) => intern_string(
+                        &self.context,
+                        self.module,
+                        &previous_name.to_string(),
+                        &format!("struct_info::<{}>::field_previous_names", name),
+                    ),
+                    None => str_type.const_null(),
+                })
+                .collect::<Vec<PointerValue>>();
+            gen_global(self.module, &str_type.const_array(&previous_names), "").as_pointer_value()
+        };
+
         self.abi_types.struct_info_type.const_named_struct(&[
             field_names.into(),
             field_types.into(),
             field_offsets.into(),
+            field_previous_names.into(),
             self.context
                 .i16_type()
                 .const_int(fields.len() as u64, false)
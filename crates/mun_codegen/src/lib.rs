@@ -3,6 +3,7 @@ mod code_gen;
 mod context;
 #[macro_use]
 mod ir;
+mod optimization;
 
 #[cfg(test)]
 mod mock;
@@ -12,11 +13,12 @@ mod test;
 pub(crate) mod intrinsics;
 pub(crate) mod type_info;
 
-pub use inkwell::{builder, context::Context, module::Module, values, OptimizationLevel};
+pub use inkwell::{builder, context::Context, module::Module, values};
 
 pub use crate::{
-    code_gen::ModuleBuilder,
+    code_gen::{CodegenStats, ModuleBuilder, ObjectFile, OutputType, PassTiming},
     context::CodegenContext,
+    optimization::OptimizationLevel,
 };
 
 #[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
@@ -0,0 +1,119 @@
+//! Describes how much effort the code generator puts into optimizing the generated code and how
+//! that translates to the LLVM optimization pipeline.
+
+use inkwell::passes::{PassManager, PassManagerBuilder, PassManagerSubType};
+
+/// The optimization level used for code generation. Mirrors the `-O` levels of the compiler.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum OptimizationLevel {
+    /// No optimizations (`-O0`)
+    None,
+    /// Few optimizations (`-O1`)
+    Less,
+    /// The default optimizations (`-O2`)
+    Default,
+    /// Optimizations that trade code size for speed (`-O3`)
+    Aggressive,
+    /// Optimizations that favor small code size (`-Os`)
+    Size,
+    /// Optimizations that reduce code size as much as possible (`-Oz`)
+    MinSize,
+}
+
+impl OptimizationLevel {
+    /// Returns the LLVM optimization level used by the target machine and the pass pipeline.
+    pub(crate) fn llvm_level(self) -> inkwell::OptimizationLevel {
+        match self {
+            OptimizationLevel::None => inkwell::OptimizationLevel::None,
+            OptimizationLevel::Less => inkwell::OptimizationLevel::Less,
+            OptimizationLevel::Default | OptimizationLevel::Size | OptimizationLevel::MinSize => {
+                inkwell::OptimizationLevel::Default
+            }
+            OptimizationLevel::Aggressive => inkwell::OptimizationLevel::Aggressive,
+        }
+    }
+
+    /// Returns the LLVM size level; `1` for `-Os`, `2` for `-Oz`, and `0` otherwise.
+    fn size_level(self) -> u32 {
+        match self {
+            OptimizationLevel::Size => 1,
+            OptimizationLevel::MinSize => 2,
+            _ => 0,
+        }
+    }
+
+    /// Returns the threshold for inlining functions, or `None` if only functions marked
+    /// `alwaysinline` should be inlined. The values match the ones used by Clang.
+    fn inline_threshold(self) -> Option<u32> {
+        match self {
+            OptimizationLevel::None | OptimizationLevel::Less => None,
+            OptimizationLevel::Default => Some(225),
+            OptimizationLevel::Aggressive => Some(250),
+            OptimizationLevel::Size => Some(75),
+            OptimizationLevel::MinSize => Some(25),
+        }
+    }
+
+    /// Returns true if loops should be unrolled.
+    fn unroll_loops(self) -> bool {
+        match self {
+            OptimizationLevel::None | OptimizationLevel::MinSize => false,
+            _ => true,
+        }
+    }
+
+    /// Returns true if loops and straight-line code should be vectorized.
+    fn vectorize(self) -> bool {
+        match self {
+            OptimizationLevel::Default | OptimizationLevel::Aggressive => true,
+            _ => false,
+        }
+    }
+}
+
+/// Constructs a `PassManagerBuilder` that is configured for the given optimization level.
+pub(crate) fn create_pass_manager_builder(
+    optimization_lvl: OptimizationLevel,
+) -> PassManagerBuilder {
+    let pass_builder = PassManagerBuilder::create();
+    pass_builder.set_optimization_level(optimization_lvl.llvm_level());
+    pass_builder.set_size_level(optimization_lvl.size_level());
+    pass_builder.set_disable_unroll_loops(!optimization_lvl.unroll_loops());
+    if let Some(threshold) = optimization_lvl.inline_threshold() {
+        pass_builder.set_inliner_with_threshold(threshold);
+    }
+    pass_builder
+}
+
+/// Adds the vectorization passes to the pass manager, if the optimization level enables them. The
+/// C API of LLVM does not allow enabling these through the `PassManagerBuilder`.
+pub(crate) fn add_vectorization_passes<T: PassManagerSubType>(
+    pass_manager: &PassManager<T>,
+    optimization_lvl: OptimizationLevel,
+) {
+    if optimization_lvl.vectorize() {
+        pass_manager.add_loop_vectorize_pass();
+        pass_manager.add_slp_vectorize_pass();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::OptimizationLevel;
+
+    #[test]
+    fn size_levels() {
+        assert_eq!(OptimizationLevel::Default.size_level(), 0);
+        assert_eq!(OptimizationLevel::Size.size_level(), 1);
+        assert_eq!(OptimizationLevel::MinSize.size_level(), 2);
+    }
+
+    #[test]
+    fn inline_thresholds() {
+        let default = OptimizationLevel::Default.inline_threshold().unwrap();
+        let size = OptimizationLevel::Size.inline_threshold().unwrap();
+        let min_size = OptimizationLevel::MinSize.inline_threshold().unwrap();
+        assert!(min_size < size && size < default);
+        assert!(OptimizationLevel::None.inline_threshold().is_none());
+    }
+}
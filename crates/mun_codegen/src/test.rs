@@ -3,7 +3,7 @@ use crate::{mock::single_file_mock_db, ModuleBuilder};
 use hir::{
     diagnostics::DiagnosticSink, line_index::LineIndex, Module, SourceDatabase,
 };
-use inkwell::OptimizationLevel;
+use crate::OptimizationLevel;
 use inkwell::context::Context;
 use mun_target::spec::Target;
 use hir::HirDatabase;
@@ -1,4 +1,6 @@
+use crate::timings::QueryStats;
 use mun_hir::salsa;
+use std::cell::Cell;
 
 #[salsa::database(
     mun_hir::SourceDatabaseStorage,
@@ -8,18 +10,35 @@ use mun_hir::salsa;
 #[derive(Debug)]
 pub(crate) struct CompilerDatabase {
     runtime: salsa::Runtime<CompilerDatabase>,
+    query_stats: Cell<QueryStats>,
 }
 
 impl CompilerDatabase {
     pub fn new() -> Self {
         CompilerDatabase {
             runtime: salsa::Runtime::default(),
+            query_stats: Cell::new(QueryStats::default()),
         }
     }
+
+    /// Returns the query statistics gathered since the previous call and resets them.
+    pub fn take_query_stats(&self) -> QueryStats {
+        self.query_stats.replace(QueryStats::default())
+    }
 }
 
 impl salsa::Database for CompilerDatabase {
     fn salsa_runtime(&self) -> &salsa::Runtime<CompilerDatabase> {
         &self.runtime
     }
+
+    fn salsa_event(&self, event: impl Fn() -> salsa::Event<CompilerDatabase>) {
+        let mut stats = self.query_stats.get();
+        match event().kind {
+            salsa::EventKind::WillExecute { .. } => stats.executions += 1,
+            salsa::EventKind::DidValidateMemoizedValue { .. } => stats.revalidations += 1,
+            _ => return,
+        }
+        self.query_stats.set(stats);
+    }
 }
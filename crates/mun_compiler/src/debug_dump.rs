@@ -0,0 +1,120 @@
+//! Textual representations of the compiler's data structures for a source file, which are useful
+//! for debugging the compiler and for attaching to bug reports.
+
+use crate::{CompilerOptions, Driver};
+use mun_codegen::Context;
+use std::{fmt, str::FromStr};
+
+/// The data structures of the compiler that can be dumped.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DebugDump {
+    /// The syntax tree produced by the parser, followed by syntax errors
+    SyntaxTree,
+    /// The definitions of the file, including the inferred types of all function bodies
+    Hir,
+    /// The type table that is emitted in the assembly
+    TypeTable,
+}
+
+impl fmt::Display for DebugDump {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            DebugDump::SyntaxTree => "syntax-tree",
+            DebugDump::Hir => "hir",
+            DebugDump::TypeTable => "type-table",
+        })
+    }
+}
+
+impl FromStr for DebugDump {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "syntax-tree" => Ok(DebugDump::SyntaxTree),
+            "hir" => Ok(DebugDump::Hir),
+            "type-table" => Ok(DebugDump::TypeTable),
+            _ => Err(format!("unknown debug dump: {}", s)),
+        }
+    }
+}
+
+/// Returns a textual representation of the specified data structure for the input file. The type
+/// table can only be generated for files without errors; otherwise the returned error contains the
+/// diagnostics.
+pub fn debug_dump(options: CompilerOptions, dump: DebugDump) -> Result<String, failure::Error> {
+    let context = Context::create();
+    let (mut driver, file_id) = Driver::with_file(options.config, options.input)?;
+    match dump {
+        DebugDump::SyntaxTree => Ok(driver.debug_dump_syntax_tree(file_id)),
+        DebugDump::Hir => Ok(driver.debug_dump_hir(file_id)),
+        DebugDump::TypeTable => {
+            let mut diagnostics = Vec::new();
+            if driver.emit_diagnostics(&mut diagnostics)? {
+                Err(failure::err_msg(
+                    String::from_utf8_lossy(&diagnostics).into_owned(),
+                ))
+            } else {
+                Ok(driver.debug_dump_type_table(&context, file_id))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{debug_dump, DebugDump};
+    use crate::{CompilerOptions, Config, DisplayColor};
+
+    fn dump(text: &str, dump: DebugDump) -> Result<String, failure::Error> {
+        let mut options = CompilerOptions::with_file("main.mun", text);
+        options.config = Config {
+            display_color: DisplayColor::Disable,
+            ..Config::default()
+        };
+        debug_dump(options, dump)
+    }
+
+    #[test]
+    fn syntax_tree() {
+        let tree = dump("fn main() {}", DebugDump::SyntaxTree).unwrap();
+        assert!(tree.starts_with("SOURCE_FILE@[0; 12)"), "{}", tree);
+        assert!(tree.contains("FUNCTION_DEF@[0; 12)"), "{}", tree);
+
+        let tree = dump("fn main( {}", DebugDump::SyntaxTree).unwrap();
+        assert!(tree.contains("error"), "{}", tree);
+    }
+
+    #[test]
+    fn hir() {
+        let hir = dump("pub fn main() -> i32 { 5 }", DebugDump::Hir).unwrap();
+        assert_eq!(
+            hir,
+            "pub fn main() -> i32 {\n    [21; 26) '{ 5 }': i32\n    [23; 24) '5': i32\n}\n"
+        );
+    }
+
+    #[test]
+    fn type_table() {
+        let text = "struct Foo { a: i32, b: f64 }\n\npub fn main() -> Foo { Foo { a: 1, b: 2.0 } }";
+        let type_table = dump(text, DebugDump::TypeTable).unwrap();
+        assert!(type_table.contains(": Foo\n"), "{}", type_table);
+        assert!(type_table.contains("    struct(gc)\n"), "{}", type_table);
+        assert!(
+            type_table.contains("        a: core::i32 at offset 0\n"),
+            "{}",
+            type_table
+        );
+        assert!(
+            type_table.contains("        b: core::f64 at offset 8\n"),
+            "{}",
+            type_table
+        );
+
+        // The type table is deterministic
+        assert_eq!(type_table, dump(text, DebugDump::TypeTable).unwrap());
+
+        // Files with errors are rejected
+        assert!(dump("pub fn main() -> Bar { 0 }", DebugDump::TypeTable).is_err());
+    }
+}
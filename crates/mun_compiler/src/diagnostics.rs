@@ -1,4 +1,4 @@
-use mun_hir::diagnostics::DiagnosticSink;
+use mun_hir::diagnostics::{Diagnostic, DiagnosticSink, Suggestion};
 use mun_hir::{FileId, HirDatabase, Module};
 
 use std::cell::RefCell;
@@ -34,101 +34,119 @@ pub fn diagnostics(db: &impl HirDatabase, file_id: FileId) -> Vec<Snippet> {
     }));
 
     let result = RefCell::new(result);
-    let mut sink = DiagnosticSink::new(|d| {
+    let emit = |diagnostic: &dyn Diagnostic, snippet: Snippet| {
         result
             .borrow_mut()
-            .push(diagnostics_snippets::generic_error(
+            .push(diagnostics_snippets::with_suggestions(
+                snippet,
+                diagnostic,
+                &parse,
+                &source_code,
+            ));
+    };
+    let mut sink = DiagnosticSink::new(|d| {
+        emit(
+            d,
+            diagnostics_snippets::generic_error(
                 d,
                 db,
                 &parse,
                 &relative_file_path,
                 &source_code,
                 &line_index,
-            ));
+            ),
+        );
     })
     .on::<mun_hir::diagnostics::UnresolvedValue, _>(|d| {
-        result
-            .borrow_mut()
-            .push(diagnostics_snippets::unresolved_value_error(
+        emit(
+            d,
+            diagnostics_snippets::unresolved_value_error(
                 d,
                 db,
                 &parse,
                 &relative_file_path,
                 &source_code,
                 &line_index,
-            ));
+            ),
+        );
     })
     .on::<mun_hir::diagnostics::UnresolvedType, _>(|d| {
-        result
-            .borrow_mut()
-            .push(diagnostics_snippets::unresolved_type_error(
+        emit(
+            d,
+            diagnostics_snippets::unresolved_type_error(
                 d,
                 db,
                 &parse,
                 &relative_file_path,
                 &source_code,
                 &line_index,
-            ));
+            ),
+        );
     })
     .on::<mun_hir::diagnostics::ExpectedFunction, _>(|d| {
-        result
-            .borrow_mut()
-            .push(diagnostics_snippets::expected_function_error(
+        emit(
+            d,
+            diagnostics_snippets::expected_function_error(
                 d,
                 db,
                 &parse,
                 &relative_file_path,
                 &source_code,
                 &line_index,
-            ));
+            ),
+        );
     })
     .on::<mun_hir::diagnostics::MismatchedType, _>(|d| {
-        result
-            .borrow_mut()
-            .push(diagnostics_snippets::mismatched_type_error(
+        emit(
+            d,
+            diagnostics_snippets::mismatched_type_error(
                 d,
                 db,
                 &parse,
                 &relative_file_path,
                 &source_code,
                 &line_index,
-            ));
+            ),
+        );
     })
     .on::<mun_hir::diagnostics::DuplicateDefinition, _>(|d| {
-        result
-            .borrow_mut()
-            .push(diagnostics_snippets::duplicate_definition_error(
+        emit(
+            d,
+            diagnostics_snippets::duplicate_definition_error(
                 d,
                 db,
                 &parse,
                 &relative_file_path,
                 &source_code,
                 &line_index,
-            ));
+            ),
+        );
     })
     .on::<mun_hir::diagnostics::PossiblyUninitializedVariable, _>(|d| {
-        result
-            .borrow_mut()
-            .push(diagnostics_snippets::possibly_uninitialized_variable_error(
+        emit(
+            d,
+            diagnostics_snippets::possibly_uninitialized_variable_error(
                 d,
                 db,
                 &parse,
                 &relative_file_path,
                 &source_code,
                 &line_index,
-            ));
+            ),
+        );
     })
     .on::<mun_hir::diagnostics::AccessUnknownField, _>(|d| {
-        result
-            .borrow_mut()
-            .push(diagnostics_snippets::access_unknown_field_error(
+        emit(
+            d,
+            diagnostics_snippets::access_unknown_field_error(
                 d,
                 db,
                 &parse,
                 &relative_file_path,
                 &source_code,
                 &line_index,
-            ));
+            ),
+        );
     });
 
     Module::from(file_id).diagnostics(db, &mut sink);
@@ -138,6 +156,18 @@ pub fn diagnostics(db: &impl HirDatabase, file_id: FileId) -> Vec<Snippet> {
     result.into_inner()
 }
 
+/// Returns the suggestions of all diagnostics in the given file.
+pub fn suggestions(db: &impl HirDatabase, file_id: FileId) -> Vec<Suggestion> {
+    let file_syntax = db.parse(file_id).syntax_node();
+
+    let mut result = Vec::new();
+    let mut sink = DiagnosticSink::new(|d| result.extend(d.suggestions(&file_syntax)));
+    Module::from(file_id).diagnostics(db, &mut sink);
+    drop(sink);
+
+    result
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{Config, DisplayColor, Driver, PathOrInline, RelativePathBuf};
@@ -35,6 +35,46 @@ fn syntax_node_ptr_location(
     }
 }
 
+/// Adds the suggestions of a diagnostic to a snippet as `help` footers. If a suggestion only changes
+/// a single line, the line is shown as it would look after applying the suggestion.
+pub(crate) fn with_suggestions(
+    mut snippet: Snippet,
+    diagnostic: &dyn HirDiagnostic,
+    parse: &Parse<SourceFile>,
+    source_code: &str,
+) -> Snippet {
+    for suggestion in diagnostic.suggestions(&parse.syntax_node()) {
+        let label = match suggestion.edits.as_slice() {
+            [edit] => {
+                let start = edit.range.range.start().to_usize();
+                let end = edit.range.range.end().to_usize();
+                let line_start = source_code[..start].rfind('\n').map_or(0, |idx| idx + 1);
+                let line_end = source_code[end..]
+                    .find('\n')
+                    .map_or(source_code.len(), |idx| end + idx);
+                let line = format!(
+                    "{}{}{}",
+                    &source_code[line_start..start],
+                    edit.insert,
+                    &source_code[end..line_end]
+                );
+                if line.contains('\n') {
+                    suggestion.message
+                } else {
+                    format!("{}: `{}`", suggestion.message, line.trim())
+                }
+            }
+            _ => suggestion.message,
+        };
+        snippet.footer.push(
+            AnnotationBuilder::new(AnnotationType::Help)
+                .label(&label)
+                .build(),
+        );
+    }
+    snippet
+}
+
 pub(crate) fn syntax_error(
     syntax_error: &SyntaxError,
     _: &impl HirDatabase,
@@ -80,6 +120,7 @@ pub(crate) fn generic_error(
     SnippetBuilder::new()
         .title(
             AnnotationBuilder::new(AnnotationType::Error)
+                .id(diagnostic.code().as_str())
                 .label(&diagnostic.message())
                 .build(),
         )
@@ -113,6 +154,7 @@ pub(crate) fn unresolved_value_error(
     SnippetBuilder::new()
         .title(
             AnnotationBuilder::new(AnnotationType::Error)
+                .id(diagnostic.code().as_str())
                 .label(&format!(
                     "cannot find value `{}` in this scope",
                     unresolved_value
@@ -150,6 +192,7 @@ pub(crate) fn unresolved_type_error(
     SnippetBuilder::new()
         .title(
             AnnotationBuilder::new(AnnotationType::Error)
+                .id(diagnostic.code().as_str())
                 .label(&format!(
                     "cannot find type `{}` in this scope",
                     unresolved_type
@@ -180,6 +223,7 @@ pub(crate) fn expected_function_error(
     SnippetBuilder::new()
         .title(
             AnnotationBuilder::new(AnnotationType::Error)
+                .id(diagnostic.code().as_str())
                 .label(&diagnostic.message())
                 .build(),
         )
@@ -210,6 +254,7 @@ pub(crate) fn mismatched_type_error(
     SnippetBuilder::new()
         .title(
             AnnotationBuilder::new(AnnotationType::Error)
+                .id(diagnostic.code().as_str())
                 .label(&diagnostic.message())
                 .build(),
         )
@@ -253,6 +298,7 @@ pub(crate) fn duplicate_definition_error(
     SnippetBuilder::new()
         .title(
             AnnotationBuilder::new(AnnotationType::Error)
+                .id(diagnostic.code().as_str())
                 .label(&diagnostic.message())
                 .build(),
         )
@@ -300,6 +346,7 @@ pub(crate) fn possibly_uninitialized_variable_error(
     SnippetBuilder::new()
         .title(
             AnnotationBuilder::new(AnnotationType::Error)
+                .id(diagnostic.code().as_str())
                 .label(&format!("{}: `{}`", diagnostic.message(), variable_name))
                 .build(),
         )
@@ -331,6 +378,7 @@ pub(crate) fn access_unknown_field_error(
     SnippetBuilder::new()
         .title(
             AnnotationBuilder::new(AnnotationType::Error)
+                .id(diagnostic.code().as_str())
                 .label(&format!(
                     "no field `{}` on type `{}`",
                     diagnostic.name,
@@ -2,11 +2,22 @@
 //! from previous compilation.
 
 use mun_codegen::Context;
-use crate::{db::CompilerDatabase, diagnostics::diagnostics, PathOrInline};
-use mun_codegen::{CodegenContext, ModuleBuilder};
-use mun_hir::{FileId, RelativePathBuf, SourceDatabase, SourceRoot, SourceRootId};
+use crate::{
+    db::CompilerDatabase,
+    diagnostics::{diagnostics, suggestions},
+    timings::{PassTiming, Timings},
+    Package, PathOrInline,
+};
+use mun_codegen::{CodegenContext, CodegenStats, ModuleBuilder, OutputType};
+use mun_hir::{
+    FileId, ModuleDef, RelativePath, RelativePathBuf, SourceDatabase, SourceRoot, SourceRootId,
+};
 
-use std::{path::PathBuf, sync::Arc};
+use std::{
+    cell::RefCell,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 mod config;
 mod display_color;
@@ -14,6 +25,8 @@ mod display_color;
 pub use self::config::Config;
 pub use self::display_color::DisplayColor;
 
+use failure::ResultExt;
+use mun_hir::diagnostics::Suggestion;
 use mun_hir::HirDatabase;
 use annotate_snippets::{
     display_list::DisplayList,
@@ -28,6 +41,13 @@ pub struct Driver<'ink> {
     db: CodegenContext<'ink, CompilerDatabase>,
     out_dir: Option<PathBuf>,
     display_color: DisplayColor,
+    emit: Vec<OutputType>,
+    package_name: Option<String>,
+    dependency_assemblies: Vec<PathBuf>,
+    source_dirs: Vec<(PathBuf, SourceRootId)>,
+    next_file_id: u32,
+    codegen_stats: CodegenStats,
+    timings: Option<RefCell<Timings>>,
 }
 
 impl<'ink> Driver<'ink> {
@@ -37,12 +57,23 @@ impl<'ink> Driver<'ink> {
             db: CodegenContext::new(CompilerDatabase::new()),
             out_dir: None,
             display_color: config.display_color,
+            emit: config.emit,
+            package_name: None,
+            dependency_assemblies: Vec::new(),
+            source_dirs: Vec::new(),
+            next_file_id: 0,
+            codegen_stats: CodegenStats::default(),
+            timings: if config.timings {
+                Some(RefCell::default())
+            } else {
+                None
+            },
         };
 
         // Move relevant configuration into the database
-        // TODO: reenable!!!
         driver.db.hir_db_mut().set_target(config.target);
-        // driver.db.set_optimization_lvl(config.optimization_lvl);
+        driver.db.set_optimization_lvl(config.optimization_lvl);
+        driver.db.set_debug_info(config.debug_info);
 
         driver.out_dir = config.out_dir;
 
@@ -62,6 +93,15 @@ impl<'ink> Driver<'ink> {
         // Get the path and contents of the path
         let (rel_path, text) = match path {
             PathOrInline::Path(p) => {
+                // Debuggers locate the source file relative to its directory
+                let source_dir = p
+                    .parent()
+                    .map(|dir| dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf()));
+                if let Some(source_dir) = &source_dir {
+                    driver.source_dirs.push((source_dir.clone(), WORKSPACE));
+                }
+                driver.db.set_source_dir(source_dir);
+
                 let filename = p.file_name().ok_or_else(|| {
                     std::io::Error::new(
                         std::io::ErrorKind::InvalidInput,
@@ -78,6 +118,7 @@ impl<'ink> Driver<'ink> {
 
         // Store the file information in the database together with the source root
         let file_id = FileId(0);
+        driver.next_file_id = 1;
         driver.db.hir_db_mut().set_file_relative_path(file_id, rel_path.clone());
         driver.db.hir_db_mut().set_file_text(file_id, Arc::new(text));
         driver.db.hir_db_mut().set_file_source_root(file_id, WORKSPACE);
@@ -86,6 +127,150 @@ impl<'ink> Driver<'ink> {
 
         Ok((driver, file_id))
     }
+
+    /// Constructs a driver with a configuration and all source files of a package. Use
+    /// `Package::config` to obtain the configuration described by the package manifest.
+    ///
+    /// The source files of the package's dependencies are loaded into separate source roots, so
+    /// their public items can be resolved. The dependencies themselves are not compiled.
+    pub fn with_package(config: Config, package: &Package) -> Result<Driver<'ink>, failure::Error> {
+        let mut driver = Driver::with_config(config);
+        driver.package_name = Some(package.name().to_owned());
+
+        let source_dir = package.source_dir();
+        driver.db.set_source_dir(Some(
+            source_dir
+                .canonicalize()
+                .unwrap_or_else(|_| source_dir.clone()),
+        ));
+
+        // Every dependency is stored in its own source root; dependencies precede their dependents
+        let dependencies = package.resolve_dependencies()?;
+        let mut source_roots = Vec::with_capacity(dependencies.len());
+        for (idx, dependency) in dependencies.iter().enumerate() {
+            let source_root_id = SourceRootId(idx as u32 + 1);
+            let mut source_root = driver.load_package_files(dependency, source_root_id)?;
+            add_source_root_dependencies(&mut source_root, dependency, &source_roots)?;
+            driver
+                .db
+                .hir_db_mut()
+                .set_source_root(source_root_id, Arc::new(source_root));
+            source_roots.push((dependency.name().to_owned(), source_root_id));
+        }
+
+        // All source files of the package itself are stored in the workspace
+        let mut source_root = driver.load_package_files(package, WORKSPACE)?;
+        if source_root.files().next().is_none() {
+            return Err(failure::format_err!(
+                "package `{}` does not contain any source files in {}",
+                package.name(),
+                source_dir.display()
+            ));
+        }
+        add_source_root_dependencies(&mut source_root, package, &source_roots)?;
+        driver.db.hir_db_mut().set_source_root(WORKSPACE, Arc::new(source_root));
+
+        driver.dependency_assemblies = package
+            .dependencies()?
+            .iter()
+            .map(Package::assembly_path)
+            .collect();
+
+        Ok(driver)
+    }
+
+    /// Stores the source files of a package in the database and returns a source root that
+    /// contains them.
+    fn load_package_files(
+        &mut self,
+        package: &Package,
+        source_root_id: SourceRootId,
+    ) -> Result<SourceRoot, failure::Error> {
+        let source_dir = package.source_dir();
+        let mut source_root = SourceRoot::default();
+        for path in package.source_files()? {
+            let rel_path = path
+                .strip_prefix(&source_dir)
+                .ok()
+                .and_then(|path| RelativePathBuf::from_path(path).ok())
+                .ok_or_else(|| {
+                    failure::format_err!("invalid source file path: {}", path.display())
+                })?;
+            let text = std::fs::read_to_string(&path)?;
+
+            let file_id = self.add_file(source_root_id, rel_path.clone(), text);
+            source_root.insert_file(rel_path, file_id);
+        }
+
+        // Remember where the files are located on disk, so changes to them can be mapped back
+        // onto the source root
+        self.source_dirs.push((
+            source_dir.canonicalize().unwrap_or(source_dir),
+            source_root_id,
+        ));
+        Ok(source_root)
+    }
+
+    /// Stores a new file in the database and returns its `FileId`. The file is not yet part of its
+    /// source root.
+    fn add_file(
+        &mut self,
+        source_root_id: SourceRootId,
+        rel_path: RelativePathBuf,
+        text: String,
+    ) -> FileId {
+        let file_id = FileId(self.next_file_id);
+        self.next_file_id += 1;
+        self.db.hir_db_mut().set_file_relative_path(file_id, rel_path);
+        self.db.hir_db_mut().set_file_text(file_id, Arc::new(text));
+        self.db.hir_db_mut().set_file_source_root(file_id, source_root_id);
+        file_id
+    }
+}
+
+/// Adds the source roots of the direct dependencies of `package` to `source_root`. `source_roots`
+/// maps the names of all previously loaded packages to their source roots.
+fn add_source_root_dependencies(
+    source_root: &mut SourceRoot,
+    package: &Package,
+    source_roots: &[(String, SourceRootId)],
+) -> Result<(), failure::Error> {
+    for name in package.manifest().dependencies.keys() {
+        let (_, id) = source_roots
+            .iter()
+            .find(|(other, _)| other == name)
+            .ok_or_else(|| failure::format_err!("unresolved dependency `{}`", name))?;
+        source_root.add_dependency(*id);
+    }
+    Ok(())
+}
+
+/// Returns true if `path` equals `dir` or is located in the directory `dir`.
+fn is_in_dir(path: &RelativePath, dir: &RelativePath) -> bool {
+    let (path, dir) = (path.as_str(), dir.as_str());
+    dir.is_empty()
+        || path == dir
+        || (path.starts_with(dir) && path[dir.len()..].starts_with('/'))
+}
+
+/// Returns the path of `path` relative to the directory `base`. Both paths must be absolute.
+fn relative_path(base: &Path, path: &Path) -> PathBuf {
+    let base = base.components().collect::<Vec<_>>();
+    let path = path.components().collect::<Vec<_>>();
+    let common = base
+        .iter()
+        .zip(path.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut result = PathBuf::new();
+    for _ in common..base.len() {
+        result.push("..");
+    }
+    for component in &path[common..] {
+        result.push(component.as_os_str());
+    }
+    result
 }
 
 impl<'ink> Driver<'ink> {
@@ -94,6 +279,65 @@ impl<'ink> Driver<'ink> {
         self.db.hir_db_mut()
             .set_file_text(file_id, Arc::new(text.as_ref().to_owned()));
     }
+
+    /// Returns the source root and the relative path of the file at `path`, if it is located in
+    /// one of the source directories of the driver. `path` must be canonicalized.
+    fn source_root_of_path(&self, path: &Path) -> Option<(SourceRootId, RelativePathBuf)> {
+        self.source_dirs.iter().find_map(|(dir, source_root_id)| {
+            let rel_path = path.strip_prefix(dir).ok()?;
+            RelativePathBuf::from_path(rel_path)
+                .ok()
+                .map(|rel_path| (*source_root_id, rel_path))
+        })
+    }
+
+    /// Returns the `FileId` of the file at `path`, if it is part of a source root.
+    pub fn file_id_at_path(&self, path: &Path) -> Option<FileId> {
+        let (source_root_id, rel_path) = self.source_root_of_path(path)?;
+        self.db.hir_db()
+            .source_root(source_root_id)
+            .file_by_relative_path(&rel_path)
+    }
+
+    /// Sets the contents of the file at `path`. If the file is not yet part of a source root, it
+    /// is added to the source root of the source directory that contains it. Returns the source
+    /// root of the file, or `None` if `path` is not located in any source directory.
+    pub fn set_file_text_at_path<T: AsRef<str>>(&mut self, path: &Path, text: T) -> Option<SourceRootId> {
+        let (source_root_id, rel_path) = self.source_root_of_path(path)?;
+        let source_root = self.db.hir_db().source_root(source_root_id);
+        match source_root.file_by_relative_path(&rel_path) {
+            Some(file_id) => self.set_file_text(file_id, text),
+            None => {
+                let mut source_root = (*source_root).clone();
+                let file_id = self.add_file(source_root_id, rel_path.clone(), text.as_ref().to_owned());
+                source_root.insert_file(rel_path, file_id);
+                self.db.hir_db_mut().set_source_root(source_root_id, Arc::new(source_root));
+            }
+        }
+        Some(source_root_id)
+    }
+
+    /// Removes the file at `path` from its source root. If `path` refers to a directory, all files
+    /// in the directory are removed. Returns the source root that the files were removed from, or
+    /// `None` if no files were removed.
+    pub fn remove_file_at_path(&mut self, path: &Path) -> Option<SourceRootId> {
+        let (source_root_id, rel_path) = self.source_root_of_path(path)?;
+        let mut source_root = (*self.db.hir_db().source_root(source_root_id)).clone();
+        let removed = source_root
+            .files()
+            .map(|file_id| self.db.hir_db().file_relative_path(file_id))
+            .filter(|path| is_in_dir(path, &rel_path))
+            .collect::<Vec<_>>();
+        if removed.is_empty() {
+            return None;
+        }
+
+        for path in removed {
+            source_root.remove_file(&path);
+        }
+        self.db.hir_db_mut().set_source_root(source_root_id, Arc::new(source_root));
+        Some(source_root_id)
+    }
 }
 
 impl<'ink> Driver<'ink> {
@@ -107,35 +351,643 @@ impl<'ink> Driver<'ink> {
             .collect()
     }
 
+    /// Returns the suggestions to fix the diagnostics of the specified file.
+    pub fn suggestions(&self, file_id: FileId) -> Vec<Suggestion> {
+        suggestions(self.db.hir_db(), file_id)
+    }
+
     /// Emits all diagnostic messages currently in the database; returns true if errors were
     /// emitted.
     pub fn emit_diagnostics(
         &self,
         writer: &mut dyn std::io::Write,
     ) -> Result<bool, failure::Error> {
-        let mut has_errors = false;
-        let dlf = DisplayListFormatter::new(self.display_color.should_enable(), false);
-        for file_id in self.db.hir_db().source_root(WORKSPACE).files() {
-            let diags = diagnostics(self.db.hir_db(), file_id);
-            for diagnostic in diags {
-                let dl = DisplayList::from(diagnostic.clone());
-                writeln!(writer, "{}", dlf.format(&dl)).unwrap();
-                if let Some(annotation) = diagnostic.title {
-                    if let AnnotationType::Error = annotation.annotation_type {
-                        has_errors = true;
+        if self.timings.is_some() {
+            self.run_front_end_passes();
+        }
+
+        self.measure("diagnostics", || {
+            let mut has_errors = false;
+            let dlf = DisplayListFormatter::new(self.display_color.should_enable(), false);
+            for file_id in self.db.hir_db().source_root(WORKSPACE).files() {
+                let diags = diagnostics(self.db.hir_db(), file_id);
+                for diagnostic in diags {
+                    let dl = DisplayList::from(diagnostic.clone());
+                    writeln!(writer, "{}", dlf.format(&dl)).unwrap();
+                    if let Some(annotation) = diagnostic.title {
+                        if let AnnotationType::Error = annotation.annotation_type {
+                            has_errors = true;
+                        }
+                    }
+                }
+            }
+            Ok(has_errors)
+        })
+    }
+
+    /// Runs the passes of the front-end for all files in the workspace one after the other, so the
+    /// time spent in each of them can be measured. Otherwise salsa interleaves them, because it
+    /// evaluates queries on demand.
+    fn run_front_end_passes(&self) {
+        let db = self.db.hir_db();
+        let files = db.source_root(WORKSPACE).files().collect::<Vec<_>>();
+
+        self.measure("parsing", || {
+            for file_id in files.iter() {
+                db.parse(*file_id);
+            }
+        });
+
+        let definitions = self.measure("HIR lowering", || {
+            let mut definitions = Vec::new();
+            for file_id in files.iter() {
+                for def in db.module_data(*file_id).definitions() {
+                    match def {
+                        ModuleDef::Function(f) => {
+                            f.ty(db);
+                            f.body(db);
+                        }
+                        ModuleDef::Struct(s) => {
+                            s.lower(db);
+                        }
+                        ModuleDef::BuiltinType(_) => (),
                     }
+                    definitions.push(*def);
                 }
             }
+            definitions
+        });
+
+        self.measure("type inference", || {
+            for def in definitions {
+                if let ModuleDef::Function(f) = def {
+                    f.infer(db);
+                }
+            }
+        });
+    }
+
+    /// Runs the pass `f` and records the time it took, if timings are enabled.
+    fn measure<T, F: FnOnce() -> T>(&self, name: &'static str, f: F) -> T {
+        match &self.timings {
+            Some(timings) => {
+                let (result, timing) = PassTiming::measure(name, f);
+                timings.borrow_mut().record(timing);
+                result
+            }
+            None => f(),
         }
-        Ok(has_errors)
+    }
+
+    /// Returns the timings of all passes since the previous call, or `None` if timings are not
+    /// enabled in the configuration.
+    pub fn take_timings(&self) -> Option<Timings> {
+        self.timings.as_ref().map(|timings| {
+            let mut timings = timings.replace(Timings::default());
+            timings.set_queries(self.db.hir_db().take_query_stats());
+            timings
+        })
+    }
+}
+
+impl<'ink> Driver<'ink> {
+    /// Returns the syntax tree of the specified file, followed by its syntax errors.
+    pub fn debug_dump_syntax_tree(&self, file_id: FileId) -> String {
+        self.db.hir_db().parse(file_id).debug_dump()
+    }
+
+    /// Returns the definitions of the specified file, including the inferred types of the
+    /// expressions and patterns in the bodies of its functions.
+    pub fn debug_dump_hir(&self, file_id: FileId) -> String {
+        mun_hir::Module::from(file_id).debug_dump(self.db.hir_db())
+    }
+
+    /// Returns the type table that is emitted in the assembly of the specified file. The file must
+    /// not contain errors.
+    pub fn debug_dump_type_table(&mut self, context: &'ink Context, file_id: FileId) -> String {
+        self.db.type_table_debug_dump(context, file_id)
     }
 }
 
 impl<'ink> Driver<'ink> {
-    /// Generate an assembly for the given file
+    /// Generate an assembly for the given file. Any additional artifacts specified in the
+    /// configuration are written next to it.
     pub fn write_assembly(&mut self, context: &'ink Context, file_id: FileId) -> Result<PathBuf, failure::Error> {
-        let module_builder = ModuleBuilder::new(context, &mut self.db, file_id)?;
-        let obj_file = module_builder.build()?;
-        obj_file.into_shared_object(self.out_dir.as_deref())
+        if let Some(out_dir) = &self.out_dir {
+            std::fs::create_dir_all(out_dir)?;
+        }
+
+        // The paths of dependencies are stored relative to the assembly, so that the runtime can
+        // find them regardless of its working directory.
+        let assembly_dir = self
+            .out_dir
+            .as_ref()
+            .map_or_else(std::env::current_dir, |dir| dir.canonicalize())?;
+        let dependencies = self.dependency_paths(Some(&assembly_dir))?;
+
+        let obj_file = self.module_builder(context, file_id, dependencies)?.build()?;
+        self.codegen_stats = obj_file.stats();
+        self.record_timings(obj_file.timings());
+        if self.emit.iter().any(|ty| *ty != OutputType::Library) {
+            self.measure("writing artifacts", || {
+                for output_type in self.emit.iter().filter(|ty| **ty != OutputType::Library) {
+                    obj_file.write(*output_type, self.out_dir.as_deref())?;
+                }
+                Ok::<_, failure::Error>(())
+            })?;
+        }
+        self.measure("linking", || {
+            obj_file.into_shared_object(self.out_dir.as_deref())
+        })
+    }
+
+    /// Generates an assembly for the given file and returns the contents of its shared library.
+    /// No files are written to the output directory. Because the assembly has no location, the
+    /// paths of its dependencies are stored as absolute paths.
+    pub fn assembly_to_memory(&mut self, context: &'ink Context, file_id: FileId) -> Result<Vec<u8>, failure::Error> {
+        let dependencies = self.dependency_paths(None)?;
+        let obj_file = self.module_builder(context, file_id, dependencies)?.build()?;
+        self.codegen_stats = obj_file.stats();
+        self.record_timings(obj_file.timings());
+        self.measure("linking", || obj_file.into_shared_object_bytes())
+    }
+
+    /// Records the timings of passes that were measured elsewhere, if timings are enabled.
+    fn record_timings(&self, passes: &[PassTiming]) {
+        if let Some(timings) = &self.timings {
+            let mut timings = timings.borrow_mut();
+            for pass in passes {
+                timings.record(pass.clone());
+            }
+        }
+    }
+
+    /// Returns the paths of the assemblies that the workspace depends on, relative to
+    /// `assembly_dir` if specified or absolute otherwise.
+    fn dependency_paths(&self, assembly_dir: Option<&Path>) -> Result<Vec<String>, failure::Error> {
+        self.dependency_assemblies
+            .iter()
+            .map(|path| {
+                let path = path.canonicalize().with_context(|_| {
+                    format!("dependency `{}` has not been built", path.display())
+                })?;
+                let path = match assembly_dir {
+                    Some(assembly_dir) => relative_path(assembly_dir, &path),
+                    None => path,
+                };
+                Ok(path.to_string_lossy().into_owned())
+            })
+            .collect()
+    }
+
+    /// Constructs the builder for the assembly of the given file.
+    fn module_builder<'a>(
+        &'a mut self,
+        context: &'ink Context,
+        file_id: FileId,
+        dependencies: Vec<String>,
+    ) -> Result<ModuleBuilder<'a, 'ink, CompilerDatabase>, failure::Error> {
+        let mut module_builder =
+            ModuleBuilder::new(context, &mut self.db, file_id)?.with_dependencies(dependencies);
+        if let Some(package_name) = &self.package_name {
+            module_builder = module_builder.with_output_name(package_name.as_str());
+        }
+        Ok(module_builder)
+    }
+
+    /// Returns statistics about the code generation of the most recently written assembly, e.g. how
+    /// many functions were reused from the previous compilation.
+    pub fn codegen_stats(&self) -> CodegenStats {
+        self.codegen_stats
+    }
+
+    /// Generates the assembly of all files in the workspace. All files are compiled into a single
+    /// assembly, which is named after the package if the driver was constructed from one.
+    pub fn write_package_assembly(&mut self, context: &'ink Context) -> Result<PathBuf, failure::Error> {
+        let file_id = self
+            .db
+            .hir_db()
+            .source_root(WORKSPACE)
+            .files()
+            .min()
+            .ok_or_else(|| failure::err_msg("there are no files to compile"))?;
+        self.write_assembly(context, file_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Config, OptimizationLevel};
+
+    #[test]
+    fn optimization_lvl() {
+        let config = Config {
+            optimization_lvl: OptimizationLevel::MinSize,
+            ..Config::default()
+        };
+        let driver = Driver::with_config(config);
+        assert_eq!(driver.db.optimization_lvl(), OptimizationLevel::MinSize);
+    }
+
+    #[test]
+    fn emit_artifacts() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let config = Config {
+            out_dir: Some(out_dir.path().to_path_buf()),
+            emit: vec![
+                OutputType::LlvmIr,
+                OutputType::LlvmBitcode,
+                OutputType::Assembly,
+                OutputType::Object,
+                OutputType::Library,
+            ],
+            ..Config::default()
+        };
+        let input = PathOrInline::Inline {
+            rel_path: RelativePathBuf::from("main.mun"),
+            contents: "pub fn main() -> i32 { 5 }".to_owned(),
+        };
+
+        let context = Context::create();
+        let (mut driver, file_id) = Driver::with_file(config, input).unwrap();
+        let assembly_path = driver.write_assembly(&context, file_id).unwrap();
+        assert_eq!(assembly_path, out_dir.path().join("main.munlib"));
+
+        for extension in &["ll", "bc", "s", "o", "munlib"] {
+            let path = out_dir.path().join("main").with_extension(extension);
+            assert!(path.is_file(), "missing {}", path.display());
+        }
+    }
+
+    #[test]
+    fn incremental_codegen() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let config = Config {
+            out_dir: Some(out_dir.path().to_path_buf()),
+            ..Config::default()
+        };
+        let input = PathOrInline::Inline {
+            rel_path: RelativePathBuf::from("main.mun"),
+            contents: "fn foo() -> i32 { 1 }\n\npub fn bar() -> i32 { 2 }\n\npub fn main() -> i32 { foo() + bar() }".to_owned(),
+        };
+
+        let context = Context::create();
+        let (mut driver, file_id) = Driver::with_file(config, input).unwrap();
+        driver.write_assembly(&context, file_id).unwrap();
+        assert_eq!(
+            driver.codegen_stats(),
+            CodegenStats {
+                generated_functions: 3,
+                reused_functions: 0
+            }
+        );
+
+        // Only the modified function is regenerated
+        driver.set_file_text(
+            file_id,
+            "fn foo() -> i32 { 1 }\n\npub fn bar() -> i32 { 3 }\n\npub fn main() -> i32 { foo() + bar() }",
+        );
+        driver.write_assembly(&context, file_id).unwrap();
+        assert_eq!(
+            driver.codegen_stats(),
+            CodegenStats {
+                generated_functions: 1,
+                reused_functions: 2
+            }
+        );
+
+        // Changing a signature only affects the functions that use it
+        driver.set_file_text(
+            file_id,
+            "fn foo() -> i32 { 1 }\n\npub fn bar() -> i64 { 3 }\n\npub fn main() -> i32 { foo() + 1 }",
+        );
+        driver.write_assembly(&context, file_id).unwrap();
+        assert_eq!(
+            driver.codegen_stats(),
+            CodegenStats {
+                generated_functions: 2,
+                reused_functions: 1
+            }
+        );
+
+        // Adding a function does not affect the functions that do not use it
+        driver.set_file_text(
+            file_id,
+            "fn foo() -> i32 { 1 }\n\npub fn bar() -> i64 { 3 }\n\npub fn main() -> i32 { foo() + 1 }\n\nfn baz() -> i32 { 4 }",
+        );
+        driver.write_assembly(&context, file_id).unwrap();
+        assert_eq!(
+            driver.codegen_stats(),
+            CodegenStats {
+                generated_functions: 1,
+                reused_functions: 3
+            }
+        );
+
+        driver.write_assembly(&context, file_id).unwrap();
+        assert_eq!(
+            driver.codegen_stats(),
+            CodegenStats {
+                generated_functions: 0,
+                reused_functions: 4
+            }
+        );
+    }
+
+    #[test]
+    fn incremental_codegen_with_debug_info() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let config = Config {
+            out_dir: Some(out_dir.path().to_path_buf()),
+            debug_info: true,
+            ..Config::default()
+        };
+        let input = PathOrInline::Inline {
+            rel_path: RelativePathBuf::from("main.mun"),
+            contents: "fn foo() -> i32 { 1 }\n\npub fn bar() -> i32 { 2 }\n\npub fn main() -> i32 { foo() + bar() }".to_owned(),
+        };
+
+        let context = Context::create();
+        let (mut driver, file_id) = Driver::with_file(config, input).unwrap();
+        driver.write_assembly(&context, file_id).unwrap();
+
+        // Only the modified function is regenerated
+        driver.set_file_text(
+            file_id,
+            "fn foo() -> i32 { 1 }\n\npub fn bar() -> i32 { 3 }\n\npub fn main() -> i32 { foo() + bar() }",
+        );
+        driver.write_assembly(&context, file_id).unwrap();
+        assert_eq!(
+            driver.codegen_stats(),
+            CodegenStats {
+                generated_functions: 1,
+                reused_functions: 2
+            }
+        );
+
+        // Moving a function changes the locations in its debug information
+        driver.set_file_text(
+            file_id,
+            "fn foo() -> i32 { 1 }\n\npub fn bar() -> i32 { 3 }\n\n\npub fn main() -> i32 { foo() + bar() }",
+        );
+        driver.write_assembly(&context, file_id).unwrap();
+        assert_eq!(
+            driver.codegen_stats(),
+            CodegenStats {
+                generated_functions: 1,
+                reused_functions: 2
+            }
+        );
+    }
+
+    #[test]
+    fn package_assembly() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path().join("foo");
+        crate::new_package(&root).unwrap();
+        std::fs::write(
+            root.join("src").join("bar.mun"),
+            "pub fn bar() -> i32 { 3 }",
+        )
+        .unwrap();
+
+        let package = Package::discover(&root).unwrap();
+        let context = Context::create();
+        let mut driver = Driver::with_package(package.config().unwrap(), &package).unwrap();
+        let assembly_path = driver.write_package_assembly(&context).unwrap();
+        assert_eq!(assembly_path, root.join("target").join("foo.munlib"));
+        assert!(assembly_path.is_file());
+
+        // Definitions with the same name in different files would clash
+        std::fs::write(
+            root.join("src").join("baz.mun"),
+            "pub fn bar() -> i32 { 4 }",
+        )
+        .unwrap();
+        let mut driver = Driver::with_package(package.config().unwrap(), &package).unwrap();
+        assert!(driver.write_package_assembly(&context).is_err());
+    }
+
+    #[test]
+    fn timings() {
+        let driver = Driver::with_config(Config::default());
+        assert!(driver.take_timings().is_none());
+
+        let out_dir = tempfile::tempdir().unwrap();
+        let config = Config {
+            out_dir: Some(out_dir.path().to_path_buf()),
+            timings: true,
+            ..Config::default()
+        };
+        let input = PathOrInline::Inline {
+            rel_path: RelativePathBuf::from("main.mun"),
+            contents: "struct Foo { a: i32 }\n\npub fn main() -> i32 { Foo { a: 5 }.a }".to_owned(),
+        };
+
+        let context = Context::create();
+        let (mut driver, file_id) = Driver::with_file(config, input).unwrap();
+        assert!(!driver.emit_diagnostics(&mut Vec::new()).unwrap());
+        driver.write_assembly(&context, file_id).unwrap();
+
+        let timings = driver.take_timings().unwrap();
+        let passes = timings
+            .passes()
+            .iter()
+            .map(|pass| pass.name)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            passes,
+            [
+                "parsing",
+                "HIR lowering",
+                "type inference",
+                "diagnostics",
+                "IR generation",
+                "LLVM optimization",
+                "machine code generation",
+                "linking"
+            ]
+        );
+        assert!(timings.queries().executions > 0);
+
+        let mut table = Vec::new();
+        timings.write_table(&mut table).unwrap();
+        let table = String::from_utf8(table).unwrap();
+        for pass in passes.iter() {
+            assert!(table.contains(pass), "missing {} in table", pass);
+        }
+
+        let mut trace = Vec::new();
+        timings.write_chrome_trace(&mut trace).unwrap();
+        let trace: serde_json::Value = serde_json::from_slice(&trace).unwrap();
+        assert_eq!(
+            trace["traceEvents"].as_array().unwrap().len(),
+            passes.len() + 1
+        );
+
+        // Timings are reset when they are taken; unaffected queries are reused by the next build
+        assert!(driver.take_timings().unwrap().passes().is_empty());
+        driver.set_file_text(
+            file_id,
+            "struct Foo { a: i32 }\n\npub fn main() -> i32 { Foo { a: 6 }.a }",
+        );
+        assert!(!driver.emit_diagnostics(&mut Vec::new()).unwrap());
+        driver.write_assembly(&context, file_id).unwrap();
+        assert!(driver.take_timings().unwrap().queries().revalidations > 0);
+    }
+
+    #[test]
+    fn query_stats_after_unrelated_edit() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path().join("foo");
+        crate::new_package(&root).unwrap();
+        let bar_path = root.join("src").join("bar.mun");
+        std::fs::write(&bar_path, "pub fn bar() -> i32 { 3 }").unwrap();
+
+        let package = Package::discover(&root).unwrap();
+        let config = Config {
+            timings: true,
+            ..package.config().unwrap()
+        };
+        let context = Context::create();
+        let mut driver = Driver::with_package(config, &package).unwrap();
+        assert!(!driver.emit_diagnostics(&mut Vec::new()).unwrap());
+        driver.write_package_assembly(&context).unwrap();
+        assert!(driver.take_timings().unwrap().queries().executions > 0);
+
+        // The queries of the file that was not edited are revalidated instead of executed
+        driver
+            .set_file_text_at_path(
+                &bar_path.canonicalize().unwrap(),
+                "pub fn bar() -> i32 { 4 }",
+            )
+            .unwrap();
+        assert!(!driver.emit_diagnostics(&mut Vec::new()).unwrap());
+        driver.write_package_assembly(&context).unwrap();
+        let queries = driver.take_timings().unwrap().queries();
+        assert!(queries.executions > 0);
+        assert!(queries.revalidations > 0);
+    }
+
+    #[test]
+    fn deterministic_output() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path().join("foo");
+        crate::new_package(&root).unwrap();
+        std::fs::write(
+            root.join("src").join("bar.mun"),
+            "struct(gc) Foo { a: i32, b: f64 }\n\nstruct(value) Bar { foo: Foo }\n\npub fn a(foo: Foo) -> i32 { foo.a }\n\npub fn b(bar: Bar) -> f64 { bar.foo.b }\n\npub fn c() -> Bar { Bar { foo: Foo { a: 1, b: 2.0 } } }",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("src").join("baz.mun"),
+            "pub fn d() -> i32 { a(c().foo) }\n\npub fn e() -> f64 { b(c()) }\n\nfn f() -> i32 { d() }",
+        )
+        .unwrap();
+        let package = Package::discover(&root).unwrap();
+
+        let compile = |debug_info: bool| {
+            let out_dir = tempfile::tempdir().unwrap();
+            let config = Config {
+                out_dir: Some(out_dir.path().to_path_buf()),
+                debug_info,
+                ..package.config().unwrap()
+            };
+            let context = Context::create();
+            let mut driver = Driver::with_package(config, &package).unwrap();
+            let assembly_path = driver.write_package_assembly(&context).unwrap();
+            std::fs::read(assembly_path).unwrap()
+        };
+
+        for debug_info in &[false, true] {
+            assert!(
+                compile(*debug_info) == compile(*debug_info),
+                "compiling twice produced different assemblies (debug_info: {})",
+                debug_info
+            );
+        }
+    }
+
+    #[test]
+    fn update_files_by_path() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path().join("foo");
+        crate::new_package(&root).unwrap();
+
+        let package = Package::discover(&root).unwrap();
+        let source_dir = package.source_dir().canonicalize().unwrap();
+        let mut driver = Driver::with_package(package.config().unwrap(), &package).unwrap();
+        assert!(driver.diagnostics().is_empty());
+
+        // Files outside of the source directory are ignored
+        assert_eq!(
+            driver.set_file_text_at_path(&root.canonicalize().unwrap().join("foo.mun"), ""),
+            None
+        );
+
+        // New files are added to the workspace
+        let nested = source_dir.join("nested").join("bar.mun");
+        assert_eq!(
+            driver.set_file_text_at_path(&nested, "pub fn bar() -> i32 { true }"),
+            Some(WORKSPACE)
+        );
+        let file_id = driver.file_id_at_path(&nested).unwrap();
+        assert_eq!(driver.diagnostics().len(), 1);
+
+        // Existing files are updated
+        assert_eq!(
+            driver.set_file_text_at_path(&nested, "pub fn bar() -> i32 { 3 }"),
+            Some(WORKSPACE)
+        );
+        assert_eq!(driver.file_id_at_path(&nested), Some(file_id));
+        assert!(driver.diagnostics().is_empty());
+
+        // Removing a directory removes all files it contains
+        driver.set_file_text_at_path(&nested, "pub fn bar() -> i32 { true }");
+        assert_eq!(
+            driver.remove_file_at_path(&source_dir.join("nested")),
+            Some(WORKSPACE)
+        );
+        assert_eq!(driver.file_id_at_path(&nested), None);
+        assert!(driver.diagnostics().is_empty());
+        assert!(driver.file_id_at_path(&source_dir.join("main.mun")).is_some());
+    }
+
+    #[test]
+    fn debug_info() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let config = Config {
+            out_dir: Some(out_dir.path().to_path_buf()),
+            emit: vec![OutputType::LlvmIr],
+            debug_info: true,
+            ..Config::default()
+        };
+        let input = PathOrInline::Inline {
+            rel_path: RelativePathBuf::from("main.mun"),
+            contents: "struct Foo { a: i32 }\n\nfn zero() -> i32 { 0 }\n\npub fn main(b: i32) -> i32 {\n    let foo = Foo { a: b };\n    if b > 0 { foo.a } else { zero() }\n}".to_owned(),
+        };
+
+        let context = Context::create();
+        let (mut driver, file_id) = Driver::with_file(config, input).unwrap();
+        let assembly_path = driver.write_assembly(&context, file_id).unwrap();
+        assert!(assembly_path.is_file());
+
+        let ir = std::fs::read_to_string(out_dir.path().join("main.ll")).unwrap();
+        assert!(ir.contains("!\"Debug Info Version\""));
+        assert!(ir.contains("DISubprogram(name: \"main\""));
+        assert!(ir.contains("DILocalVariable(name: \"b\", arg: 1"));
+        assert!(ir.contains("DILocalVariable(name: \"foo\""));
+        assert!(ir.contains("DICompositeType(tag: DW_TAG_structure_type, name: \"Foo\""));
+        // Struct handles are pointers in the generic address space
+        assert!(ir.contains("DIDerivedType(tag: DW_TAG_pointer_type, name: \"**Foo\""));
+        assert!(!ir.contains("dwarfAddressSpace"));
+        assert!(ir.contains("DILexicalBlock("));
+        assert!(ir.contains("DISubprogram(name: \"zero\""));
+
+        // Every function is described by a compile unit of its own, but the module flags of the
+        // functions are merged
+        assert_eq!(ir.matches("distinct !DICompileUnit(").count(), 2);
+        assert_eq!(ir.matches("!\"Debug Info Version\"").count(), 1);
     }
 }
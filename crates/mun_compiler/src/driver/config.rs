@@ -1,5 +1,5 @@
 use crate::DisplayColor;
-pub use mun_codegen::OptimizationLevel;
+pub use mun_codegen::{OptimizationLevel, OutputType};
 use mun_target::spec::Target;
 use std::path::PathBuf;
 
@@ -18,6 +18,15 @@ pub struct Config {
 
     /// Whether or not to use colors in terminal output
     pub display_color: DisplayColor,
+
+    /// Additional artifacts to write next to the assembly, e.g. LLVM IR for inspection.
+    pub emit: Vec<OutputType>,
+
+    /// Whether or not to generate DWARF debug information.
+    pub debug_info: bool,
+
+    /// Whether or not to measure the time spent in each compiler pass.
+    pub timings: bool,
 }
 
 impl Default for Config {
@@ -30,6 +39,9 @@ impl Default for Config {
             optimization_lvl: OptimizationLevel::Default,
             out_dir: None,
             display_color: DisplayColor::Auto,
+            emit: Vec::new(),
+            debug_info: false,
+            timings: false,
         }
     }
 }
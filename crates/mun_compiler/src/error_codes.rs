@@ -0,0 +1,70 @@
+//! Long-form explanations of the diagnostic codes emitted by the compiler. These are displayed by
+//! `mun explain <code>`.
+
+macro_rules! register_explanations {
+    ($($code:literal,)*) => {
+        /// Returns the long-form explanation of a diagnostic code. The code may be specified with or
+        /// without the leading `E` (e.g. `E0005` or `0005`).
+        pub fn explain(code: &str) -> Option<&'static str> {
+            let code = code.trim().trim_start_matches(|c| c == 'E' || c == 'e');
+            match code {
+                $($code => Some(include_str!(concat!("error_codes/E", $code, ".md"))),)*
+                _ => None,
+            }
+        }
+    };
+}
+
+register_explanations! {
+    "0001",
+    "0002",
+    "0003",
+    "0004",
+    "0005",
+    "0006",
+    "0007",
+    "0008",
+    "0009",
+    "0010",
+    "0011",
+    "0012",
+    "0013",
+    "0014",
+    "0015",
+    "0016",
+    "0017",
+    "0018",
+    "0019",
+    "0020",
+    "0021",
+    "0022",
+    "0023",
+    "0024",
+    "0025",
+    "0026",
+    "0027",
+    "0028",
+    "0029",
+}
+
+#[cfg(test)]
+mod tests {
+    use super::explain;
+
+    #[test]
+    fn all_codes_have_explanations() {
+        for code in mun_hir::diagnostics::codes::ALL {
+            assert!(
+                explain(code.as_str()).is_some(),
+                "missing explanation for {}",
+                code
+            );
+        }
+    }
+
+    #[test]
+    fn explain_without_prefix() {
+        assert_eq!(explain("E0005"), explain("0005"));
+        assert!(explain("E9999").is_none());
+    }
+}
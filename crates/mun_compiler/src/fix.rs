@@ -0,0 +1,137 @@
+//! Applies machine-applicable suggestions of diagnostics to source files.
+
+use crate::{CompilerOptions, Driver, PathOrInline};
+use mun_hir::diagnostics::{Applicability, Suggestion, TextEdit};
+
+/// Applies the suggestions to `text`. Suggestions whose edits overlap with the edits of a previously
+/// applied suggestion are skipped. Returns the resulting text and the number of applied
+/// suggestions.
+pub fn apply_suggestions(text: &str, suggestions: &[Suggestion]) -> (String, usize) {
+    let mut applied = 0;
+    let mut edits: Vec<&TextEdit> = Vec::new();
+    for suggestion in suggestions {
+        let overlaps = suggestion.edits.iter().any(|edit| {
+            edits.iter().any(|other| {
+                let (a, b) = (edit.range.range, other.range.range);
+                (a.start() < b.end() && b.start() < a.end()) || a.start() == b.start()
+            })
+        });
+        if !overlaps {
+            edits.extend(suggestion.edits.iter());
+            applied += 1;
+        }
+    }
+
+    // Apply the edits back to front so the offsets of the remaining edits stay valid
+    edits.sort_by_key(|edit| edit.range.range.start());
+    let mut result = text.to_owned();
+    for edit in edits.into_iter().rev() {
+        let range = edit.range.range;
+        result.replace_range(
+            range.start().to_usize()..range.end().to_usize(),
+            &edit.insert,
+        );
+    }
+
+    (result, applied)
+}
+
+/// Applies all machine-applicable suggestions to the input file and writes the result back to
+/// disk. Returns the number of applied suggestions.
+pub fn fix(options: CompilerOptions) -> Result<usize, failure::Error> {
+    let path = match &options.input {
+        PathOrInline::Path(path) => path.clone(),
+        PathOrInline::Inline { .. } => {
+            return Err(failure::err_msg("only files on disk can be fixed"));
+        }
+    };
+
+    let (driver, file_id) = Driver::with_file(options.config, options.input)?;
+    let suggestions = driver
+        .suggestions(file_id)
+        .into_iter()
+        .filter(|s| s.applicability == Applicability::MachineApplicable)
+        .collect::<Vec<_>>();
+    if suggestions.is_empty() {
+        return Ok(0);
+    }
+
+    let text = std::fs::read_to_string(&path)?;
+    let (text, applied) = apply_suggestions(&text, &suggestions);
+    std::fs::write(&path, text)?;
+
+    Ok(applied)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::apply_suggestions;
+    use crate::{Config, DisplayColor, Driver, PathOrInline, RelativePathBuf};
+    use mun_hir::diagnostics::Applicability;
+
+    /// Applies all machine-applicable suggestions to the source code
+    fn fix(source_code: &str) -> String {
+        let config = Config {
+            display_color: DisplayColor::Disable,
+            ..Config::default()
+        };
+
+        let input = PathOrInline::Inline {
+            rel_path: RelativePathBuf::from("main.mun"),
+            contents: source_code.to_owned(),
+        };
+
+        let (driver, file_id) = Driver::with_file(config, input).unwrap();
+        let suggestions = driver
+            .suggestions(file_id)
+            .into_iter()
+            .filter(|s| s.applicability == Applicability::MachineApplicable)
+            .collect::<Vec<_>>();
+
+        apply_suggestions(source_code, &suggestions).0
+    }
+
+    /// Asserts that the source code compiles without diagnostics
+    fn assert_no_diagnostics(source_code: &str) {
+        let input = PathOrInline::Inline {
+            rel_path: RelativePathBuf::from("main.mun"),
+            contents: source_code.to_owned(),
+        };
+        let (driver, _) = Driver::with_file(Config::default(), input).unwrap();
+        assert!(driver.diagnostics().is_empty(), "{}", source_code);
+    }
+
+    #[test]
+    fn fix_missing_fields() {
+        let fixed = fix("struct Foo { a: i32, b: f64, c: bool }\n\nfn main() {\n    let a = Foo { a: 3 };\n    let b = Foo { a: 3, };\n    let c = Foo {};\n}");
+        assert_eq!(
+            fixed,
+            "struct Foo { a: i32, b: f64, c: bool }\n\nfn main() {\n    let a = Foo { a: 3, b: 0.0, c: false };\n    let b = Foo { a: 3, b: 0.0, c: false, };\n    let c = Foo { a: 0, b: 0.0, c: false };\n}"
+        );
+        assert_no_diagnostics(&fixed);
+    }
+
+    #[test]
+    fn fix_missing_else_branch() {
+        let fixed = fix("fn main(a: bool) -> i32 {\n    if a { 5 }\n}");
+        assert_eq!(
+            fixed,
+            "fn main(a: bool) -> i32 {\n    if a { 5 } else { 0 }\n}"
+        );
+        assert_no_diagnostics(&fixed);
+    }
+
+    #[test]
+    fn fix_invalid_literal_suffix() {
+        let fixed = fix("fn main() {\n    let a = 123_foo;\n    let b = 1.0_bar;\n}");
+        assert_eq!(fixed, "fn main() {\n    let a = 123;\n    let b = 1.0;\n}");
+        assert_no_diagnostics(&fixed);
+    }
+
+    #[test]
+    fn fix_return_missing_expression() {
+        let fixed = fix("fn main() -> bool {\n    return;\n}");
+        assert_eq!(fixed, "fn main() -> bool {\n    return false;\n}");
+        assert_no_diagnostics(&fixed);
+    }
+}
@@ -1,23 +1,36 @@
 #![allow(clippy::enum_variant_names)] // This is a HACK because we use salsa
 mod annotate;
 mod db;
+mod debug_dump;
 ///! This library contains the code required to go from source code to binaries.
 mod diagnostics;
 mod diagnostics_snippets;
 mod driver;
+mod error_codes;
+mod fix;
+mod manifest;
+mod timings;
 
 pub use mun_hir::{FileId, RelativePath, RelativePathBuf};
 pub use mun_target::spec::Target;
 use std::path::{Path, PathBuf};
 
+pub use crate::debug_dump::{debug_dump, DebugDump};
 pub use crate::driver::DisplayColor;
 pub use crate::driver::{Config, Driver};
+pub use crate::error_codes::explain;
+pub use crate::fix::{apply_suggestions, fix};
+pub use crate::manifest::{
+    find_manifest, init_package, new_package, parse_opt_level, DependencyManifest, Manifest,
+    Package, PackageManifest, MANIFEST_FILENAME,
+};
+pub use crate::timings::{PassTiming, QueryStats, Timings};
 pub use annotate::{AnnotationBuilder, SliceBuilder, SnippetBuilder};
-pub use mun_codegen::OptimizationLevel;
+pub use mun_codegen::{OptimizationLevel, OutputType};
 
 use mun_codegen::Context;
 
-use std::io::stderr;
+use std::io::{stderr, Write};
 
 #[derive(Debug, Clone)]
 pub enum PathOrInline {
@@ -66,6 +79,83 @@ pub fn main(options: CompilerOptions) -> Result<Option<PathBuf>, failure::Error>
     if driver.emit_diagnostics(&mut stderr())? {
         Ok(None)
     } else {
-        driver.write_assembly(&context, file_id).map(Some)
+        let assembly_path = driver.write_assembly(&context, file_id)?;
+        if let Some(timings) = driver.take_timings() {
+            report_timings(&timings, &assembly_path)?;
+        }
+        Ok(Some(assembly_path))
     }
 }
+
+/// Compiles the input into an assembly and returns the contents of its shared library, without
+/// writing any files. If the input contains errors, the returned error contains the diagnostics.
+pub fn compile_to_memory(options: CompilerOptions) -> Result<Vec<u8>, failure::Error> {
+    let context = Context::create();
+    let (mut driver, file_id) = Driver::with_file(options.config, options.input)?;
+
+    let mut diagnostics = Vec::new();
+    if driver.emit_diagnostics(&mut diagnostics)? {
+        Err(failure::err_msg(
+            String::from_utf8_lossy(&diagnostics).into_owned(),
+        ))
+    } else {
+        driver.assembly_to_memory(&context, file_id)
+    }
+}
+
+/// Compiles all source files of a package into a single assembly. The dependencies of the package
+/// are compiled first, each into its own assembly in the output directory of the dependency. The
+/// package itself is compiled into `config.out_dir` or, if none is specified, the output directory
+/// of the package. Returns `None` if errors were emitted.
+pub fn compile_package(package: &Package, config: Config) -> Result<Option<PathBuf>, failure::Error> {
+    let context = Context::create();
+
+    // Dependencies are compiled with the same configuration as the package itself
+    for dependency in package.resolve_dependencies()? {
+        let config = Config {
+            out_dir: Some(dependency.output_dir()),
+            ..config.clone()
+        };
+        let mut driver = Driver::with_package(config, &dependency)?;
+        if driver.emit_diagnostics(&mut stderr())? {
+            return Ok(None);
+        }
+        let assembly_path = driver.write_package_assembly(&context)?;
+        if let Some(timings) = driver.take_timings() {
+            report_timings(&timings, &assembly_path)?;
+        }
+    }
+
+    // Like its dependencies, the package is compiled into its own output directory by default
+    let config = Config {
+        out_dir: config.out_dir.or_else(|| Some(package.output_dir())),
+        ..config
+    };
+    let mut driver = Driver::with_package(config, package)?;
+
+    if driver.emit_diagnostics(&mut stderr())? {
+        Ok(None)
+    } else {
+        let assembly_path = driver.write_package_assembly(&context)?;
+        if let Some(timings) = driver.take_timings() {
+            report_timings(&timings, &assembly_path)?;
+        }
+        Ok(Some(assembly_path))
+    }
+}
+
+/// Prints the timings of the compilation of an assembly to stderr as a table, and writes them as
+/// a Chrome trace next to the assembly, e.g. `main.timings.json` for `main.munlib`. Returns the
+/// path of the trace.
+pub fn report_timings(timings: &Timings, assembly_path: &Path) -> Result<PathBuf, failure::Error> {
+    let trace_path = assembly_path.with_extension("timings.json");
+    let mut trace = std::io::BufWriter::new(std::fs::File::create(&trace_path)?);
+    timings.write_chrome_trace(&mut trace)?;
+    trace.flush()?;
+
+    let mut writer = stderr();
+    writeln!(writer, "Timings of {}:", assembly_path.display())?;
+    timings.write_table(&mut writer)?;
+    writeln!(writer, "Trace written to {}", trace_path.display())?;
+    Ok(trace_path)
+}
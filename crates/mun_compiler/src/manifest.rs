@@ -0,0 +1,440 @@
+//! A package is described by a `mun.toml` manifest file. The manifest specifies the name of the
+//! package, where its source files are located, and how it should be compiled. All source files
+//! of a package are compiled into a single assembly.
+//!
+//! ```toml
+//! [package]
+//! name = "hello_world"
+//! source-dir = "src"      # default: "src"
+//! output-dir = "target"   # default: "target"
+//! target = "x86_64-unknown-linux-gnu"  # default: the host target
+//! opt-level = 2           # default: 2, possible values are 0-3, "s" and "z"
+//!
+//! [dependencies]
+//! math = { path = "../math" }
+//! ```
+//!
+//! A package can depend on other local packages. The public functions and the structs of a
+//! dependency are visible from the package's source files. Every package is compiled into its own
+//! assembly, which loads the assemblies of its dependencies at runtime.
+
+use crate::{Config, OptimizationLevel, OutputType, Target};
+use failure::{format_err, ResultExt};
+use serde::{Deserialize, Deserializer};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// The filename of a package manifest.
+pub const MANIFEST_FILENAME: &str = "mun.toml";
+
+/// The file extension of Mun source files.
+const SOURCE_FILE_EXTENSION: &str = "mun";
+
+/// The contents of a `mun.toml` file.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Manifest {
+    pub package: PackageManifest,
+
+    /// The packages this package depends on, by name
+    #[serde(default)]
+    pub dependencies: BTreeMap<String, DependencyManifest>,
+}
+
+/// The `[package]` section of a manifest.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct PackageManifest {
+    /// The name of the package, which is also the name of the assembly
+    pub name: String,
+
+    /// The version of the package
+    pub version: Option<String>,
+
+    /// The directory that contains the source files, relative to the manifest
+    #[serde(default = "default_source_dir")]
+    pub source_dir: PathBuf,
+
+    /// The directory in which the assembly is stored, relative to the manifest
+    #[serde(default = "default_output_dir")]
+    pub output_dir: PathBuf,
+
+    /// The target triple to compile the package for, defaults to the host target
+    pub target: Option<String>,
+
+    /// The optimization level to compile the package with
+    #[serde(default, deserialize_with = "deserialize_opt_level")]
+    pub opt_level: Option<OptimizationLevel>,
+}
+
+/// An entry of the `[dependencies]` section of a manifest.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct DependencyManifest {
+    /// The directory that contains the manifest of the dependency, relative to the manifest
+    pub path: PathBuf,
+}
+
+fn default_source_dir() -> PathBuf {
+    PathBuf::from("src")
+}
+
+fn default_output_dir() -> PathBuf {
+    PathBuf::from("target")
+}
+
+/// Parses an optimization level as specified on the command line: `0`-`3`, `s` or `z`.
+pub fn parse_opt_level(value: &str) -> Option<OptimizationLevel> {
+    match value {
+        "0" => Some(OptimizationLevel::None),
+        "1" => Some(OptimizationLevel::Less),
+        "2" => Some(OptimizationLevel::Default),
+        "3" => Some(OptimizationLevel::Aggressive),
+        "s" => Some(OptimizationLevel::Size),
+        "z" => Some(OptimizationLevel::MinSize),
+        _ => None,
+    }
+}
+
+/// Deserializes an optimization level that is either specified as an integer or as a string.
+fn deserialize_opt_level<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<OptimizationLevel>, D::Error> {
+    struct OptLevelVisitor;
+
+    impl<'de> serde::de::Visitor<'de> for OptLevelVisitor {
+        type Value = Option<OptimizationLevel>;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("an optimization level 0-3, \"s\" or \"z\"")
+        }
+
+        fn visit_i64<E: serde::de::Error>(self, value: i64) -> Result<Self::Value, E> {
+            self.visit_str(&value.to_string())
+        }
+
+        fn visit_str<E: serde::de::Error>(self, value: &str) -> Result<Self::Value, E> {
+            parse_opt_level(value)
+                .map(Some)
+                .ok_or_else(|| E::invalid_value(serde::de::Unexpected::Str(value), &self))
+        }
+    }
+
+    deserializer.deserialize_any(OptLevelVisitor)
+}
+
+/// A package on disk: a manifest together with the directory that contains it.
+#[derive(Debug, Clone)]
+pub struct Package {
+    manifest: Manifest,
+    root: PathBuf,
+}
+
+impl Package {
+    /// Reads the package from the manifest at the specified path.
+    pub fn from_file<P: AsRef<Path>>(manifest_path: P) -> Result<Package, failure::Error> {
+        let manifest_path = manifest_path.as_ref();
+        let contents = std::fs::read_to_string(manifest_path)
+            .with_context(|_| format!("could not read {}", manifest_path.display()))?;
+        let manifest: Manifest = toml::from_str(&contents)
+            .with_context(|_| format!("could not parse {}", manifest_path.display()))?;
+
+        let root = manifest_path
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
+        Ok(Package { manifest, root })
+    }
+
+    /// Searches for a manifest in `dir` and its ancestors and reads the first one found.
+    pub fn discover<P: AsRef<Path>>(dir: P) -> Result<Package, failure::Error> {
+        let dir = dir.as_ref();
+        let manifest_path = find_manifest(dir).ok_or_else(|| {
+            format_err!(
+                "could not find `{}` in `{}` or any parent directory",
+                MANIFEST_FILENAME,
+                dir.display()
+            )
+        })?;
+        Package::from_file(manifest_path)
+    }
+
+    /// Returns the manifest of the package.
+    pub fn manifest(&self) -> &Manifest {
+        &self.manifest
+    }
+
+    /// Returns the name of the package.
+    pub fn name(&self) -> &str {
+        &self.manifest.package.name
+    }
+
+    /// Returns the directory that contains the manifest.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Returns the directory that contains the source files of the package.
+    pub fn source_dir(&self) -> PathBuf {
+        self.root.join(&self.manifest.package.source_dir)
+    }
+
+    /// Returns the directory in which the assembly of the package is stored.
+    pub fn output_dir(&self) -> PathBuf {
+        self.root.join(&self.manifest.package.output_dir)
+    }
+
+    /// Returns the compiler configuration described by the manifest. Settings that are not
+    /// specified by the manifest have their default value.
+    pub fn config(&self) -> Result<Config, failure::Error> {
+        let package = &self.manifest.package;
+        let mut config = Config::default();
+        if let Some(target) = &package.target {
+            config.target = Target::search(target)?;
+        }
+        if let Some(opt_level) = package.opt_level {
+            config.optimization_lvl = opt_level;
+        }
+        config.out_dir = Some(self.output_dir());
+        Ok(config)
+    }
+
+    /// Returns the path of the assembly that is built from the package.
+    pub fn assembly_path(&self) -> PathBuf {
+        self.output_dir()
+            .join(self.name())
+            .with_extension(OutputType::Library.extension())
+    }
+
+    /// Reads the manifests of the direct dependencies of the package.
+    pub fn dependencies(&self) -> Result<Vec<Package>, failure::Error> {
+        self.manifest
+            .dependencies
+            .iter()
+            .map(|(name, dependency)| {
+                let package =
+                    Package::from_file(self.root.join(&dependency.path).join(MANIFEST_FILENAME))
+                        .with_context(|_| format!("could not load dependency `{}`", name))?;
+                if package.name() != name {
+                    return Err(format_err!(
+                        "dependency `{}` refers to package `{}`",
+                        name,
+                        package.name()
+                    ));
+                }
+                Ok(package)
+            })
+            .collect()
+    }
+
+    /// Returns all direct and indirect dependencies of the package. A dependency always precedes
+    /// the packages that depend on it.
+    pub fn resolve_dependencies(&self) -> Result<Vec<Package>, failure::Error> {
+        let mut resolved = Vec::new();
+        let mut stack = vec![self.name().to_owned()];
+        self.resolve_dependencies_rec(&mut stack, &mut resolved)?;
+        Ok(resolved)
+    }
+
+    fn resolve_dependencies_rec(
+        &self,
+        stack: &mut Vec<String>,
+        resolved: &mut Vec<Package>,
+    ) -> Result<(), failure::Error> {
+        for dependency in self.dependencies()? {
+            if stack.iter().any(|name| name == dependency.name()) {
+                return Err(format_err!(
+                    "cyclic dependency between packages: {} -> {}",
+                    stack.join(" -> "),
+                    dependency.name()
+                ));
+            }
+            if resolved.iter().any(|p| p.name() == dependency.name()) {
+                continue;
+            }
+
+            stack.push(dependency.name().to_owned());
+            dependency.resolve_dependencies_rec(stack, resolved)?;
+            stack.pop();
+            resolved.push(dependency);
+        }
+        Ok(())
+    }
+
+    /// Returns the paths of all source files of the package, in sorted order.
+    pub fn source_files(&self) -> Result<Vec<PathBuf>, failure::Error> {
+        let source_dir = self.source_dir();
+        let mut files = Vec::new();
+        collect_source_files(&source_dir, &mut files)
+            .with_context(|_| format!("could not read {}", source_dir.display()))?;
+        files.sort();
+        Ok(files)
+    }
+}
+
+/// Recursively collects all Mun source files in `dir`.
+fn collect_source_files(dir: &Path, files: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_source_files(&path, files)?;
+        } else if path.extension().and_then(|ext| ext.to_str()) == Some(SOURCE_FILE_EXTENSION) {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Returns the path of the first manifest found in `dir` or one of its ancestors.
+pub fn find_manifest(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .map(|dir| dir.join(MANIFEST_FILENAME))
+        .find(|path| path.is_file())
+}
+
+/// Creates a new package in the directory `path`, which must not exist yet.
+pub fn new_package(path: &Path) -> Result<(), failure::Error> {
+    if path.exists() {
+        return Err(format_err!(
+            "destination `{}` already exists",
+            path.display()
+        ));
+    }
+    std::fs::create_dir_all(path)?;
+    init_package(path)
+}
+
+/// Creates a new package in the existing directory `path`. The package is named after the
+/// directory.
+pub fn init_package(path: &Path) -> Result<(), failure::Error> {
+    let manifest_path = path.join(MANIFEST_FILENAME);
+    if manifest_path.exists() {
+        return Err(format_err!(
+            "`{}` already exists, cannot initialize a package",
+            manifest_path.display()
+        ));
+    }
+
+    let name = package_name(path)?;
+    std::fs::write(
+        &manifest_path,
+        format!("[package]\nname = \"{}\"\nversion = \"0.1.0\"\n", name),
+    )?;
+
+    let source_dir = path.join(default_source_dir());
+    std::fs::create_dir_all(&source_dir)?;
+    let main_path = source_dir.join("main.mun");
+    if !main_path.exists() {
+        std::fs::write(main_path, "pub fn main() -> i32 {\n    0\n}\n")?;
+    }
+
+    Ok(())
+}
+
+/// Derives a package name from the name of its directory.
+fn package_name(path: &Path) -> Result<String, failure::Error> {
+    let path = path.canonicalize()?;
+    let dir_name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| format_err!("cannot derive a package name from `{}`", path.display()))?;
+    Ok(dir_name
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '_' })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_manifest() {
+        let manifest: Manifest = toml::from_str(
+            r#"
+            [package]
+            name = "foo"
+            output-dir = "out"
+            opt-level = "s"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(manifest.package.name, "foo");
+        assert_eq!(manifest.package.source_dir, PathBuf::from("src"));
+        assert_eq!(manifest.package.output_dir, PathBuf::from("out"));
+        assert_eq!(manifest.package.target, None);
+        assert_eq!(manifest.package.opt_level, Some(OptimizationLevel::Size));
+
+        let manifest: Manifest =
+            toml::from_str("[package]\nname = \"foo\"\nopt-level = 3\n").unwrap();
+        assert_eq!(
+            manifest.package.opt_level,
+            Some(OptimizationLevel::Aggressive)
+        );
+        assert!(toml::from_str::<Manifest>("[package]\nname = \"foo\"\nopt-level = 4\n").is_err());
+
+        let manifest: Manifest = toml::from_str(
+            "[package]\nname = \"foo\"\n\n[dependencies]\nbar = { path = \"../bar\" }\n",
+        )
+        .unwrap();
+        assert_eq!(
+            manifest.dependencies.get("bar"),
+            Some(&DependencyManifest {
+                path: PathBuf::from("../bar")
+            })
+        );
+    }
+
+    #[test]
+    fn resolve_dependencies() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let write_manifest = |name: &str, dependencies: &[&str]| {
+            let root = temp_dir.path().join(name);
+            std::fs::create_dir_all(&root).unwrap();
+            let mut manifest = format!("[package]\nname = \"{}\"\n\n[dependencies]\n", name);
+            for dependency in dependencies {
+                manifest.push_str(&format!("{0} = {{ path = \"../{0}\" }}\n", dependency));
+            }
+            std::fs::write(root.join(MANIFEST_FILENAME), manifest).unwrap();
+            Package::from_file(root.join(MANIFEST_FILENAME)).unwrap()
+        };
+
+        write_manifest("c", &[]);
+        write_manifest("b", &["c"]);
+        let a = write_manifest("a", &["b", "c"]);
+        let names = a
+            .resolve_dependencies()
+            .unwrap()
+            .iter()
+            .map(|p| p.name().to_owned())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["c", "b"]);
+
+        write_manifest("c", &["a"]);
+        assert!(a.resolve_dependencies().is_err());
+    }
+
+    #[test]
+    fn discover_and_init() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path().join("my-package");
+        new_package(&root).unwrap();
+        assert!(new_package(&root).is_err());
+        assert!(init_package(&root).is_err());
+
+        let nested = root.join("src").join("nested");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::write(nested.join("foo.mun"), "fn foo() {}").unwrap();
+
+        let package = Package::discover(&nested).unwrap();
+        assert_eq!(package.name(), "my_package");
+        assert_eq!(
+            package.source_files().unwrap(),
+            vec![
+                package.source_dir().join("main.mun"),
+                package.source_dir().join("nested").join("foo.mun"),
+            ]
+        );
+    }
+}
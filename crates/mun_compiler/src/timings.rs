@@ -0,0 +1,162 @@
+//! Measurements of the time spent in the passes of the compiler. They can be reported as a table
+//! or as a trace in the Chrome trace event format, which can be inspected with `chrome://tracing`.
+
+pub use mun_codegen::PassTiming;
+use serde::Serialize;
+use std::io::{self, Write};
+use std::time::Duration;
+
+/// Statistics about the evaluation of salsa queries. An execution is a query that is (re)computed;
+/// a revalidation is a memoized value from a previous revision that is reused after salsa verified
+/// that its inputs did not change. Salsa does not report reads of values that were already
+/// computed or validated in the current revision, so those are not counted.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct QueryStats {
+    /// The number of queries that were executed
+    pub executions: usize,
+    /// The number of memoized values of a previous revision that were revalidated and reused
+    pub revalidations: usize,
+}
+
+/// The time spent in the passes of a compilation, in the order in which they ran.
+#[derive(Clone, Debug, Default)]
+pub struct Timings {
+    passes: Vec<PassTiming>,
+    queries: QueryStats,
+}
+
+impl Timings {
+    /// Returns the timings of the passes, in the order in which they ran.
+    pub fn passes(&self) -> &[PassTiming] {
+        &self.passes
+    }
+
+    /// Returns statistics about the salsa queries that were evaluated during the compilation.
+    pub fn queries(&self) -> QueryStats {
+        self.queries
+    }
+
+    /// Returns the total time spent in all passes.
+    pub fn total(&self) -> Duration {
+        self.passes.iter().map(|pass| pass.duration).sum()
+    }
+
+    pub(crate) fn record(&mut self, timing: PassTiming) {
+        self.passes.push(timing);
+    }
+
+    pub(crate) fn set_queries(&mut self, queries: QueryStats) {
+        self.queries = queries;
+    }
+
+    /// Writes a table that lists the time spent in each pass and its share of the total.
+    pub fn write_table(&self, writer: &mut dyn Write) -> io::Result<()> {
+        let total = self.total();
+        writeln!(writer, "{:<24} {:>12} {:>7}", "pass", "time", "share")?;
+        for pass in self.passes.iter() {
+            writeln!(
+                writer,
+                "{:<24} {:>12} {:>6.1}%",
+                pass.name,
+                format_duration(pass.duration),
+                share(pass.duration, total)
+            )?;
+        }
+        writeln!(
+            writer,
+            "{:<24} {:>12} {:>6.1}%",
+            "total",
+            format_duration(total),
+            100.0
+        )?;
+        writeln!(
+            writer,
+            "salsa queries: {} executed, {} revalidated",
+            self.queries.executions, self.queries.revalidations
+        )
+    }
+
+    /// Writes the timings as a JSON trace in the Chrome trace event format.
+    pub fn write_chrome_trace(&self, writer: &mut dyn Write) -> io::Result<()> {
+        let origin = self.passes.iter().map(|pass| pass.start).min();
+        let mut events = self
+            .passes
+            .iter()
+            .map(|pass| TraceEvent {
+                name: pass.name,
+                cat: "pass",
+                ph: "X",
+                ts: origin.map_or(0, |origin| micros(pass.start - origin)),
+                dur: Some(micros(pass.duration)),
+                pid: 0,
+                tid: 0,
+                args: None,
+            })
+            .collect::<Vec<_>>();
+        events.push(TraceEvent {
+            name: "salsa queries",
+            cat: "queries",
+            ph: "C",
+            ts: 0,
+            dur: None,
+            pid: 0,
+            tid: 0,
+            args: Some(TraceQueryArgs {
+                executions: self.queries.executions,
+                revalidations: self.queries.revalidations,
+            }),
+        });
+
+        let trace = Trace {
+            trace_events: events,
+        };
+        serde_json::to_writer_pretty(&mut *writer, &trace)?;
+        writeln!(writer)
+    }
+}
+
+/// A trace in the Chrome trace event format.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Trace {
+    trace_events: Vec<TraceEvent>,
+}
+
+/// A single event of a Chrome trace. Times are specified in microseconds.
+#[derive(Serialize)]
+struct TraceEvent {
+    name: &'static str,
+    cat: &'static str,
+    /// The phase of the event: `X` for a complete event, `C` for a counter
+    ph: &'static str,
+    ts: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dur: Option<u64>,
+    pid: u32,
+    tid: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    args: Option<TraceQueryArgs>,
+}
+
+#[derive(Serialize)]
+struct TraceQueryArgs {
+    executions: usize,
+    revalidations: usize,
+}
+
+fn micros(duration: Duration) -> u64 {
+    duration.as_micros() as u64
+}
+
+fn format_duration(duration: Duration) -> String {
+    format!("{:.3}ms", duration.as_secs_f64() * 1000.0)
+}
+
+/// Returns the percentage of `total` that `duration` makes up.
+fn share(duration: Duration, total: Duration) -> f64 {
+    if total == Duration::from_secs(0) {
+        0.0
+    } else {
+        duration.as_secs_f64() / total.as_secs_f64() * 100.0
+    }
+}
@@ -1,26 +1,45 @@
-use std::sync::mpsc::channel;
+mod package_build;
+mod protocol;
+mod server;
+
+use std::net::TcpListener;
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError};
 use std::time::Duration;
 
 use failure::Error;
-use mun_compiler::{CompilerOptions, Driver, PathOrInline};
-use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use mun_codegen::Context;
+use mun_compiler::{CompilerOptions, Config, Driver, Package, PathOrInline};
+use notify::{DebouncedEvent, RecommendedWatcher, RecursiveMode, Watcher};
+
+use self::package_build::package_builds;
+pub use self::server::Server;
 
 use std::io::stderr;
 
+/// The delay with which the watcher reports changes to a single file
+const WATCH_DELAY: Duration = Duration::from_millis(10);
+
+/// Changes that are reported within this period of each other are handled by a single rebuild
+const BATCH_DELAY: Duration = Duration::from_millis(50);
+
 pub fn main(options: CompilerOptions) -> Result<(), Error> {
     // Need to canonicalize path to do comparisons
     let input_path = match &options.input {
         PathOrInline::Path(path) => path.canonicalize()?,
-        PathOrInline::Inline { .. } => panic!("cannot run compiler with inline path"),
+        PathOrInline::Inline { .. } => {
+            return Err(failure::err_msg(
+                "the compiler daemon can only watch files on disk",
+            ))
+        }
     };
 
     let (tx, rx) = channel();
 
-    let mut watcher: RecommendedWatcher = Watcher::new(tx, Duration::from_millis(10))?;
+    let mut watcher: RecommendedWatcher = Watcher::new(tx, WATCH_DELAY)?;
     watcher.watch(&input_path, RecursiveMode::NonRecursive)?;
     println!("Watching: {}", input_path.display());
 
-    let context = mun_codegen::Context::create();
+    let context = Context::create();
     let (mut driver, file_id) = Driver::with_file(options.config, options.input)?;
 
     // Compile at least once
@@ -29,18 +48,93 @@ pub fn main(options: CompilerOptions) -> Result<(), Error> {
     }
 
     loop {
-        use notify::DebouncedEvent::*;
-        match rx.recv() {
-            Ok(Write(ref path)) | Ok(Create(ref path)) if path == &input_path => {
-                let contents = std::fs::read_to_string(path)?;
-                driver.set_file_text(file_id, &contents);
-                if !driver.emit_diagnostics(&mut stderr())? {
-                    driver.write_assembly(&context, file_id)?;
-                    println!("Successfully compiled: {}", path.display())
+        let events = recv_batch(&rx)?;
+        let changed = events.iter().any(|event| match event {
+            DebouncedEvent::Write(path) | DebouncedEvent::Create(path) => path == &input_path,
+            _ => false,
+        });
+        if changed {
+            let contents = std::fs::read_to_string(&input_path)?;
+            driver.set_file_text(file_id, &contents);
+            if !driver.emit_diagnostics(&mut stderr())? {
+                let assembly_path = driver.write_assembly(&context, file_id)?;
+                println!("Successfully compiled: {}", input_path.display());
+                if let Some(timings) = driver.take_timings() {
+                    mun_compiler::report_timings(&timings, &assembly_path)?;
                 }
             }
-            Ok(_) => {}
-            Err(e) => eprintln!("Watcher error: {:?}", e),
+        }
+    }
+}
+
+/// Continuously monitors the source files of a package and its dependencies for changes. When
+/// source files are created, modified, renamed or removed, the assemblies that are affected by the
+/// change are recompiled: the package that contains the files and all packages that depend on it.
+pub fn watch_package(package: &Package, config: Config) -> Result<(), Error> {
+    let context = Context::create();
+
+    let mut builds = package_builds(package, config)?;
+
+    let (tx, rx) = channel();
+    let mut watcher: RecommendedWatcher = Watcher::new(tx, WATCH_DELAY)?;
+    for build in builds.iter() {
+        let source_dir = build.package().source_dir().canonicalize()?;
+        watcher.watch(&source_dir, RecursiveMode::Recursive)?;
+        println!("Watching: {}", source_dir.display());
+    }
+
+    // Compile at least once
+    for build in builds.iter_mut() {
+        build.build_and_report(&context);
+    }
+
+    loop {
+        let events = recv_batch(&rx)?;
+        let mut affected = vec![false; builds.len()];
+        for event in events.iter() {
+            for (build, affected) in builds.iter_mut().zip(affected.iter_mut()) {
+                match build.apply(event) {
+                    Ok(true) => *affected = true,
+                    Ok(false) => (),
+                    Err(e) => eprintln!("Failed to process change: {}", e),
+                }
+            }
+        }
+
+        for (build, affected) in builds.iter_mut().zip(affected) {
+            if affected {
+                build.build_and_report(&context);
+            }
+        }
+    }
+}
+
+/// Runs a build server for the package. Requests are read from stdin, unless an `address` is
+/// specified on which the server listens for TCP connections.
+pub fn serve(package: &Package, config: Config, address: Option<&str>) -> Result<(), Error> {
+    let context = Context::create();
+    let mut server = Server::new(&context, package, config)?;
+    match address {
+        Some(address) => {
+            let listener = TcpListener::bind(address)?;
+            eprintln!("Listening on: {}", listener.local_addr()?);
+            server.serve_tcp(listener)
+        }
+        None => server.serve_stdio(),
+    }
+}
+
+/// Blocks until the watcher reports a change and then collects all changes that follow within
+/// `BATCH_DELAY` of each other, so a burst of saves results in a single rebuild.
+fn recv_batch(rx: &Receiver<DebouncedEvent>) -> Result<Vec<DebouncedEvent>, Error> {
+    let mut events = vec![rx.recv()?];
+    loop {
+        match rx.recv_timeout(BATCH_DELAY) {
+            Ok(event) => events.push(event),
+            Err(RecvTimeoutError::Timeout) => return Ok(events),
+            Err(RecvTimeoutError::Disconnected) => {
+                return Err(failure::err_msg("the file watcher stopped unexpectedly"))
+            }
         }
     }
 }
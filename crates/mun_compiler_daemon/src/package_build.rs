@@ -0,0 +1,166 @@
+//! A `PackageBuild` keeps the state that is required to incrementally rebuild a package.
+
+use std::path::{Path, PathBuf};
+
+use annotate_snippets::snippet::Snippet;
+use failure::Error;
+use mun_codegen::{CodegenStats, Context};
+use mun_compiler::{Config, Driver, Package};
+use notify::DebouncedEvent;
+
+use std::io::stderr;
+
+/// The file extension of Mun source files
+const SOURCE_FILE_EXTENSION: &str = "mun";
+
+/// The driver of a package that is built by the daemon.
+pub(crate) struct PackageBuild<'ink> {
+    package: Package,
+    config: Config,
+    driver: Driver<'ink>,
+}
+
+impl<'ink> PackageBuild<'ink> {
+    pub fn new(package: Package, config: Config) -> Result<Self, Error> {
+        let driver = Driver::with_package(config.clone(), &package)?;
+        Ok(PackageBuild {
+            package,
+            config,
+            driver,
+        })
+    }
+
+    /// Rereads all source files of the package from disk.
+    fn reload(&mut self) -> Result<(), Error> {
+        self.driver = Driver::with_package(self.config.clone(), &self.package)?;
+        Ok(())
+    }
+
+    /// Returns the package that is built.
+    pub fn package(&self) -> &Package {
+        &self.package
+    }
+
+    /// Returns the diagnostics of the package's source files.
+    pub fn diagnostics(&self) -> Vec<Snippet> {
+        self.driver.diagnostics()
+    }
+
+    /// Compiles the package and returns the path of its assembly.
+    pub fn build(&mut self, context: &'ink Context) -> Result<PathBuf, Error> {
+        self.driver.write_package_assembly(context)
+    }
+
+    /// Returns statistics about the code generation of the most recent build.
+    pub fn codegen_stats(&self) -> CodegenStats {
+        self.driver.codegen_stats()
+    }
+
+    /// Compiles the package if it does not contain errors. Errors are reported to stderr.
+    pub fn build_and_report(&mut self, context: &'ink Context) {
+        match self.driver.emit_diagnostics(&mut stderr()) {
+            Ok(false) => match self.build(context) {
+                Ok(path) => {
+                    let stats = self.codegen_stats();
+                    println!(
+                        "Successfully compiled: {} ({} functions generated, {} reused)",
+                        path.display(),
+                        stats.generated_functions,
+                        stats.reused_functions
+                    );
+                    if let Some(timings) = self.driver.take_timings() {
+                        if let Err(e) = mun_compiler::report_timings(&timings, &path) {
+                            eprintln!("Failed to report timings: {}", e);
+                        }
+                    }
+                }
+                Err(e) => eprintln!("Failed to compile `{}`: {}", self.package.name(), e),
+            },
+            Ok(true) => (),
+            Err(e) => eprintln!("{}", e),
+        }
+    }
+
+    /// Sets the contents of the source file at `path`, which does not have to exist on disk.
+    /// Returns true if the file is part of the package or one of its dependencies.
+    pub fn set_file_text(&mut self, path: &Path, text: &str) -> bool {
+        self.driver.set_file_text_at_path(path, text).is_some()
+    }
+
+    /// Applies a file system change to the source files of the driver. Returns true if the change
+    /// affects the package.
+    pub fn apply(&mut self, event: &DebouncedEvent) -> Result<bool, Error> {
+        let affected = match event {
+            DebouncedEvent::Create(path) | DebouncedEvent::Write(path) => self.update(path)?,
+            DebouncedEvent::Remove(path) => self.driver.remove_file_at_path(path).is_some(),
+            DebouncedEvent::Rename(from, to) => {
+                let removed = self.driver.remove_file_at_path(from).is_some();
+                self.update(to)? || removed
+            }
+            DebouncedEvent::Rescan => {
+                self.reload()?;
+                true
+            }
+            DebouncedEvent::Error(e, path) => {
+                eprintln!("Watcher error: {:?} ({:?})", e, path);
+                false
+            }
+            DebouncedEvent::NoticeWrite(_)
+            | DebouncedEvent::NoticeRemove(_)
+            | DebouncedEvent::Chmod(_) => false,
+        };
+        Ok(affected)
+    }
+
+    /// Rereads the file at `path`, or all source files in the directory at `path`.
+    fn update(&mut self, path: &Path) -> Result<bool, Error> {
+        let mut affected = false;
+        for path in source_files(path)? {
+            match std::fs::read_to_string(&path) {
+                Ok(text) => {
+                    affected |= self.driver.set_file_text_at_path(&path, text).is_some();
+                }
+                // The file was removed before we got the chance to read it
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                    affected |= self.driver.remove_file_at_path(&path).is_some();
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Ok(affected)
+    }
+}
+
+/// Constructs the builds of a package and all of its dependencies. Dependencies precede the
+/// packages that depend on them, so building them in order results in up-to-date assemblies.
+pub(crate) fn package_builds<'ink>(
+    package: &Package,
+    config: Config,
+) -> Result<Vec<PackageBuild<'ink>>, Error> {
+    let mut builds = package
+        .resolve_dependencies()?
+        .into_iter()
+        .map(|dependency| {
+            let config = Config {
+                out_dir: Some(dependency.output_dir()),
+                ..config.clone()
+            };
+            PackageBuild::new(dependency, config)
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+    builds.push(PackageBuild::new(package.clone(), config)?);
+    Ok(builds)
+}
+
+/// Returns `path` if it is a Mun source file, or all Mun source files in the directory at `path`.
+fn source_files(path: &Path) -> Result<Vec<PathBuf>, Error> {
+    let mut files = Vec::new();
+    if path.is_dir() {
+        for entry in std::fs::read_dir(path)? {
+            files.extend(source_files(&entry?.path())?);
+        }
+    } else if path.extension().and_then(|ext| ext.to_str()) == Some(SOURCE_FILE_EXTENSION) {
+        files.push(path.to_path_buf());
+    }
+    Ok(files)
+}
@@ -0,0 +1,167 @@
+//! The messages of the daemon's protocol. The protocol is based on
+//! [JSON-RPC 2.0](https://www.jsonrpc.org/specification); every message is a single line of JSON.
+//!
+//! A client sends requests to the daemon:
+//!
+//! * `build` compiles all packages and returns the paths of the produced assemblies,
+//! * `check` only reports diagnostics,
+//! * `setFileContents` overrides the contents of a source file with `{ "path", "contents" }`,
+//! * `shutdown` stops the daemon.
+//!
+//! Requests without an `id` are notifications, to which the daemon does not respond, not even if
+//! they fail. Requests with a `null` id are responded to.
+//!
+//! While handling `build` and `check` requests, the daemon streams `diagnostic` and `assembly`
+//! notifications to the client before it sends the response.
+
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+use std::path::PathBuf;
+
+/// The version of JSON-RPC that is used by the protocol.
+pub const JSONRPC_VERSION: &str = "2.0";
+
+/// A request that is sent by the client.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Request {
+    pub jsonrpc: String,
+    /// The identifier that is echoed in the response, or `None` if the request is a notification.
+    /// An `id` of `null` is `Some(Value::Null)`.
+    #[serde(default, deserialize_with = "deserialize_id")]
+    pub id: Option<Value>,
+    pub method: String,
+    #[serde(default)]
+    pub params: Value,
+}
+
+/// Deserializes an `id` that is present, including an `id` of `null`. A missing `id` is `None`
+/// through `#[serde(default)]`.
+fn deserialize_id<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Value>, D::Error> {
+    Value::deserialize(deserializer).map(Some)
+}
+
+/// The response to a request.
+#[derive(Debug, Clone, Serialize)]
+pub struct Response {
+    pub jsonrpc: &'static str,
+    pub id: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<ResponseError>,
+}
+
+impl Response {
+    /// Constructs a response that indicates success.
+    pub fn ok(id: Value, result: Value) -> Response {
+        Response {
+            jsonrpc: JSONRPC_VERSION,
+            id,
+            result: Some(result),
+            error: None,
+        }
+    }
+
+    /// Constructs a response that indicates failure.
+    pub fn err(id: Value, error: ResponseError) -> Response {
+        Response {
+            jsonrpc: JSONRPC_VERSION,
+            id,
+            result: None,
+            error: Some(error),
+        }
+    }
+}
+
+/// The error of a failed request.
+#[derive(Debug, Clone, Serialize)]
+pub struct ResponseError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl ResponseError {
+    pub const PARSE_ERROR: i64 = -32700;
+    pub const INVALID_REQUEST: i64 = -32600;
+    pub const METHOD_NOT_FOUND: i64 = -32601;
+    pub const INVALID_PARAMS: i64 = -32602;
+    pub const INTERNAL_ERROR: i64 = -32603;
+
+    pub fn new<S: Into<String>>(code: i64, message: S) -> ResponseError {
+        ResponseError {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+/// A message from the daemon that does not expect a response.
+#[derive(Debug, Clone, Serialize)]
+pub struct Notification<T> {
+    pub jsonrpc: &'static str,
+    pub method: &'static str,
+    pub params: T,
+}
+
+impl<T> Notification<T> {
+    pub fn new(method: &'static str, params: T) -> Notification<T> {
+        Notification {
+            jsonrpc: JSONRPC_VERSION,
+            method,
+            params,
+        }
+    }
+}
+
+/// The parameters of a `setFileContents` request.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SetFileContentsParams {
+    pub path: PathBuf,
+    pub contents: String,
+}
+
+/// The parameters of a `diagnostic` notification.
+#[derive(Debug, Clone, Serialize)]
+pub struct DiagnosticParams {
+    /// The name of the package that contains the file
+    pub package: String,
+    /// The path of the file, relative to the source directory of the package
+    pub file: Option<String>,
+    /// Either `error`, `warning`, `info`, `note` or `help`
+    pub severity: &'static str,
+    /// The diagnostic code, e.g. `E0005`
+    pub code: Option<String>,
+    pub message: String,
+    /// The diagnostic as it would be displayed on the command line
+    pub rendered: String,
+}
+
+/// The parameters of an `assembly` notification.
+#[derive(Debug, Clone, Serialize)]
+pub struct AssemblyParams {
+    pub package: String,
+    pub path: PathBuf,
+    /// The number of functions for which code was generated
+    pub generated_functions: usize,
+    /// The number of functions whose code was reused from the previous build
+    pub reused_functions: usize,
+}
+
+/// The result of a `build` request.
+#[derive(Debug, Clone, Serialize)]
+pub struct BuildResult {
+    /// False if errors were reported
+    pub success: bool,
+    /// The paths of all assemblies that were produced
+    pub assemblies: Vec<PathBuf>,
+    /// The names of the packages that were not built, because they or one of their dependencies
+    /// failed to compile
+    pub not_built: Vec<String>,
+}
+
+/// The result of a `check` request.
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckResult {
+    /// False if errors were reported
+    pub success: bool,
+}
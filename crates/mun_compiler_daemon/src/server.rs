@@ -0,0 +1,587 @@
+//! A long-lived compiler process that builds packages on request. Clients, like editors and game
+//! tools, communicate with the server using the protocol described in the `protocol` module,
+//! either over stdio or over a TCP connection on the local machine.
+
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+
+use annotate_snippets::{
+    display_list::DisplayList,
+    formatter::DisplayListFormatter,
+    snippet::{AnnotationType, Snippet},
+};
+use failure::Error;
+use mun_codegen::Context;
+use mun_compiler::{Config, Package};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::package_build::{package_builds, PackageBuild};
+use crate::protocol::{
+    AssemblyParams, BuildResult, CheckResult, DiagnosticParams, Notification, Request, Response,
+    ResponseError, SetFileContentsParams, JSONRPC_VERSION,
+};
+
+/// A server that retains the state of the compiler between requests, so that every build only
+/// recompiles what changed since the previous build.
+pub struct Server<'ink> {
+    context: &'ink Context,
+    builds: Vec<PackageBuild<'ink>>,
+}
+
+impl<'ink> Server<'ink> {
+    /// Constructs a server that builds the specified package and its dependencies.
+    pub fn new(context: &'ink Context, package: &Package, config: Config) -> Result<Self, Error> {
+        Ok(Server {
+            context,
+            builds: package_builds(package, config)?,
+        })
+    }
+
+    /// Serves requests that are read from stdin and writes the responses to stdout, until stdin
+    /// is closed or a shutdown is requested.
+    pub fn serve_stdio(&mut self) -> Result<(), Error> {
+        let stdin = io::stdin();
+        let stdout = io::stdout();
+        self.serve(stdin.lock(), stdout.lock())?;
+        Ok(())
+    }
+
+    /// Serves the clients that connect to `listener`, one at a time, until a client requests a
+    /// shutdown.
+    pub fn serve_tcp(&mut self, listener: TcpListener) -> Result<(), Error> {
+        for stream in listener.incoming() {
+            let stream = stream?;
+            let reader = BufReader::new(stream.try_clone()?);
+            if self.serve(reader, stream)? {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Serves requests that are read from `reader`, one per line, and writes the notifications and
+    /// responses to `writer`. Requests without an id are not responded to. Returns true if the
+    /// client requested a shutdown, or false if the end of `reader` was reached.
+    pub fn serve<R: BufRead, W: Write>(&mut self, reader: R, mut writer: W) -> Result<bool, Error> {
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let request = match serde_json::from_str::<Request>(&line) {
+                Ok(request) => request,
+                Err(e) => {
+                    let error = ResponseError::new(ResponseError::PARSE_ERROR, e.to_string());
+                    write_message(&mut writer, &Response::err(Value::Null, error))?;
+                    continue;
+                }
+            };
+
+            let result = if request.jsonrpc != JSONRPC_VERSION {
+                Err(ResponseError::new(
+                    ResponseError::INVALID_REQUEST,
+                    format!("unsupported JSON-RPC version `{}`", request.jsonrpc),
+                ))
+            } else {
+                self.handle(&request, &mut writer)?
+            };
+
+            let shutdown = request.method == "shutdown" && result.is_ok();
+            if let Some(id) = request.id {
+                let response = match result {
+                    Ok(result) => Response::ok(id, result),
+                    Err(error) => Response::err(id, error),
+                };
+                write_message(&mut writer, &response)?;
+            }
+
+            if shutdown {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Handles a single request. Notifications are written to `writer`.
+    fn handle<W: Write>(
+        &mut self,
+        request: &Request,
+        writer: &mut W,
+    ) -> Result<Result<Value, ResponseError>, Error> {
+        let result = match request.method.as_str() {
+            "build" => Ok(to_value(self.build(writer)?)),
+            "check" => Ok(to_value(self.check(writer)?)),
+            "setFileContents" => {
+                match serde_json::from_value::<SetFileContentsParams>(request.params.clone()) {
+                    Ok(params) => self
+                        .set_file_contents(&params.path, &params.contents)
+                        .map(|_| Value::Null),
+                    Err(e) => Err(ResponseError::new(
+                        ResponseError::INVALID_PARAMS,
+                        e.to_string(),
+                    )),
+                }
+            }
+            "shutdown" => Ok(Value::Null),
+            method => Err(ResponseError::new(
+                ResponseError::METHOD_NOT_FOUND,
+                format!("unknown method `{}`", method),
+            )),
+        };
+        Ok(result)
+    }
+
+    /// Compiles all packages that do not contain errors. Dependencies are compiled before the
+    /// packages that depend on them; packages that depend on a package that failed to compile are
+    /// not built.
+    fn build<W: Write>(&mut self, writer: &mut W) -> Result<BuildResult, Error> {
+        let mut result = BuildResult {
+            success: true,
+            assemblies: Vec::new(),
+            not_built: Vec::new(),
+        };
+        for build in self.builds.iter_mut() {
+            let package = build.package().name().to_owned();
+            let dependency_failed = build
+                .package()
+                .manifest()
+                .dependencies
+                .keys()
+                .any(|dependency| result.not_built.contains(dependency));
+            if !report_diagnostics(build, writer)? || dependency_failed {
+                result.success = false;
+                result.not_built.push(package);
+                continue;
+            }
+
+            match build.build(self.context) {
+                Ok(path) => {
+                    let stats = build.codegen_stats();
+                    write_message(
+                        writer,
+                        &Notification::new(
+                            "assembly",
+                            AssemblyParams {
+                                package,
+                                path: path.clone(),
+                                generated_functions: stats.generated_functions,
+                                reused_functions: stats.reused_functions,
+                            },
+                        ),
+                    )?;
+                    result.assemblies.push(path);
+                }
+                Err(e) => {
+                    let message = format!("failed to compile `{}`: {}", package, e);
+                    write_message(
+                        writer,
+                        &Notification::new(
+                            "diagnostic",
+                            DiagnosticParams {
+                                package: package.clone(),
+                                file: None,
+                                severity: "error",
+                                code: None,
+                                message: message.clone(),
+                                rendered: message,
+                            },
+                        ),
+                    )?;
+                    result.success = false;
+                    result.not_built.push(package);
+                }
+            }
+        }
+        Ok(result)
+    }
+
+    /// Reports the diagnostics of all packages.
+    fn check<W: Write>(&mut self, writer: &mut W) -> Result<CheckResult, Error> {
+        let mut success = true;
+        for build in self.builds.iter() {
+            success &= report_diagnostics(build, writer)?;
+        }
+        Ok(CheckResult { success })
+    }
+
+    /// Overrides the contents of the source file at `path` in all packages that contain it.
+    fn set_file_contents(&mut self, path: &Path, contents: &str) -> Result<(), ResponseError> {
+        let path = canonicalize_file_path(path);
+        let mut found = false;
+        for build in self.builds.iter_mut() {
+            found |= build.set_file_text(&path, contents);
+        }
+
+        if found {
+            Ok(())
+        } else {
+            Err(ResponseError::new(
+                ResponseError::INVALID_PARAMS,
+                format!("`{}` is not part of any package", path.display()),
+            ))
+        }
+    }
+}
+
+/// Writes the diagnostics of a package as notifications. Returns false if errors were reported.
+fn report_diagnostics<W: Write>(build: &PackageBuild, writer: &mut W) -> Result<bool, Error> {
+    let mut success = true;
+    let formatter = DisplayListFormatter::new(false, false);
+    for snippet in build.diagnostics() {
+        let params = diagnostic_params(build.package().name(), snippet, &formatter);
+        success &= params.severity != "error";
+        write_message(writer, &Notification::new("diagnostic", params))?;
+    }
+    Ok(success)
+}
+
+/// Converts a diagnostic snippet into the parameters of a `diagnostic` notification.
+fn diagnostic_params(
+    package: &str,
+    snippet: Snippet,
+    formatter: &DisplayListFormatter,
+) -> DiagnosticParams {
+    let rendered = formatter.format(&DisplayList::from(snippet.clone()));
+    let file = snippet
+        .slices
+        .first()
+        .and_then(|slice| slice.origin.clone());
+    let (severity, code, message) = match snippet.title {
+        Some(title) => (
+            severity(&title.annotation_type),
+            title.id,
+            title.label.unwrap_or_default(),
+        ),
+        None => ("error", None, String::new()),
+    };
+    DiagnosticParams {
+        package: package.to_owned(),
+        file,
+        severity,
+        code,
+        message,
+        rendered,
+    }
+}
+
+fn severity(annotation_type: &AnnotationType) -> &'static str {
+    match annotation_type {
+        AnnotationType::Error => "error",
+        AnnotationType::Warning => "warning",
+        AnnotationType::Info => "info",
+        AnnotationType::Note => "note",
+        AnnotationType::Help => "help",
+    }
+}
+
+/// Canonicalizes the path of a file that does not have to exist yet.
+fn canonicalize_file_path(path: &Path) -> PathBuf {
+    if let Ok(path) = path.canonicalize() {
+        return path;
+    }
+    match (path.parent(), path.file_name()) {
+        (Some(dir), Some(file_name)) => dir
+            .canonicalize()
+            .map(|dir| dir.join(file_name))
+            .unwrap_or_else(|_| path.to_path_buf()),
+        _ => path.to_path_buf(),
+    }
+}
+
+fn to_value<T: Serialize>(value: T) -> Value {
+    serde_json::to_value(value).expect("protocol messages can always be serialized")
+}
+
+/// Writes a message as a single line of JSON.
+fn write_message<W: Write, T: Serialize>(writer: &mut W, message: &T) -> Result<(), Error> {
+    serde_json::to_writer(&mut *writer, message)?;
+    writer.write_all(b"\n")?;
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::io::Cursor;
+    use std::net::TcpStream;
+
+    /// Creates a package with a single source file in a temporary directory.
+    fn package(temp_dir: &tempfile::TempDir) -> Package {
+        let root = temp_dir.path().join("foo");
+        mun_compiler::new_package(&root).unwrap();
+        Package::discover(&root).unwrap()
+    }
+
+    /// Serves the requests and returns all messages that were written by the server.
+    fn serve(server: &mut Server, requests: &[Value]) -> (bool, Vec<Value>) {
+        let input = requests
+            .iter()
+            .map(|request| format!("{}\n", request))
+            .collect::<String>();
+        let mut output = Vec::new();
+        let shutdown = server.serve(Cursor::new(input), &mut output).unwrap();
+        let messages = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        (shutdown, messages)
+    }
+
+    fn request(id: u64, method: &str, params: Value) -> Value {
+        json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params })
+    }
+
+    fn notification(method: &str, params: Value) -> Value {
+        json!({ "jsonrpc": "2.0", "method": method, "params": params })
+    }
+
+    #[test]
+    fn requests() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let package = package(&temp_dir);
+        let main_path = package.source_dir().join("main.mun");
+
+        let context = Context::create();
+        let mut server = Server::new(&context, &package, package.config().unwrap()).unwrap();
+
+        // A file with errors
+        let (shutdown, messages) = serve(
+            &mut server,
+            &[
+                request(
+                    1,
+                    "setFileContents",
+                    json!({ "path": main_path, "contents": "pub fn main() -> i32 { true }" }),
+                ),
+                request(2, "check", Value::Null),
+                request(3, "build", Value::Null),
+            ],
+        );
+        assert!(!shutdown);
+        assert_eq!(messages.len(), 5);
+        assert_eq!(
+            messages[0],
+            json!({ "jsonrpc": "2.0", "id": 1, "result": null })
+        );
+        assert_eq!(messages[1]["method"], "diagnostic");
+        assert_eq!(messages[1]["params"]["package"], "foo");
+        assert_eq!(messages[1]["params"]["file"], "main.mun");
+        assert_eq!(messages[1]["params"]["severity"], "error");
+        assert_eq!(messages[2]["result"], json!({ "success": false }));
+        assert_eq!(messages[3]["method"], "diagnostic");
+        assert_eq!(
+            messages[4]["result"],
+            json!({ "success": false, "assemblies": [], "not_built": ["foo"] })
+        );
+
+        // Fixing the error results in an assembly
+        let (shutdown, messages) = serve(
+            &mut server,
+            &[
+                request(
+                    4,
+                    "setFileContents",
+                    json!({ "path": main_path, "contents": "pub fn main() -> i32 { 5 }" }),
+                ),
+                request(5, "build", Value::Null),
+                request(6, "shutdown", Value::Null),
+                request(7, "check", Value::Null),
+            ],
+        );
+        assert!(shutdown);
+        assert_eq!(messages.len(), 4);
+        assert_eq!(messages[1]["method"], "assembly");
+        assert_eq!(messages[1]["params"]["generated_functions"], 1);
+        assert_eq!(messages[1]["params"]["reused_functions"], 0);
+        let assembly_path = package.assembly_path().canonicalize().unwrap();
+        assert_eq!(
+            Path::new(messages[1]["params"]["path"].as_str().unwrap())
+                .canonicalize()
+                .unwrap(),
+            assembly_path
+        );
+        assert_eq!(messages[2]["id"], 5);
+        assert_eq!(messages[2]["result"]["success"], true);
+        assert_eq!(
+            messages[3],
+            json!({ "jsonrpc": "2.0", "id": 6, "result": null })
+        );
+    }
+
+    #[test]
+    fn invalid_requests() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let package = package(&temp_dir);
+
+        let context = Context::create();
+        let mut server = Server::new(&context, &package, package.config().unwrap()).unwrap();
+
+        let mut output = Vec::new();
+        let input = "not json\n".to_owned()
+            + &request(1, "foo", Value::Null).to_string()
+            + "\n"
+            + &request(2, "setFileContents", json!({ "path": "/foo.mun" })).to_string()
+            + "\n"
+            + &request(
+                3,
+                "setFileContents",
+                json!({ "path": temp_dir.path().join("bar.mun"), "contents": "" }),
+            )
+            .to_string();
+        assert!(!server.serve(Cursor::new(input), &mut output).unwrap());
+
+        let codes = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str::<Value>(line).unwrap()["error"]["code"].clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            codes,
+            vec![
+                json!(ResponseError::PARSE_ERROR),
+                json!(ResponseError::METHOD_NOT_FOUND),
+                json!(ResponseError::INVALID_PARAMS),
+                json!(ResponseError::INVALID_PARAMS),
+            ]
+        );
+    }
+
+    #[test]
+    fn notifications() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let package = package(&temp_dir);
+        let main_path = package.source_dir().join("main.mun");
+
+        let context = Context::create();
+        let mut server = Server::new(&context, &package, package.config().unwrap()).unwrap();
+
+        // Neither successful nor failed notifications are responded to
+        let (shutdown, messages) = serve(
+            &mut server,
+            &[
+                notification(
+                    "setFileContents",
+                    json!({ "path": main_path, "contents": "pub fn main() -> i32 { 5 }" }),
+                ),
+                notification("foo", Value::Null),
+                request(1, "build", Value::Null),
+                notification("shutdown", Value::Null),
+                request(2, "check", Value::Null),
+            ],
+        );
+        assert!(shutdown);
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0]["method"], "assembly");
+        assert_eq!(messages[1]["id"], 1);
+        assert_eq!(messages[1]["result"]["success"], true);
+    }
+
+    #[test]
+    fn null_id() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let package = package(&temp_dir);
+
+        let context = Context::create();
+        let mut server = Server::new(&context, &package, package.config().unwrap()).unwrap();
+
+        // A request with a `null` id is not a notification
+        let (shutdown, messages) = serve(
+            &mut server,
+            &[json!({ "jsonrpc": "2.0", "id": null, "method": "check" })],
+        );
+        assert!(!shutdown);
+        assert_eq!(
+            messages,
+            vec![json!({ "jsonrpc": "2.0", "id": null, "result": { "success": true } })]
+        );
+    }
+
+    #[test]
+    fn dependents_of_failed_packages_are_not_built() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let math_root = temp_dir.path().join("math");
+        mun_compiler::new_package(&math_root).unwrap();
+        std::fs::write(
+            math_root.join("src/main.mun"),
+            "pub fn add(a: i32, b: i32) -> i32 { a + b }",
+        )
+        .unwrap();
+        let app_root = temp_dir.path().join("app");
+        mun_compiler::new_package(&app_root).unwrap();
+        std::fs::write(
+            app_root.join("mun.toml"),
+            "[package]\nname = \"app\"\n\n[dependencies]\nmath = { path = \"../math\" }\n",
+        )
+        .unwrap();
+        std::fs::write(
+            app_root.join("src/main.mun"),
+            "pub fn main() -> i32 { add(2, 3) }",
+        )
+        .unwrap();
+        let package = Package::discover(&app_root).unwrap();
+
+        let context = Context::create();
+        let mut server = Server::new(&context, &package, package.config().unwrap()).unwrap();
+
+        // The dependency is valid, but contains an error once it is edited
+        let (_, messages) = serve(
+            &mut server,
+            &[
+                request(
+                    1,
+                    "setFileContents",
+                    json!({
+                        "path": math_root.join("src/main.mun"),
+                        "contents": "pub fn add(a: i32, b: i32) -> i32 { true }",
+                    }),
+                ),
+                request(2, "build", Value::Null),
+            ],
+        );
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[1]["method"], "diagnostic");
+        assert_eq!(messages[1]["params"]["package"], "math");
+        assert_eq!(
+            messages[2]["result"],
+            json!({ "success": false, "assemblies": [], "not_built": ["math", "app"] })
+        );
+    }
+
+    #[test]
+    fn tcp() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let package = package(&temp_dir);
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let server_package = package.clone();
+        let server = std::thread::spawn(move || {
+            let context = Context::create();
+            let config = server_package.config().unwrap();
+            let mut server = Server::new(&context, &server_package, config).unwrap();
+            server.serve_tcp(listener).unwrap();
+        });
+
+        let mut stream = TcpStream::connect(address).unwrap();
+        writeln!(stream, "{}", request(1, "build", Value::Null)).unwrap();
+        writeln!(stream, "{}", request(2, "shutdown", Value::Null)).unwrap();
+
+        let messages = BufReader::new(stream)
+            .lines()
+            .map(|line| serde_json::from_str::<Value>(&line.unwrap()).unwrap())
+            .collect::<Vec<_>>();
+        server.join().unwrap();
+
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[0]["method"], "assembly");
+        assert_eq!(messages[1]["result"]["success"], true);
+        assert_eq!(messages[2]["id"], 2);
+        assert!(package.assembly_path().is_file());
+    }
+}
@@ -6,7 +6,7 @@ use crate::{
     ids::{AstItemDef, StructId},
     AsName, DefDatabase, Name,
 };
-use mun_syntax::ast::{self, NameOwner, TypeAscriptionOwner};
+use mun_syntax::ast::{self, AttrsOwner, NameOwner, TypeAscriptionOwner};
 
 pub use mun_syntax::ast::StructMemoryKind;
 
@@ -26,6 +26,9 @@ pub use mun_syntax::ast::StructMemoryKind;
 pub struct StructFieldData {
     pub name: Name,
     pub type_ref: TypeRefId,
+    /// The previous name of the field, from which its value is migrated when hot reloading. This
+    /// is specified with the `#[migrate_from(name)]` attribute.
+    pub migrate_from: Option<Name>,
 }
 
 /// An identifier for a struct's or tuple's field
@@ -84,6 +87,7 @@ impl StructData {
                     .map(|fd| StructFieldData {
                         name: fd.name().map(|n| n.as_name()).unwrap_or_else(Name::missing),
                         type_ref: type_ref_builder.alloc_from_node_opt(fd.ascribed_type().as_ref()),
+                        migrate_from: migrate_from(&fd),
                     })
                     .collect();
                 (fields, StructKind::Record)
@@ -95,6 +99,7 @@ impl StructData {
                     .map(|(index, fd)| StructFieldData {
                         name: Name::new_tuple_field(index),
                         type_ref: type_ref_builder.alloc_from_node_opt(fd.type_ref().as_ref()),
+                        migrate_from: None,
                     })
                     .collect();
                 (fields, StructKind::Tuple)
@@ -121,3 +126,17 @@ impl StructData {
         &self.type_ref_map
     }
 }
+
+/// Returns the name specified by the `#[migrate_from(name)]` attribute of a record field, if any.
+fn migrate_from(field: &ast::RecordFieldDef) -> Option<Name> {
+    field
+        .attrs()
+        .filter(|attr| {
+            attr.name_ref()
+                .map_or(false, |name_ref| name_ref.text().as_str() == "migrate_from")
+        })
+        .filter_map(|attr| attr.attr_arg_list())
+        .filter_map(|args| args.args().next())
+        .map(|name_ref| name_ref.as_name())
+        .last()
+}
@@ -1,24 +1,26 @@
+mod debug_dump;
 pub(crate) mod src;
 
 use self::src::HasSource;
 use crate::adt::{StructData, StructFieldId};
 use crate::builtin_type::BuiltinType;
 use crate::code_model::diagnostics::ModuleDefinitionDiagnostic;
-use crate::diagnostics::DiagnosticSink;
+use crate::diagnostics::{DiagnosticSink, DuplicatePackageDefinition};
 use crate::expr::validator::ExprValidator;
 use crate::expr::{Body, BodySourceMap};
 use crate::ids::AstItemDef;
 use crate::ids::LocationCtx;
-use crate::name_resolution::Namespace;
+use crate::name_resolution::{self, Namespace};
 use crate::raw::{DefKind, RawFileItem};
 use crate::resolve::{Resolution, Resolver};
 use crate::ty::{lower::LowerBatchResult, InferenceResult};
 use crate::type_ref::{TypeRefBuilder, TypeRefId, TypeRefMap, TypeRefSourceMap};
 use crate::{
     ids::{FunctionId, StructId},
-    AsName, DefDatabase, FileId, HirDatabase, Name, Ty,
+    AsName, DefDatabase, FileId, FileRange, HirDatabase, Name, Ty,
 };
 use mun_syntax::ast::{ExternOwner, NameOwner, TypeAscriptionOwner, VisibilityOwner};
+use mun_syntax::{AstNode, SyntaxNodePtr};
 use rustc_hash::FxHashMap;
 use std::sync::Arc;
 
@@ -51,6 +53,7 @@ impl Module {
         for diag in db.module_data(self.file_id).diagnostics.iter() {
             diag.add_to(db, self, sink);
         }
+        self.package_diagnostics(db, sink);
         for decl in self.declarations(db) {
             #[allow(clippy::single_match)]
             match decl {
@@ -60,6 +63,43 @@ impl Module {
             }
         }
     }
+
+    /// Reports the public items of this module whose names are already taken by a public item of
+    /// a file of the same package that precedes this module's file.
+    fn package_diagnostics(self, db: &impl HirDatabase, sink: &mut DiagnosticSink) {
+        let source_root_id = db.file_source_root(self.file_id);
+        let mut first_definitions = FxHashMap::default();
+        for file_id in name_resolution::files_by_path(db, source_root_id) {
+            if file_id == self.file_id {
+                break;
+            }
+            for def in db.module_data(file_id).definitions() {
+                if let Some((name, _)) = name_resolution::package_item(db, *def) {
+                    first_definitions.entry(name).or_insert(file_id);
+                }
+            }
+        }
+
+        for def in self.declarations(db) {
+            let name = match name_resolution::package_item(db, def) {
+                Some((name, _)) => name,
+                None => continue,
+            };
+            if let Some(first_file_id) = first_definitions.get(&name) {
+                let definition = match def {
+                    ModuleDef::Function(f) => SyntaxNodePtr::new(f.source(db).value.syntax()),
+                    ModuleDef::Struct(s) => SyntaxNodePtr::new(s.source(db).value.syntax()),
+                    ModuleDef::BuiltinType(_) => unreachable!(),
+                };
+                sink.push(DuplicatePackageDefinition {
+                    file: self.file_id,
+                    name: name.to_string(),
+                    definition,
+                    first_definition_file: db.file_relative_path(*first_file_id),
+                })
+            }
+        }
+    }
 }
 
 #[derive(Debug, Clone, Hash, PartialEq, Eq, Default)]
@@ -339,10 +379,19 @@ impl Function {
         db.fn_data(self).is_extern
     }
 
-    pub(crate) fn body_source_map(self, db: &impl HirDatabase) -> Arc<BodySourceMap> {
+    pub fn body_source_map(self, db: &impl HirDatabase) -> Arc<BodySourceMap> {
         db.body_with_source_map(self.into()).1
     }
 
+    /// Returns the range of the function definition in its source file.
+    pub fn source_range(self, db: &impl DefDatabase) -> FileRange {
+        let src = self.source(db);
+        FileRange {
+            file_id: src.file_id,
+            range: src.value.syntax().text_range(),
+        }
+    }
+
     pub(crate) fn resolver(self, db: &impl HirDatabase) -> Resolver {
         // take the outer scope...
         self.module(db).resolver(db)
@@ -381,6 +430,12 @@ impl StructField {
         self.parent.data(db).fields[self.id].name.clone()
     }
 
+    /// Returns the previous name of the field, from which its value is migrated when hot
+    /// reloading.
+    pub fn migrate_from(self, db: &impl HirDatabase) -> Option<Name> {
+        self.parent.data(db).fields[self.id].migrate_from.clone()
+    }
+
     pub fn id(self) -> StructFieldId {
         self.id
     }
@@ -421,6 +476,15 @@ impl Struct {
         db.type_for_def(self.into(), Namespace::Types)
     }
 
+    /// Returns the range of the struct definition in its source file.
+    pub fn source_range(self, db: &impl DefDatabase) -> FileRange {
+        let src = self.source(db);
+        FileRange {
+            file_id: src.file_id,
+            range: src.value.syntax().text_range(),
+        }
+    }
+
     pub fn lower(self, db: &impl HirDatabase) -> Arc<LowerBatchResult> {
         db.lower_struct(self)
     }
@@ -0,0 +1,168 @@
+//! A stable textual representation of the HIR of a module, which is useful for debugging the
+//! compiler and for attaching to bug reports.
+
+use super::{Function, Module, ModuleDef, Struct};
+use crate::adt::StructKind;
+use crate::{HirDatabase, HirDisplay, StructMemoryKind, Visibility};
+use std::fmt::Write;
+
+/// The maximum length of the source text that is displayed next to an inferred type.
+const MAX_SOURCE_TEXT_LEN: usize = 15;
+
+impl Module {
+    /// Returns a textual representation of the definitions of the module. The body of a function
+    /// is represented by the source ranges of its expressions and patterns, together with their
+    /// inferred types.
+    pub fn debug_dump(self, db: &impl HirDatabase) -> String {
+        self.declarations(db)
+            .into_iter()
+            .filter_map(|def| match def {
+                ModuleDef::Function(f) => Some(dump_function(db, f)),
+                ModuleDef::Struct(s) => Some(dump_struct(db, s)),
+                ModuleDef::BuiltinType(_) => None,
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+fn dump_struct(db: &impl HirDatabase, s: Struct) -> String {
+    let data = s.data(db);
+    let memory_kind = match data.memory_kind {
+        StructMemoryKind::GC => "gc",
+        StructMemoryKind::Value => "value",
+    };
+
+    let mut buf = format!("struct({}) {}", memory_kind, s.name(db));
+    match data.kind {
+        StructKind::Record => {
+            buf.push_str(" {\n");
+            for field in s.fields(db) {
+                writeln!(buf, "    {}: {},", field.name(db), field.ty(db).display(db)).unwrap();
+            }
+            buf.push_str("}\n");
+        }
+        StructKind::Tuple => {
+            let fields = s
+                .fields(db)
+                .into_iter()
+                .map(|field| field.ty(db).display(db).to_string())
+                .collect::<Vec<_>>();
+            writeln!(buf, "({});", fields.join(", ")).unwrap();
+        }
+        StructKind::Unit => buf.push_str(";\n"),
+    }
+    buf
+}
+
+fn dump_function(db: &impl HirDatabase, f: Function) -> String {
+    let mut buf = String::new();
+    if f.visibility(db) == Visibility::Public {
+        buf.push_str("pub ");
+    }
+    if f.is_extern(db) {
+        buf.push_str("extern ");
+    }
+
+    let sig = f.ty(db).callable_sig(db).unwrap();
+    let params = sig
+        .params()
+        .iter()
+        .map(|ty| ty.display(db).to_string())
+        .collect::<Vec<_>>();
+    write!(
+        buf,
+        "fn {}({}) -> {}",
+        f.name(db),
+        params.join(", "),
+        sig.ret().display(db)
+    )
+    .unwrap();
+
+    if f.is_extern(db) {
+        buf.push('\n');
+        return buf;
+    }
+
+    let infer = f.infer(db);
+    let source_map = f.body_source_map(db);
+    let mut types = Vec::new();
+    for (pat, ty) in infer.type_of_pat.iter() {
+        if let Some(src) = source_map.pat_syntax(pat) {
+            types.push((src.map(|ast| ast.syntax_node_ptr()), ty));
+        }
+    }
+    for (expr, ty) in infer.type_of_expr.iter() {
+        if let Some(src) = source_map.expr_syntax(expr) {
+            let src =
+                src.map(|ast| ast.either(|it| it.syntax_node_ptr(), |it| it.syntax_node_ptr()));
+            types.push((src, ty));
+        }
+    }
+
+    // Sort by source range, so the output does not depend on the order in which the body was
+    // lowered
+    types.sort_by_key(|(src, _)| (src.value.range().start(), src.value.range().end()));
+
+    buf.push_str(" {\n");
+    for (src, ty) in types {
+        let node = src.value.to_node(&src.file_syntax(db));
+        let text = node.text().to_string().replace("\n", " ");
+        writeln!(
+            buf,
+            "    {} '{}': {}",
+            src.value.range(),
+            ellipsize(text, MAX_SOURCE_TEXT_LEN),
+            ty.display(db)
+        )
+        .unwrap();
+    }
+    buf.push_str("}\n");
+    buf
+}
+
+/// Shortens `text` to at most `max_len` bytes by replacing its middle with an ellipsis.
+fn ellipsize(mut text: String, max_len: usize) -> String {
+    if text.len() <= max_len {
+        return text;
+    }
+    let ellipsis = "...";
+    let e_len = ellipsis.len();
+    let mut prefix_len = (max_len - e_len) / 2;
+    while !text.is_char_boundary(prefix_len) {
+        prefix_len += 1;
+    }
+    let mut suffix_len = max_len - e_len - prefix_len;
+    while !text.is_char_boundary(text.len() - suffix_len) {
+        suffix_len += 1;
+    }
+    text.replace_range(prefix_len..text.len() - suffix_len, ellipsis);
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mock::MockDatabase;
+    use crate::Module;
+
+    #[test]
+    fn debug_dump() {
+        let (db, file_id) = MockDatabase::with_single_file(
+            "struct(value) Foo { a: i32 }\n\npub fn main(foo: Foo) -> i32 { foo.a }",
+        );
+        assert_eq!(
+            Module::from(file_id).debug_dump(&db),
+            "struct(value) Foo {
+    a: i32,
+}
+
+pub fn main(Foo) -> i32 {
+    [42; 45) 'foo': Foo
+    [59; 68) '{ foo.a }': i32
+    [61; 64) 'foo': Foo
+    [61; 66) 'foo.a': i32
+}
+"
+        );
+    }
+}
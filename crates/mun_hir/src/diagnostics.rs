@@ -1,9 +1,16 @@
 use crate::adt::StructKind;
 use crate::in_file::InFile;
-use crate::{FileId, HirDatabase, IntTy, Name, Ty};
-use mun_syntax::{ast, AstPtr, SmolStr, SyntaxNode, SyntaxNodePtr, TextRange};
+use crate::{FileId, FileRange, HirDatabase, IntTy, Name, RelativePathBuf, Ty};
+use mun_syntax::{
+    ast, AstNode, AstPtr, Direction, SmolStr, SyntaxKind, SyntaxNode, SyntaxNodePtr, TextRange,
+    TextUnit,
+};
 use std::{any::Any, fmt};
 
+pub mod codes;
+
+pub use codes::DiagnosticCode;
+
 /// Diagnostic defines hir API for errors and warnings.
 ///
 /// It is used as a `dyn` object, which you can downcast to concrete diagnostics. DiagnosticSink
@@ -15,13 +22,76 @@ use std::{any::Any, fmt};
 /// diagnostics are transformed into an instance of `Diagnostic` on demand.
 pub trait Diagnostic: Any + Send + Sync + fmt::Debug + 'static {
     fn message(&self) -> String;
+    /// Returns the stable code that identifies this kind of diagnostic.
+    fn code(&self) -> DiagnosticCode;
     fn source(&self) -> InFile<SyntaxNodePtr>;
     fn highlight_range(&self) -> TextRange {
         self.source().value.range()
     }
+    /// Returns structured suggestions to fix the diagnostic. `file_syntax` is the root node of the
+    /// file that contains the diagnostic.
+    fn suggestions(&self, _file_syntax: &SyntaxNode) -> Vec<Suggestion> {
+        Vec::new()
+    }
     fn as_any(&self) -> &(dyn Any + Send + 'static);
 }
 
+/// Indicates how confident we are that a `Suggestion` is what the user intended.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Applicability {
+    /// The suggestion results in valid code and can be applied automatically.
+    MachineApplicable,
+    /// The suggestion contains placeholders (e.g. `_`) that have to be filled in by the user.
+    HasPlaceholders,
+}
+
+/// Replaces the text in `range` with `insert`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TextEdit {
+    pub range: FileRange,
+    pub insert: String,
+}
+
+impl TextEdit {
+    /// Constructs an edit that inserts `text` at `offset`.
+    pub fn insert(file_id: FileId, offset: TextUnit, text: String) -> TextEdit {
+        TextEdit {
+            range: FileRange {
+                file_id,
+                range: TextRange::offset_len(offset, 0.into()),
+            },
+            insert: text,
+        }
+    }
+
+    /// Constructs an edit that replaces the text in `range` with `text`.
+    pub fn replace(file_id: FileId, range: TextRange, text: String) -> TextEdit {
+        TextEdit {
+            range: FileRange { file_id, range },
+            insert: text,
+        }
+    }
+}
+
+/// A structured suggestion to fix a diagnostic.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Suggestion {
+    /// A short description of the fix, e.g. "add missing fields"
+    pub message: String,
+    /// The edits to apply. The ranges of the edits never overlap.
+    pub edits: Vec<TextEdit>,
+    pub applicability: Applicability,
+}
+
+/// Returns the source representation of a trivial value for `ty` and the applicability of a
+/// suggestion that uses it.
+fn trivial_value_or_placeholder(ty: &Ty) -> (&'static str, Applicability) {
+    match ty.trivial_value() {
+        Some(value) => (value, Applicability::MachineApplicable),
+        None => ("_", Applicability::HasPlaceholders),
+    }
+}
+
 pub trait AstDiagnostic {
     type AST;
     fn ast(&self, db: &impl HirDatabase) -> Self::AST;
@@ -88,6 +158,10 @@ impl Diagnostic for UnresolvedValue {
         "undefined value".to_string()
     }
 
+    fn code(&self) -> DiagnosticCode {
+        codes::UNRESOLVED_VALUE
+    }
+
     fn source(&self) -> InFile<SyntaxNodePtr> {
         InFile::new(self.file, self.expr)
     }
@@ -108,6 +182,10 @@ impl Diagnostic for UnresolvedType {
         "undefined type".to_string()
     }
 
+    fn code(&self) -> DiagnosticCode {
+        codes::UNRESOLVED_TYPE
+    }
+
     fn source(&self) -> InFile<SyntaxNodePtr> {
         InFile::new(self.file, self.type_ref.syntax_node_ptr())
     }
@@ -129,6 +207,10 @@ impl Diagnostic for ExpectedFunction {
         "expected function type".to_string()
     }
 
+    fn code(&self) -> DiagnosticCode {
+        codes::EXPECTED_FUNCTION
+    }
+
     fn source(&self) -> InFile<SyntaxNodePtr> {
         InFile::new(self.file, self.expr)
     }
@@ -154,6 +236,10 @@ impl Diagnostic for ParameterCountMismatch {
         )
     }
 
+    fn code(&self) -> DiagnosticCode {
+        codes::PARAMETER_COUNT_MISMATCH
+    }
+
     fn source(&self) -> InFile<SyntaxNodePtr> {
         InFile::new(self.file, self.expr)
     }
@@ -176,6 +262,10 @@ impl Diagnostic for MismatchedType {
         "mismatched type".to_string()
     }
 
+    fn code(&self) -> DiagnosticCode {
+        codes::MISMATCHED_TYPE
+    }
+
     fn source(&self) -> InFile<SyntaxNodePtr> {
         InFile::new(self.file, self.expr)
     }
@@ -198,6 +288,10 @@ impl Diagnostic for IncompatibleBranch {
         "mismatched branches".to_string()
     }
 
+    fn code(&self) -> DiagnosticCode {
+        codes::INCOMPATIBLE_BRANCH
+    }
+
     fn source(&self) -> InFile<SyntaxNodePtr> {
         InFile::new(self.file, self.if_expr)
     }
@@ -224,6 +318,10 @@ impl Diagnostic for InvalidLHS {
         "invalid left hand side of expression".to_string()
     }
 
+    fn code(&self) -> DiagnosticCode {
+        codes::INVALID_LHS
+    }
+
     fn source(&self) -> InFile<SyntaxNodePtr> {
         InFile::new(self.file, self.lhs)
     }
@@ -245,10 +343,27 @@ impl Diagnostic for MissingElseBranch {
         "missing else branch".to_string()
     }
 
+    fn code(&self) -> DiagnosticCode {
+        codes::MISSING_ELSE_BRANCH
+    }
+
     fn source(&self) -> InFile<SyntaxNodePtr> {
         InFile::new(self.file, self.if_expr)
     }
 
+    fn suggestions(&self, _file_syntax: &SyntaxNode) -> Vec<Suggestion> {
+        let (value, applicability) = trivial_value_or_placeholder(&self.found);
+        vec![Suggestion {
+            message: "add an `else` branch".to_owned(),
+            edits: vec![TextEdit::insert(
+                self.file,
+                self.if_expr.range().end(),
+                format!(" else {{ {} }}", value),
+            )],
+            applicability,
+        }]
+    }
+
     fn as_any(&self) -> &(dyn Any + Send + 'static) {
         self
     }
@@ -267,6 +382,10 @@ impl Diagnostic for CannotApplyBinaryOp {
         "cannot apply binary operator".to_string()
     }
 
+    fn code(&self) -> DiagnosticCode {
+        codes::CANNOT_APPLY_BINARY_OP
+    }
+
     fn source(&self) -> InFile<SyntaxNodePtr> {
         InFile::new(self.file, self.expr)
     }
@@ -288,6 +407,10 @@ impl Diagnostic for CannotApplyUnaryOp {
         "cannot apply unary operator".to_string()
     }
 
+    fn code(&self) -> DiagnosticCode {
+        codes::CANNOT_APPLY_UNARY_OP
+    }
+
     fn source(&self) -> InFile<SyntaxNodePtr> {
         InFile::new(self.file, self.expr)
     }
@@ -310,6 +433,41 @@ impl Diagnostic for DuplicateDefinition {
         format!("the name `{}` is defined multiple times", self.name)
     }
 
+    fn code(&self) -> DiagnosticCode {
+        codes::DUPLICATE_DEFINITION
+    }
+
+    fn source(&self) -> InFile<SyntaxNodePtr> {
+        InFile::new(self.file, self.definition)
+    }
+
+    fn as_any(&self) -> &(dyn Any + Send + 'static) {
+        self
+    }
+}
+
+/// A public item of a file has the same name as a public item of another file of the same package.
+#[derive(Debug)]
+pub struct DuplicatePackageDefinition {
+    pub file: FileId,
+    pub name: String,
+    pub definition: SyntaxNodePtr,
+    /// The path of the file that contains the first definition, relative to its source root
+    pub first_definition_file: RelativePathBuf,
+}
+
+impl Diagnostic for DuplicatePackageDefinition {
+    fn message(&self) -> String {
+        format!(
+            "the name `{}` is already defined in `{}`",
+            self.name, self.first_definition_file
+        )
+    }
+
+    fn code(&self) -> DiagnosticCode {
+        codes::DUPLICATE_PACKAGE_DEFINITION
+    }
+
     fn source(&self) -> InFile<SyntaxNodePtr> {
         InFile::new(self.file, self.definition)
     }
@@ -323,6 +481,8 @@ impl Diagnostic for DuplicateDefinition {
 pub struct ReturnMissingExpression {
     pub file: FileId,
     pub return_expr: SyntaxNodePtr,
+    /// The return type of the function that contains the `return`
+    pub return_ty: Ty,
 }
 
 impl Diagnostic for ReturnMissingExpression {
@@ -330,10 +490,27 @@ impl Diagnostic for ReturnMissingExpression {
         "`return;` in a function whose return type is not `()`".to_owned()
     }
 
+    fn code(&self) -> DiagnosticCode {
+        codes::RETURN_MISSING_EXPRESSION
+    }
+
     fn source(&self) -> InFile<SyntaxNodePtr> {
         InFile::new(self.file, self.return_expr)
     }
 
+    fn suggestions(&self, _file_syntax: &SyntaxNode) -> Vec<Suggestion> {
+        let (value, applicability) = trivial_value_or_placeholder(&self.return_ty);
+        vec![Suggestion {
+            message: "return a value".to_owned(),
+            edits: vec![TextEdit::insert(
+                self.file,
+                self.return_expr.range().end(),
+                format!(" {}", value),
+            )],
+            applicability,
+        }]
+    }
+
     fn as_any(&self) -> &(dyn Any + Send + 'static) {
         self
     }
@@ -350,6 +527,10 @@ impl Diagnostic for BreakOutsideLoop {
         "`break` outside of a loop".to_owned()
     }
 
+    fn code(&self) -> DiagnosticCode {
+        codes::BREAK_OUTSIDE_LOOP
+    }
+
     fn source(&self) -> InFile<SyntaxNodePtr> {
         InFile::new(self.file, self.break_expr)
     }
@@ -370,6 +551,10 @@ impl Diagnostic for BreakWithValueOutsideLoop {
         "`break` with value can only appear in a `loop`".to_owned()
     }
 
+    fn code(&self) -> DiagnosticCode {
+        codes::BREAK_WITH_VALUE_OUTSIDE_LOOP
+    }
+
     fn source(&self) -> InFile<SyntaxNodePtr> {
         InFile::new(self.file, self.break_expr)
     }
@@ -392,6 +577,10 @@ impl Diagnostic for AccessUnknownField {
         "attempted to access a non-existent field in a struct.".to_string()
     }
 
+    fn code(&self) -> DiagnosticCode {
+        codes::ACCESS_UNKNOWN_FIELD
+    }
+
     fn source(&self) -> InFile<SyntaxNodePtr> {
         InFile::new(self.file, self.expr)
     }
@@ -420,6 +609,10 @@ impl Diagnostic for FieldCountMismatch {
         )
     }
 
+    fn code(&self) -> DiagnosticCode {
+        codes::FIELD_COUNT_MISMATCH
+    }
+
     fn source(&self) -> InFile<SyntaxNodePtr> {
         InFile::new(self.file, self.expr)
     }
@@ -434,6 +627,8 @@ pub struct MissingFields {
     pub file: FileId,
     pub fields: SyntaxNodePtr,
     pub field_names: Vec<Name>,
+    /// The types of the fields in `field_names`
+    pub field_tys: Vec<Ty>,
 }
 
 impl Diagnostic for MissingFields {
@@ -446,10 +641,75 @@ impl Diagnostic for MissingFields {
         message
     }
 
+    fn code(&self) -> DiagnosticCode {
+        codes::MISSING_FIELDS
+    }
+
     fn source(&self) -> InFile<SyntaxNodePtr> {
         InFile::new(self.file, self.fields)
     }
 
+    fn suggestions(&self, file_syntax: &SyntaxNode) -> Vec<Suggestion> {
+        let field_list = match ast::RecordLit::cast(self.fields.to_node(file_syntax))
+            .and_then(|lit| lit.record_field_list())
+        {
+            Some(field_list) => field_list,
+            None => return Vec::new(),
+        };
+
+        let mut applicability = Applicability::MachineApplicable;
+        let missing_fields = self
+            .field_names
+            .iter()
+            .zip(self.field_tys.iter())
+            .map(|(name, ty)| {
+                let (value, field_applicability) = trivial_value_or_placeholder(ty);
+                if field_applicability != Applicability::MachineApplicable {
+                    applicability = field_applicability;
+                }
+                format!("{}: {}", name, value)
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        // Insert the fields after the last field, taking into account a trailing comma, or after
+        // the opening curly brace if there are no fields yet.
+        let (offset, text) = match field_list.fields().last() {
+            Some(last_field) => {
+                let trailing_comma = last_field
+                    .syntax()
+                    .siblings_with_tokens(Direction::Next)
+                    .skip(1)
+                    .find(|it| !it.kind().is_trivia())
+                    .filter(|it| it.kind() == SyntaxKind::COMMA);
+                match trailing_comma {
+                    Some(comma) => (comma.text_range().end(), format!(" {},", missing_fields)),
+                    None => (
+                        last_field.syntax().text_range().end(),
+                        format!(", {}", missing_fields),
+                    ),
+                }
+            }
+            None => {
+                let l_curly = match field_list
+                    .syntax()
+                    .children_with_tokens()
+                    .find(|it| it.kind() == SyntaxKind::L_CURLY)
+                {
+                    Some(l_curly) => l_curly,
+                    None => return Vec::new(),
+                };
+                (l_curly.text_range().end(), format!(" {} ", missing_fields))
+            }
+        };
+
+        vec![Suggestion {
+            message: "add the missing fields".to_owned(),
+            edits: vec![TextEdit::insert(self.file, offset, text)],
+            applicability,
+        }]
+    }
+
     fn as_any(&self) -> &(dyn Any + Send + 'static) {
         self
     }
@@ -471,6 +731,10 @@ impl Diagnostic for MismatchedStructLit {
         )
     }
 
+    fn code(&self) -> DiagnosticCode {
+        codes::MISMATCHED_STRUCT_LIT
+    }
+
     fn source(&self) -> InFile<SyntaxNodePtr> {
         InFile::new(self.file, self.expr)
     }
@@ -492,6 +756,10 @@ impl Diagnostic for NoFields {
         "attempted to access a field on a primitive type.".to_string()
     }
 
+    fn code(&self) -> DiagnosticCode {
+        codes::NO_FIELDS
+    }
+
     fn source(&self) -> InFile<SyntaxNodePtr> {
         InFile::new(self.file, self.receiver_expr)
     }
@@ -511,6 +779,10 @@ impl Diagnostic for NoSuchField {
         "no such field".to_string()
     }
 
+    fn code(&self) -> DiagnosticCode {
+        codes::NO_SUCH_FIELD
+    }
+
     fn source(&self) -> InFile<SyntaxNodePtr> {
         InFile::new(self.file, self.field)
     }
@@ -531,6 +803,10 @@ impl Diagnostic for PossiblyUninitializedVariable {
         "use of possibly-uninitialized variable".to_string()
     }
 
+    fn code(&self) -> DiagnosticCode {
+        codes::POSSIBLY_UNINITIALIZED_VARIABLE
+    }
+
     fn source(&self) -> InFile<SyntaxNodePtr> {
         InFile::new(self.file, self.pat)
     }
@@ -550,6 +826,10 @@ impl Diagnostic for ExternCannotHaveBody {
         "extern functions cannot have bodies".to_string()
     }
 
+    fn code(&self) -> DiagnosticCode {
+        codes::EXTERN_CANNOT_HAVE_BODY
+    }
+
     fn source(&self) -> InFile<SyntaxNodePtr> {
         self.func
     }
@@ -569,6 +849,10 @@ impl Diagnostic for ExternNonPrimitiveParam {
         "extern functions can only have primitives as parameter- and return types".to_string()
     }
 
+    fn code(&self) -> DiagnosticCode {
+        codes::EXTERN_NON_PRIMITIVE_PARAM
+    }
+
     fn source(&self) -> InFile<SyntaxNodePtr> {
         self.param
     }
@@ -589,6 +873,10 @@ impl Diagnostic for IntLiteralTooLarge {
         "int literal is too large".to_owned()
     }
 
+    fn code(&self) -> DiagnosticCode {
+        codes::INT_LITERAL_TOO_LARGE
+    }
+
     fn source(&self) -> InFile<SyntaxNodePtr> {
         self.literal.map(|ptr| ptr.into())
     }
@@ -610,6 +898,10 @@ impl Diagnostic for LiteralOutOfRange {
         format!("literal out of range for `{}`", self.int_ty.as_str())
     }
 
+    fn code(&self) -> DiagnosticCode {
+        codes::LITERAL_OUT_OF_RANGE
+    }
+
     fn source(&self) -> InFile<SyntaxNodePtr> {
         self.literal.map(|ptr| ptr.into())
     }
@@ -631,10 +923,29 @@ impl Diagnostic for InvalidLiteralSuffix {
         format!("invalid suffix `{}`", self.suffix)
     }
 
+    fn code(&self) -> DiagnosticCode {
+        codes::INVALID_LITERAL_SUFFIX
+    }
+
     fn source(&self) -> InFile<SyntaxNodePtr> {
         self.literal.map(|ptr| ptr.into())
     }
 
+    fn suggestions(&self, file_syntax: &SyntaxNode) -> Vec<Suggestion> {
+        let literal = self.literal.value.to_node(file_syntax);
+        let token = literal.token();
+        let (text, _) = literal.text_and_suffix();
+        vec![Suggestion {
+            message: "remove the suffix".to_owned(),
+            edits: vec![TextEdit::replace(
+                self.literal.file_id,
+                token.text_range(),
+                text.trim_end_matches('_').to_owned(),
+            )],
+            applicability: Applicability::MachineApplicable,
+        }]
+    }
+
     fn as_any(&self) -> &(dyn Any + Send + 'static) {
         self
     }
@@ -658,6 +969,10 @@ impl Diagnostic for InvalidFloatingPointLiteral {
         }
     }
 
+    fn code(&self) -> DiagnosticCode {
+        codes::INVALID_FLOATING_POINT_LITERAL
+    }
+
     fn source(&self) -> InFile<SyntaxNodePtr> {
         self.literal.map(|ptr| ptr.into())
     }
@@ -678,6 +993,10 @@ impl Diagnostic for InvalidLiteral {
         "invalid literal value".to_owned()
     }
 
+    fn code(&self) -> DiagnosticCode {
+        codes::INVALID_LITERAL
+    }
+
     fn source(&self) -> InFile<SyntaxNodePtr> {
         self.literal.map(|ptr| ptr.into())
     }
@@ -0,0 +1,63 @@
+//! Every diagnostic emitted by HIR has a stable code (e.g. `E0005`). Codes are never reused or
+//! renumbered once released, which makes them suitable for referencing in documentation and for
+//! looking up a long-form explanation with `mun explain`.
+
+use std::fmt;
+
+/// A stable identifier of a kind of diagnostic.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct DiagnosticCode(&'static str);
+
+impl DiagnosticCode {
+    /// Returns the textual representation of the code, e.g. `E0005`.
+    pub fn as_str(self) -> &'static str {
+        self.0
+    }
+}
+
+impl fmt::Display for DiagnosticCode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+macro_rules! diagnostic_codes {
+    ($($name:ident = $code:literal,)*) => {
+        $(pub const $name: DiagnosticCode = DiagnosticCode($code);)*
+
+        /// All diagnostic codes that can be emitted.
+        pub const ALL: &[DiagnosticCode] = &[$($name),*];
+    };
+}
+
+diagnostic_codes! {
+    UNRESOLVED_VALUE = "E0001",
+    UNRESOLVED_TYPE = "E0002",
+    EXPECTED_FUNCTION = "E0003",
+    PARAMETER_COUNT_MISMATCH = "E0004",
+    MISMATCHED_TYPE = "E0005",
+    INCOMPATIBLE_BRANCH = "E0006",
+    INVALID_LHS = "E0007",
+    MISSING_ELSE_BRANCH = "E0008",
+    CANNOT_APPLY_BINARY_OP = "E0009",
+    CANNOT_APPLY_UNARY_OP = "E0010",
+    DUPLICATE_DEFINITION = "E0011",
+    RETURN_MISSING_EXPRESSION = "E0012",
+    BREAK_OUTSIDE_LOOP = "E0013",
+    BREAK_WITH_VALUE_OUTSIDE_LOOP = "E0014",
+    ACCESS_UNKNOWN_FIELD = "E0015",
+    FIELD_COUNT_MISMATCH = "E0016",
+    MISSING_FIELDS = "E0017",
+    MISMATCHED_STRUCT_LIT = "E0018",
+    NO_FIELDS = "E0019",
+    NO_SUCH_FIELD = "E0020",
+    POSSIBLY_UNINITIALIZED_VARIABLE = "E0021",
+    EXTERN_CANNOT_HAVE_BODY = "E0022",
+    EXTERN_NON_PRIMITIVE_PARAM = "E0023",
+    INT_LITERAL_TOO_LARGE = "E0024",
+    LITERAL_OUT_OF_RANGE = "E0025",
+    INVALID_LITERAL_SUFFIX = "E0026",
+    INVALID_FLOATING_POINT_LITERAL = "E0027",
+    INVALID_LITERAL = "E0028",
+    DUPLICATE_PACKAGE_DEFINITION = "E0029",
+}
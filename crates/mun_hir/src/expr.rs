@@ -12,7 +12,7 @@ use crate::type_ref::{TypeRef, TypeRefBuilder, TypeRefId, TypeRefMap, TypeRefSou
 use either::Either;
 pub use mun_syntax::ast::PrefixOp as UnaryOp;
 use mun_syntax::ast::{ArgListOwner, BinOp, LoopBodyOwner, NameOwner, TypeAscriptionOwner};
-use mun_syntax::{ast, AstNode, AstPtr, SmolStr, T};
+use mun_syntax::{ast, AstNode, AstPtr, SmolStr, TextRange, T};
 use rustc_hash::FxHashMap;
 use std::ops::Index;
 use std::sync::Arc;
@@ -155,6 +155,22 @@ impl BodySourceMap {
         self.expr_map_back.get(expr).cloned()
     }
 
+    /// Returns the range in the source file of the syntax node of the specified expression.
+    pub fn expr_range(&self, expr: ExprId) -> Option<TextRange> {
+        self.expr_syntax(expr).map(|source| {
+            source
+                .value
+                .either(|it| it.syntax_node_ptr(), |it| it.syntax_node_ptr())
+                .range()
+        })
+    }
+
+    /// Returns the range in the source file of the syntax node of the specified pattern.
+    pub fn pat_range(&self, pat: PatId) -> Option<TextRange> {
+        self.pat_syntax(pat)
+            .map(|source| source.value.syntax_node_ptr().range())
+    }
+
     pub fn type_ref_syntax(&self, type_ref: TypeRefId) -> Option<AstPtr<ast::TypeRef>> {
         self.type_refs.type_ref_syntax(type_ref)
     }
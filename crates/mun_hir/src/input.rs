@@ -1,3 +1,4 @@
+use mun_syntax::TextRange;
 use relative_path::{RelativePath, RelativePathBuf};
 use rustc_hash::FxHashMap;
 
@@ -8,6 +9,13 @@ use rustc_hash::FxHashMap;
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct FileId(pub u32);
 
+/// A range of text in a specific file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FileRange {
+    pub file_id: FileId,
+    pub range: TextRange,
+}
+
 /// Files are grouped into source roots. A source root is a directory on the file systems which is
 /// watched for changes. Typically it corresponds to a single library.
 ///
@@ -22,6 +30,7 @@ pub struct SourceRootId(pub u32);
 #[derive(Default, Clone, Debug, PartialEq, Eq)]
 pub struct SourceRoot {
     files: FxHashMap<RelativePathBuf, FileId>,
+    dependencies: Vec<SourceRootId>,
 }
 
 impl SourceRoot {
@@ -40,4 +49,16 @@ impl SourceRoot {
     pub fn file_by_relative_path(&self, path: &RelativePath) -> Option<FileId> {
         self.files.get(path).copied()
     }
+
+    /// Adds a source root whose public items are visible from the files of this source root.
+    pub fn add_dependency(&mut self, dependency: SourceRootId) {
+        if !self.dependencies.contains(&dependency) {
+            self.dependencies.push(dependency);
+        }
+    }
+
+    /// Returns the source roots that this source root depends on.
+    pub fn dependencies(&self) -> &[SourceRootId] {
+        &self.dependencies
+    }
 }
@@ -37,6 +37,7 @@ mod tests;
 
 pub use salsa;
 
+pub use mun_syntax::{TextRange, TextUnit};
 pub use relative_path::{RelativePath, RelativePathBuf};
 
 pub use crate::{
@@ -48,19 +49,19 @@ pub use crate::{
     },
     display::HirDisplay,
     expr::{
-        resolver_for_expr, ArithOp, BinaryOp, Body, CmpOp, Expr, ExprId, ExprScopes, Literal,
-        LogicOp, Ordering, Pat, PatId, RecordLitField, Statement, UnaryOp,
+        resolver_for_expr, ArithOp, BinaryOp, Body, BodySourceMap, CmpOp, Expr, ExprId, ExprScopes,
+        Literal, LogicOp, Ordering, Pat, PatId, RecordLitField, Statement, UnaryOp,
     },
     ids::ItemLoc,
-    input::{FileId, SourceRoot, SourceRootId},
+    input::{FileId, FileRange, SourceRoot, SourceRootId},
     name::Name,
     name_resolution::PerNs,
     path::{Path, PathKind},
     raw::RawItems,
     resolve::{Resolution, Resolver},
     ty::{
-        lower::CallableDef, ApplicationTy, FloatTy, InferenceResult, IntTy, ResolveBitness, Ty,
-        TypeCtor,
+        lower::CallableDef, ApplicationTy, FloatTy, FnSig, InferenceResult, IntTy, ResolveBitness,
+        Ty, TypeCtor,
     },
 };
 
@@ -1,7 +1,7 @@
 mod per_ns;
 
 pub use self::per_ns::{Namespace, PerNs};
-use crate::{builtin_type::BuiltinType, FileId, HirDatabase, ModuleDef, Name};
+use crate::{builtin_type::BuiltinType, FileId, HirDatabase, ModuleDef, Name, SourceRootId};
 use once_cell::sync::Lazy;
 use rustc_hash::FxHashMap;
 use std::sync::Arc;
@@ -67,5 +67,52 @@ pub(crate) fn module_scope_query(db: &impl HirDatabase, file_id: FileId) -> Arc<
             _ => {}
         }
     }
+
+    // The public items of the other files of the source root are also in scope, followed by the
+    // public items of the dependencies of the source root, unless they are shadowed by an item
+    // that was added before.
+    let source_root_id = db.file_source_root(file_id);
+    let source_root = db.source_root(source_root_id);
+    let siblings = files_by_path(db, source_root_id)
+        .into_iter()
+        .filter(|sibling| *sibling != file_id);
+    let dependencies = source_root
+        .dependencies()
+        .iter()
+        .flat_map(|dependency| files_by_path(db, *dependency));
+    for file_id in siblings.chain(dependencies) {
+        for def in db.module_data(file_id).definitions() {
+            if let Some((name, def)) = package_item(db, *def) {
+                scope
+                    .items
+                    .entry(name)
+                    .or_insert_with(|| Resolution { def });
+            }
+        }
+    }
+
     Arc::new(scope)
 }
+
+/// Returns the files of a source root, ordered by their path like the code generator does, so
+/// that the file that provides an item does not depend on the order in which files were loaded.
+pub(crate) fn files_by_path(db: &impl HirDatabase, source_root_id: SourceRootId) -> Vec<FileId> {
+    let mut files = db.source_root(source_root_id).files().collect::<Vec<_>>();
+    files.sort_by_key(|file_id| db.file_relative_path(*file_id));
+    files
+}
+
+/// Returns the name and resolution of a definition if it is visible outside of its file, i.e. from
+/// the other files of its package and from the packages that depend on it.
+pub(crate) fn package_item(
+    db: &impl HirDatabase,
+    def: ModuleDef,
+) -> Option<(Name, PerNs<ModuleDef>)> {
+    match def {
+        ModuleDef::Function(f) if f.visibility(db).is_public() && !f.is_extern(db) => {
+            Some((f.name(db), PerNs::values(def)))
+        }
+        ModuleDef::Struct(s) => Some((s.name(db), PerNs::both(def, def))),
+        _ => None,
+    }
+}
@@ -47,3 +47,167 @@ fn check_module_data_does_not_change() {
         )
     }
 }
+
+/// Tests that the public items of a dependency are visible from the files of a source root.
+#[test]
+fn dependency_items_are_in_scope() {
+    use crate::input::{SourceRoot, SourceRootId};
+    use crate::{FileId, RelativePathBuf};
+
+    let (mut db, file_id) = MockDatabase::with_single_file("fn bar() {}");
+
+    let dependency_id = SourceRootId(1);
+    let dependency_file_id = FileId(1);
+    let rel_path = RelativePathBuf::from("dep.mun");
+    db.set_file_relative_path(dependency_file_id, rel_path.clone());
+    db.set_file_text(
+        dependency_file_id,
+        Arc::new("pub fn foo() {}\nfn private() {}\nstruct Baz;\npub fn bar() {}".to_owned()),
+    );
+    db.set_file_source_root(dependency_file_id, dependency_id);
+    let mut dependency = SourceRoot::default();
+    dependency.insert_file(rel_path, dependency_file_id);
+    db.set_source_root(dependency_id, Arc::new(dependency));
+
+    let source_root_id = db.file_source_root(file_id);
+    let mut source_root = (*db.source_root(source_root_id)).clone();
+    source_root.add_dependency(dependency_id);
+    db.set_source_root(source_root_id, Arc::new(source_root));
+
+    let scope = db.module_scope(file_id);
+    let module_of = |name: &str| {
+        scope
+            .entries()
+            .find(|(n, _)| n.to_string() == name)
+            .and_then(|(_, r)| r.def.take_values().or_else(|| r.def.take_types()))
+            .and_then(|def| match def {
+                crate::ModuleDef::Function(f) => Some(f.module(&db).file_id),
+                crate::ModuleDef::Struct(s) => Some(s.module(&db).file_id),
+                _ => None,
+            })
+    };
+    assert_eq!(module_of("foo"), Some(dependency_file_id));
+    assert_eq!(module_of("Baz"), Some(dependency_file_id));
+    assert_eq!(module_of("private"), None);
+    assert_eq!(module_of("bar"), Some(file_id));
+}
+
+#[test]
+fn dependency_items_are_resolved_in_path_order() {
+    use crate::input::{SourceRoot, SourceRootId};
+    use crate::{FileId, RelativePathBuf};
+
+    let (mut db, file_id) = MockDatabase::with_single_file("fn bar() {}");
+
+    // The file ids are assigned in the opposite order of the paths
+    let dependency_id = SourceRootId(1);
+    let mut dependency = SourceRoot::default();
+    for (dependency_file_id, path) in [(FileId(1), "b.mun"), (FileId(2), "a.mun")].iter() {
+        let rel_path = RelativePathBuf::from(*path);
+        db.set_file_relative_path(*dependency_file_id, rel_path.clone());
+        db.set_file_text(*dependency_file_id, Arc::new("pub fn foo() {}".to_owned()));
+        db.set_file_source_root(*dependency_file_id, dependency_id);
+        dependency.insert_file(rel_path, *dependency_file_id);
+    }
+    db.set_source_root(dependency_id, Arc::new(dependency));
+
+    let source_root_id = db.file_source_root(file_id);
+    let mut source_root = (*db.source_root(source_root_id)).clone();
+    source_root.add_dependency(dependency_id);
+    db.set_source_root(source_root_id, Arc::new(source_root));
+
+    let scope = db.module_scope(file_id);
+    let foo = scope
+        .entries()
+        .find(|(n, _)| n.to_string() == "foo")
+        .and_then(|(_, r)| r.def.take_values());
+    match foo {
+        Some(crate::ModuleDef::Function(f)) => assert_eq!(f.module(&db).file_id, FileId(2)),
+        _ => panic!("expected `foo` to resolve to a function"),
+    }
+}
+
+/// Inserts files into the source root of the file of a `MockDatabase`.
+fn add_sibling_files(db: &mut MockDatabase, file_id: crate::FileId, files: &[(u32, &str, &str)]) {
+    use crate::{FileId, RelativePathBuf};
+
+    let source_root_id = db.file_source_root(file_id);
+    let mut source_root = (*db.source_root(source_root_id)).clone();
+    for (id, path, text) in files.iter() {
+        let sibling_file_id = FileId(*id);
+        let rel_path = RelativePathBuf::from(*path);
+        db.set_file_relative_path(sibling_file_id, rel_path.clone());
+        db.set_file_text(sibling_file_id, Arc::new((*text).to_owned()));
+        db.set_file_source_root(sibling_file_id, source_root_id);
+        source_root.insert_file(rel_path, sibling_file_id);
+    }
+    db.set_source_root(source_root_id, Arc::new(source_root));
+}
+
+/// Tests that the public items of the other files of a package are visible from a file.
+#[test]
+fn sibling_items_are_in_scope() {
+    use crate::FileId;
+
+    let (mut db, file_id) = MockDatabase::with_single_file("fn bar() {}");
+    add_sibling_files(
+        &mut db,
+        file_id,
+        &[(
+            1,
+            "util.mun",
+            "pub fn foo() {}\nfn private() {}\nstruct Baz;\npub fn bar() {}",
+        )],
+    );
+
+    let scope = db.module_scope(file_id);
+    let module_of = |name: &str| {
+        scope
+            .entries()
+            .find(|(n, _)| n.to_string() == name)
+            .and_then(|(_, r)| r.def.take_values().or_else(|| r.def.take_types()))
+            .and_then(|def| match def {
+                crate::ModuleDef::Function(f) => Some(f.module(&db).file_id),
+                crate::ModuleDef::Struct(s) => Some(s.module(&db).file_id),
+                _ => None,
+            })
+    };
+    assert_eq!(module_of("foo"), Some(FileId(1)));
+    assert_eq!(module_of("Baz"), Some(FileId(1)));
+    assert_eq!(module_of("private"), None);
+    assert_eq!(module_of("bar"), Some(file_id));
+}
+
+/// Tests that only public items whose names clash across the files of a package are reported.
+#[test]
+fn duplicate_package_definitions() {
+    use crate::diagnostics::{DiagnosticSink, DuplicatePackageDefinition};
+    use crate::{FileId, Module};
+
+    let (mut db, file_id) =
+        MockDatabase::with_single_file("pub fn foo() {}\nfn private() {}\nstruct Baz;");
+    add_sibling_files(
+        &mut db,
+        file_id,
+        &[(
+            1,
+            "util.mun",
+            "pub fn foo() {}\nfn private() {}\nstruct Baz;\npub fn bar() {}",
+        )],
+    );
+
+    let mut duplicates = Vec::new();
+    for file_id in [file_id, FileId(1)].iter() {
+        let mut sink = DiagnosticSink::new(|_| {}).on(|d: &DuplicatePackageDefinition| {
+            duplicates.push((d.file, d.name.clone(), d.first_definition_file.to_string()))
+        });
+        Module::from(*file_id).diagnostics(&db, &mut sink);
+    }
+    assert_eq!(
+        duplicates,
+        vec![
+            (FileId(1), "foo".to_owned(), "main.mun".to_owned()),
+            (FileId(1), "Baz".to_owned(), "main.mun".to_owned()),
+        ]
+    );
+}
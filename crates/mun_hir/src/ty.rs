@@ -109,6 +109,22 @@ impl Ty {
         }
     }
 
+    /// Returns the source representation of a trivial value of this type (e.g. `0` for integers)
+    /// or `None` if the type has no such value. This is used to generate code for suggestions.
+    pub(crate) fn trivial_value(&self) -> Option<&'static str> {
+        match self {
+            Ty::Apply(ApplicationTy { ctor, .. }) => match ctor {
+                TypeCtor::Int(_) => Some("0"),
+                TypeCtor::Float(_) => Some("0.0"),
+                TypeCtor::Bool => Some("false"),
+                _ => None,
+            },
+            Ty::Infer(InferTy::IntVar(_)) => Some("0"),
+            Ty::Infer(InferTy::FloatVar(_)) => Some("0.0"),
+            _ => None,
+        }
+    }
+
     /// Returns the callable definition for the given expression or `None` if the type does not
     /// represent a callable.
     pub fn as_callable_def(&self) -> Option<CallableDef> {
@@ -365,7 +365,10 @@ impl<'a, D: HirDatabase> InferenceResultBuilder<'a, D> {
                     self.infer_expr(*expr, &Expectation::has_type(self.return_ty.clone()));
                 } else if self.return_ty != Ty::Empty {
                     self.diagnostics
-                        .push(InferenceDiagnostic::ReturnMissingExpression { id: tgt_expr });
+                        .push(InferenceDiagnostic::ReturnMissingExpression {
+                            id: tgt_expr,
+                            return_ty: self.return_ty.clone(),
+                        });
                 }
 
                 Ty::simple(TypeCtor::Never)
@@ -644,23 +647,24 @@ impl<'a, D: HirDatabase> InferenceResultBuilder<'a, D> {
         }
 
         let lit_fields: FxHashSet<_> = fields.iter().map(|f| &f.name).collect();
-        let missed_fields: Vec<Name> = struct_data
-            .fields
-            .iter()
-            .filter_map(|(_f, d)| {
-                let name = d.name.clone();
+        let (missed_fields, missed_field_tys): (Vec<Name>, Vec<Ty>) = expected
+            .fields(self.db)
+            .into_iter()
+            .filter_map(|f| {
+                let name = f.name(self.db);
                 if lit_fields.contains(&name) {
                     None
                 } else {
-                    Some(name)
+                    Some((name, f.ty(self.db)))
                 }
             })
-            .collect();
+            .unzip();
 
         if !missed_fields.is_empty() {
             self.diagnostics.push(InferenceDiagnostic::MissingFields {
                 id: tgt_expr,
                 names: missed_fields,
+                tys: missed_field_tys,
             });
         }
     }
@@ -1050,6 +1054,7 @@ mod diagnostics {
         },
         ReturnMissingExpression {
             id: ExprId,
+            return_ty: Ty,
         },
         BreakOutsideLoop {
             id: ExprId,
@@ -1070,6 +1075,7 @@ mod diagnostics {
         MissingFields {
             id: ExprId,
             names: Vec<Name>,
+            tys: Vec<Ty>,
         },
         MismatchedStructLit {
             id: ExprId,
@@ -1235,7 +1241,7 @@ mod diagnostics {
                         lhs,
                     });
                 }
-                InferenceDiagnostic::ReturnMissingExpression { id } => {
+                InferenceDiagnostic::ReturnMissingExpression { id, return_ty } => {
                     let id = body
                         .expr_syntax(*id)
                         .unwrap()
@@ -1244,6 +1250,7 @@ mod diagnostics {
                     sink.push(ReturnMissingExpression {
                         file,
                         return_expr: id,
+                        return_ty: return_ty.clone(),
                     });
                 }
                 InferenceDiagnostic::BreakOutsideLoop { id } => {
@@ -1302,7 +1309,7 @@ mod diagnostics {
                         found: *found,
                     })
                 }
-                InferenceDiagnostic::MissingFields { id, names } => {
+                InferenceDiagnostic::MissingFields { id, names, tys } => {
                     let fields = body
                         .expr_syntax(*id)
                         .unwrap()
@@ -1313,6 +1320,7 @@ mod diagnostics {
                         file,
                         fields,
                         field_names: names.to_vec(),
+                        field_tys: tys.to_vec(),
                     });
                 }
                 InferenceDiagnostic::MismatchedStructLit {
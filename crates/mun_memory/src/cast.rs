@@ -1,3 +1,4 @@
+use crate::mapping::IntegerNarrowing;
 use abi::HasStaticTypeInfo;
 use lazy_static::lazy_static;
 use std::{collections::HashMap, ptr::NonNull};
@@ -59,6 +60,20 @@ where
     unsafe { *dest.cast::<B>().as_mut() = value.into() };
 }
 
+/// Returns whether values of the type with `old_guid` can be converted to the type with
+/// `new_guid` without loss of information.
+pub fn is_lossless_cast(old_guid: abi::Guid, new_guid: abi::Guid) -> bool {
+    CAST_FN_TABLE.contains_key(&(old_guid, new_guid))
+}
+
+/// Returns whether values of the type with `old_guid` can be converted to the type with
+/// `new_guid`, if loss of information is allowed.
+pub fn is_lossy_cast(old_guid: abi::Guid, new_guid: abi::Guid) -> bool {
+    old_guid != new_guid
+        && PRIMITIVE_TABLE.contains_key(&old_guid)
+        && PRIMITIVE_TABLE.contains_key(&new_guid)
+}
+
 pub fn try_cast_from_to(
     old_guid: abi::Guid,
     new_guid: abi::Guid,
@@ -73,9 +88,193 @@ pub fn try_cast_from_to(
     }
 }
 
+/// Converts the value of the type with `old_guid` at `src` to the type with `new_guid` at `dest`,
+/// allowing loss of information:
+///
+/// * integers are narrowed according to `narrowing`;
+/// * floating-point values are rounded to the nearest integer and saturated, where NaN becomes
+///   zero;
+/// * integers are converted to the nearest floating-point value;
+/// * booleans become zero or one, and non-zero values become `true`.
+///
+/// Returns `false` if either type is not a primitive type.
+pub fn try_lossy_cast_from_to(
+    old_guid: abi::Guid,
+    new_guid: abi::Guid,
+    narrowing: IntegerNarrowing,
+    src: NonNull<u8>,
+    dest: NonNull<u8>,
+) -> bool {
+    let (old, new) = match (
+        PRIMITIVE_TABLE.get(&old_guid),
+        PRIMITIVE_TABLE.get(&new_guid),
+    ) {
+        (Some(old), Some(new)) => (*old, *new),
+        _ => return false,
+    };
+
+    let value = unsafe { read_primitive(old, src) };
+    unsafe { write_primitive(new, value, narrowing, dest) };
+    true
+}
+
+#[derive(Clone, Copy)]
+enum Primitive {
+    Bool,
+    I8,
+    I16,
+    I32,
+    I64,
+    I128,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    F32,
+    F64,
+}
+
+lazy_static! {
+    static ref PRIMITIVE_TABLE: HashMap<abi::Guid, Primitive> = {
+        let mut table = HashMap::new();
+        table.insert(bool::type_info().guid, Primitive::Bool);
+        table.insert(i8::type_info().guid, Primitive::I8);
+        table.insert(i16::type_info().guid, Primitive::I16);
+        table.insert(i32::type_info().guid, Primitive::I32);
+        table.insert(i64::type_info().guid, Primitive::I64);
+        table.insert(i128::type_info().guid, Primitive::I128);
+        table.insert(u8::type_info().guid, Primitive::U8);
+        table.insert(u16::type_info().guid, Primitive::U16);
+        table.insert(u32::type_info().guid, Primitive::U32);
+        table.insert(u64::type_info().guid, Primitive::U64);
+        table.insert(u128::type_info().guid, Primitive::U128);
+        table.insert(f32::type_info().guid, Primitive::F32);
+        table.insert(f64::type_info().guid, Primitive::F64);
+        table
+    };
+}
+
+/// A primitive value, widened to the largest type of its kind
+#[derive(Clone, Copy)]
+enum Scalar {
+    Bool(bool),
+    Signed(i128),
+    Unsigned(u128),
+    Float(f64),
+}
+
+unsafe fn read_primitive(primitive: Primitive, src: NonNull<u8>) -> Scalar {
+    let src = src.as_ptr();
+    match primitive {
+        Primitive::Bool => Scalar::Bool(*src.cast::<bool>()),
+        Primitive::I8 => Scalar::Signed(i128::from(*src.cast::<i8>())),
+        Primitive::I16 => Scalar::Signed(i128::from(*src.cast::<i16>())),
+        Primitive::I32 => Scalar::Signed(i128::from(*src.cast::<i32>())),
+        Primitive::I64 => Scalar::Signed(i128::from(*src.cast::<i64>())),
+        Primitive::I128 => Scalar::Signed(*src.cast::<i128>()),
+        Primitive::U8 => Scalar::Unsigned(u128::from(*src.cast::<u8>())),
+        Primitive::U16 => Scalar::Unsigned(u128::from(*src.cast::<u16>())),
+        Primitive::U32 => Scalar::Unsigned(u128::from(*src.cast::<u32>())),
+        Primitive::U64 => Scalar::Unsigned(u128::from(*src.cast::<u64>())),
+        Primitive::U128 => Scalar::Unsigned(*src.cast::<u128>()),
+        Primitive::F32 => Scalar::Float(f64::from(*src.cast::<f32>())),
+        Primitive::F64 => Scalar::Float(*src.cast::<f64>()),
+    }
+}
+
+/// Converts a `Scalar` to the integer type `$ty`.
+macro_rules! scalar_to_int {
+    ($ty:ident, $value:expr, $narrowing:expr) => {
+        match $value {
+            Scalar::Bool(value) => value as $ty,
+            Scalar::Signed(value) => match $narrowing {
+                IntegerNarrowing::Truncate => value as $ty,
+                IntegerNarrowing::Saturate => {
+                    if value < std::$ty::MIN as i128 {
+                        std::$ty::MIN
+                    } else if value > 0 && value as u128 > std::$ty::MAX as u128 {
+                        std::$ty::MAX
+                    } else {
+                        value as $ty
+                    }
+                }
+            },
+            Scalar::Unsigned(value) => match $narrowing {
+                IntegerNarrowing::Truncate => value as $ty,
+                IntegerNarrowing::Saturate => {
+                    if value > std::$ty::MAX as u128 {
+                        std::$ty::MAX
+                    } else {
+                        value as $ty
+                    }
+                }
+            },
+            Scalar::Float(value) => {
+                let value = value.round();
+                if value.is_nan() {
+                    0
+                } else if value <= std::$ty::MIN as f64 {
+                    std::$ty::MIN
+                } else if value >= std::$ty::MAX as f64 {
+                    std::$ty::MAX
+                } else {
+                    value as $ty
+                }
+            }
+        }
+    };
+}
+
+/// Converts a `Scalar` to the floating-point type `$ty`.
+macro_rules! scalar_to_float {
+    ($ty:ident, $value:expr) => {
+        match $value {
+            Scalar::Bool(value) => u8::from(value) as $ty,
+            Scalar::Signed(value) => value as $ty,
+            Scalar::Unsigned(value) => value as $ty,
+            Scalar::Float(value) => value as $ty,
+        }
+    };
+}
+
+// The macros also convert to the widened types themselves
+#[allow(clippy::unnecessary_cast)]
+unsafe fn write_primitive(
+    primitive: Primitive,
+    value: Scalar,
+    narrowing: IntegerNarrowing,
+    dest: NonNull<u8>,
+) {
+    let dest = dest.as_ptr();
+    match primitive {
+        Primitive::Bool => {
+            *dest.cast::<bool>() = match value {
+                Scalar::Bool(value) => value,
+                Scalar::Signed(value) => value != 0,
+                Scalar::Unsigned(value) => value != 0,
+                Scalar::Float(value) => value != 0.0,
+            }
+        }
+        Primitive::I8 => *dest.cast::<i8>() = scalar_to_int!(i8, value, narrowing),
+        Primitive::I16 => *dest.cast::<i16>() = scalar_to_int!(i16, value, narrowing),
+        Primitive::I32 => *dest.cast::<i32>() = scalar_to_int!(i32, value, narrowing),
+        Primitive::I64 => *dest.cast::<i64>() = scalar_to_int!(i64, value, narrowing),
+        Primitive::I128 => *dest.cast::<i128>() = scalar_to_int!(i128, value, narrowing),
+        Primitive::U8 => *dest.cast::<u8>() = scalar_to_int!(u8, value, narrowing),
+        Primitive::U16 => *dest.cast::<u16>() = scalar_to_int!(u16, value, narrowing),
+        Primitive::U32 => *dest.cast::<u32>() = scalar_to_int!(u32, value, narrowing),
+        Primitive::U64 => *dest.cast::<u64>() = scalar_to_int!(u64, value, narrowing),
+        Primitive::U128 => *dest.cast::<u128>() = scalar_to_int!(u128, value, narrowing),
+        Primitive::F32 => *dest.cast::<f32>() = scalar_to_float!(f32, value),
+        Primitive::F64 => *dest.cast::<f64>() = scalar_to_float!(f64, value),
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::try_cast_from_to;
+    use super::{is_lossless_cast, is_lossy_cast, try_cast_from_to, try_lossy_cast_from_to};
+    use crate::mapping::IntegerNarrowing;
     use abi::HasStaticTypeInfo;
     use std::ptr::NonNull;
 
@@ -247,4 +446,80 @@ mod tests {
     fn cast_u64_to_u128() {
         assert_cast(5u64, 0u128);
     }
+
+    fn assert_lossy_cast<A, B>(a: A, narrowing: IntegerNarrowing, expected: B)
+    where
+        A: Copy + HasStaticTypeInfo,
+        B: Copy + Default + PartialEq + std::fmt::Debug + HasStaticTypeInfo,
+    {
+        let mut b = B::default();
+        assert!(try_lossy_cast_from_to(
+            A::type_info().guid,
+            B::type_info().guid,
+            narrowing,
+            unsafe { NonNull::new_unchecked(&a as *const _ as *mut _) },
+            unsafe { NonNull::new_unchecked(&mut b as *mut _) }.cast::<u8>(),
+        ));
+        assert_eq!(b, expected);
+    }
+
+    #[test]
+    fn lossy_cast_detection() {
+        assert!(is_lossless_cast(
+            i32::type_info().guid,
+            i64::type_info().guid
+        ));
+        assert!(!is_lossless_cast(
+            i64::type_info().guid,
+            i32::type_info().guid
+        ));
+        assert!(is_lossy_cast(i64::type_info().guid, i32::type_info().guid));
+        assert!(is_lossy_cast(f64::type_info().guid, bool::type_info().guid));
+        assert!(!is_lossy_cast(f64::type_info().guid, f64::type_info().guid));
+    }
+
+    #[test]
+    fn lossy_cast_truncate() {
+        assert_lossy_cast(300i32, IntegerNarrowing::Truncate, 44u8);
+        assert_lossy_cast(-1i64, IntegerNarrowing::Truncate, std::u32::MAX);
+        assert_lossy_cast(std::u64::MAX, IntegerNarrowing::Truncate, -1i64);
+        assert_lossy_cast(70000u32, IntegerNarrowing::Truncate, 4464i16);
+    }
+
+    #[test]
+    fn lossy_cast_saturate() {
+        assert_lossy_cast(300i32, IntegerNarrowing::Saturate, 255u8);
+        assert_lossy_cast(-1i64, IntegerNarrowing::Saturate, 0u32);
+        assert_lossy_cast(std::u64::MAX, IntegerNarrowing::Saturate, std::i64::MAX);
+        assert_lossy_cast(-70000i32, IntegerNarrowing::Saturate, std::i16::MIN);
+        assert_lossy_cast(std::i128::MIN, IntegerNarrowing::Saturate, 0u128);
+        assert_lossy_cast(std::u128::MAX, IntegerNarrowing::Saturate, std::i128::MAX);
+    }
+
+    #[test]
+    fn lossy_cast_round() {
+        assert_lossy_cast(2.5f64, IntegerNarrowing::Truncate, 3i32);
+        assert_lossy_cast(-2.4f32, IntegerNarrowing::Truncate, -2i8);
+        assert_lossy_cast(1e10f64, IntegerNarrowing::Truncate, std::i32::MAX);
+        assert_lossy_cast(-5.0f64, IntegerNarrowing::Truncate, 0u16);
+        assert_lossy_cast(std::f64::NAN, IntegerNarrowing::Truncate, 0i64);
+        assert_lossy_cast(1.1f64, IntegerNarrowing::Truncate, 1.1f32);
+    }
+
+    #[test]
+    fn lossy_cast_int_to_float() {
+        assert_lossy_cast(16_777_217i32, IntegerNarrowing::Truncate, 16_777_216f32);
+        assert_lossy_cast(-3i64, IntegerNarrowing::Truncate, -3f64);
+        assert_lossy_cast(7u128, IntegerNarrowing::Truncate, 7f32);
+    }
+
+    #[test]
+    fn lossy_cast_bool() {
+        assert_lossy_cast(true, IntegerNarrowing::Truncate, 1i32);
+        assert_lossy_cast(false, IntegerNarrowing::Truncate, 0u8);
+        assert_lossy_cast(true, IntegerNarrowing::Truncate, 1f64);
+        assert_lossy_cast(2i32, IntegerNarrowing::Truncate, true);
+        assert_lossy_cast(0u64, IntegerNarrowing::Truncate, false);
+        assert_lossy_cast(0.5f32, IntegerNarrowing::Truncate, true);
+    }
 }
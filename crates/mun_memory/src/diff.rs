@@ -1,6 +1,6 @@
 pub mod myers;
 
-use crate::{TypeDesc, TypeFields};
+use crate::{TypeDesc, TypeFields, TypeMemory};
 
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum FieldEditKind {
@@ -74,12 +74,26 @@ impl PartialOrd for Diff {
 /// Given an `old` and a `new` set of types `T`, calculates the difference.
 pub fn diff<T>(old: &[T], new: &[T]) -> Vec<Diff>
 where
-    T: Copy + Eq + TypeDesc + TypeFields<T>,
+    T: Copy + Eq + TypeDesc + TypeFields<T> + TypeMemory,
 {
     let diff = myers::diff(old, new);
     let mut mapping: Vec<Diff> = Vec::with_capacity(diff.len());
     let (deletions, insertions) = myers::split_diff(&diff);
 
+    // The `Guid` of a struct does not depend on its memory kind, so structs that are equal can
+    // still have a different memory layout.
+    for (old_index, new_index) in unchanged_pairs(old.len(), new.len(), &deletions, &insertions) {
+        let old_ty = unsafe { old.get_unchecked(old_index) };
+        let new_ty = unsafe { new.get_unchecked(new_index) };
+        if old_ty.group().is_struct() && memory_kind_changed(old_ty, new_ty) {
+            mapping.push(Diff::Edit {
+                diff: field_diff(&old_ty.fields(), &new_ty.fields()),
+                old_index,
+                new_index,
+            });
+        }
+    }
+
     // ASSUMPTION: `FundamentalTypes` can never be converted to `StructTypes`, hence they can be
     // compared separately.
     let deleted_fundamentals = deletions
@@ -171,7 +185,7 @@ fn append_struct_mapping<T>(
     insertions: Vec<usize>,
     mapping: &mut Vec<Diff>,
 ) where
-    T: Eq + TypeDesc + TypeFields<T>,
+    T: Eq + TypeDesc + TypeFields<T> + TypeMemory,
 {
     let num_deleted = deletions.len();
     let num_inserted = insertions.len();
@@ -228,20 +242,19 @@ fn append_struct_mapping<T>(
 
         let old_index = unsafe { *deletions.get_unchecked(delete_idx) };
         let new_index = unsafe { *insertions.get_unchecked(insert_idx) };
+        let old_ty = unsafe { old.get_unchecked(old_index) };
+        let new_ty = unsafe { new.get_unchecked(new_index) };
+
         // If there is no difference between the old and new fields
-        mapping.push(if *length == 0 {
+        mapping.push(if *length == 0 && !memory_kind_changed(old_ty, new_ty) {
             // Move the struct
             Diff::Move {
                 old_index,
                 new_index,
             }
         } else {
-            let old_ty = unsafe { old.get_unchecked(old_index) };
-            let new_ty = unsafe { new.get_unchecked(new_index) };
-
             // ASSUMPTION: Don't use recursion, because all types are individually checked for
             // differences.
-            // TODO: Support value struct vs heap struct?
             let diff = field_diff(&old_ty.fields(), &new_ty.fields());
 
             // Edit the struct, potentially moving it in the process.
@@ -288,13 +301,35 @@ fn append_struct_mapping<T>(
 /// Given an `old` and a `new` set of fields, calculates the difference.
 fn field_diff<T>(old: &[(&str, T)], new: &[(&str, T)]) -> Vec<FieldDiff>
 where
-    T: Eq,
+    T: Eq + TypeDesc + TypeFields<T> + TypeMemory,
 {
     let diff = myers::diff(old, new);
     let (deletions, insertions) = myers::split_diff(&diff);
-    let mut insertions: Vec<Option<usize>> = insertions.into_iter().map(Some).collect();
 
     let mut mapping = Vec::with_capacity(diff.len());
+
+    // Fields of which the type changed its memory kind need to be converted, even though their
+    // types are equal.
+    for (old_index, new_index) in unchanged_pairs(old.len(), new.len(), &deletions, &insertions) {
+        let old_ty = unsafe { &old.get_unchecked(old_index).1 };
+        let new_ty = unsafe { &new.get_unchecked(new_index).1 };
+        if field_memory_kind_changed(old_ty, new_ty) {
+            mapping.push(if old_index == new_index {
+                FieldDiff::Edit {
+                    index: old_index,
+                    kind: FieldEditKind::ConvertType,
+                }
+            } else {
+                FieldDiff::Move {
+                    old_index,
+                    new_index,
+                    edit: Some(FieldEditKind::ConvertType),
+                }
+            });
+        }
+    }
+
+    let mut insertions: Vec<Option<usize>> = insertions.into_iter().map(Some).collect();
     // For all deletions,
     'outer: for old_idx in deletions {
         let old_ty = unsafe { old.get_unchecked(old_idx) };
@@ -397,6 +432,78 @@ where
         }
     }
 
+    // Moved and renamed fields of which the type changed its memory kind need to be converted
+    for diff in mapping.iter_mut() {
+        match diff {
+            FieldDiff::Edit { index, kind } => {
+                let old_ty = unsafe { &old.get_unchecked(*index).1 };
+                let new_ty = unsafe { &new.get_unchecked(*index).1 };
+                if field_memory_kind_changed(old_ty, new_ty) {
+                    *kind = FieldEditKind::ConvertType;
+                }
+            }
+            FieldDiff::Move {
+                old_index,
+                new_index,
+                edit,
+            } => {
+                let old_ty = unsafe { &old.get_unchecked(*old_index).1 };
+                let new_ty = unsafe { &new.get_unchecked(*new_index).1 };
+                if field_memory_kind_changed(old_ty, new_ty) {
+                    *edit = Some(FieldEditKind::ConvertType);
+                }
+            }
+            FieldDiff::Insert { .. } | FieldDiff::Delete { .. } => (),
+        }
+    }
+
     mapping.shrink_to_fit();
     mapping
 }
+
+/// Given the number of `old` and `new` elements and the `deletions` and `insertions` of their
+/// `myers::diff`, returns the `(old_index, new_index)` pairs of elements that remained unchanged.
+fn unchanged_pairs(
+    num_old: usize,
+    num_new: usize,
+    deletions: &[usize],
+    insertions: &[usize],
+) -> Vec<(usize, usize)> {
+    let old_indices = (0..num_old).filter(|idx| !deletions.contains(idx));
+    let new_indices = (0..num_new).filter(|idx| !insertions.contains(idx));
+    old_indices.zip(new_indices).collect()
+}
+
+/// Returns whether the memory layout of the struct types `old` and `new` differs, because either
+/// of them or the type of one of their (nested) value struct fields changed its memory kind.
+///
+/// Expects `old` and `new` to have equal fields, apart from their memory kinds.
+fn memory_kind_changed<T>(old: &T, new: &T) -> bool
+where
+    T: TypeDesc + TypeFields<T> + TypeMemory,
+{
+    old.is_stack_allocated() != new.is_stack_allocated()
+        || old
+            .fields()
+            .iter()
+            .zip(new.fields().iter())
+            .any(|((_, old_ty), (_, new_ty))| field_memory_kind_changed(old_ty, new_ty))
+}
+
+/// Returns whether the memory layout of a field changed from `old` to `new`, because its struct
+/// type or one of its nested value struct fields changed its memory kind.
+pub(crate) fn field_memory_kind_changed<T>(old: &T, new: &T) -> bool
+where
+    T: TypeDesc + TypeFields<T> + TypeMemory,
+{
+    if !old.group().is_struct() || !new.group().is_struct() {
+        return false;
+    }
+
+    if old.is_stack_allocated() != new.is_stack_allocated() {
+        return true;
+    }
+
+    // The fields of a garbage collected struct are stored in a separate object
+    old.is_stack_allocated() && memory_kind_changed(old, new)
+}
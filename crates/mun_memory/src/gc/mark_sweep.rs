@@ -1,7 +1,7 @@
 use crate::{
     cast,
     gc::{Event, GcPtr, GcRuntime, Observer, RawGcPtr, Stats, TypeTrace},
-    mapping::{self, FieldMapping, MemoryMapper},
+    mapping::{self, FieldMapping, MemoryMapper, MigrationPolicy},
     TypeDesc, TypeMemory,
 };
 use mapping::{Conversion, Mapping};
@@ -68,6 +68,14 @@ where
     pub fn observer(&self) -> &O {
         &self.observer
     }
+
+    /// Returns `true` if the type of any allocated object satisfies the `predicate`.
+    pub fn any_object_type<F: FnMut(&T) -> bool>(&self, mut predicate: F) -> bool {
+        self.objects
+            .read()
+            .values()
+            .any(|object| predicate(&object.ty))
+    }
 }
 
 fn alloc_obj<T: Clone + TypeMemory + TypeTrace>(ty: T) -> Pin<Box<ObjectInfo<T>>> {
@@ -101,7 +109,7 @@ where
     }
 
     fn ptr_type(&self, handle: GcPtr) -> T {
-        let _ = self.objects.read();
+        let _objects = self.objects.read();
 
         // Convert the handle to our internal representation
         let object_info: *const ObjectInfo<T> = handle.into();
@@ -111,7 +119,7 @@ where
     }
 
     fn root(&self, handle: GcPtr) {
-        let _ = self.objects.write();
+        let _objects = self.objects.write();
 
         // Convert the handle to our internal representation
         let object_info: *mut ObjectInfo<T> = handle.into();
@@ -120,7 +128,7 @@ where
     }
 
     fn unroot(&self, handle: GcPtr) {
-        let _ = self.objects.write();
+        let _objects = self.objects.write();
 
         // Convert the handle to our internal representation
         let object_info: *mut ObjectInfo<T> = handle.into();
@@ -249,11 +257,13 @@ where
                         NonNull::new_unchecked(std::alloc::alloc_zeroed(conversion.new_ty.layout()))
                     };
 
-                    map_fields(
+                    map_object(
                         self,
                         &mut new_allocations,
                         &mapping.conversions,
-                        &conversion.field_mapping,
+                        mapping.policy,
+                        old_ty,
+                        conversion,
                         src,
                         dest,
                     );
@@ -284,10 +294,40 @@ where
 
         return deleted;
 
+        /// Maps the fields of the object at `src` to the object at `dest` and applies the
+        /// user-defined migration of the `conversion`, if any.
+        fn map_object<T, O>(
+            gc: &MarkSweep<T, O>,
+            new_allocations: &mut Vec<Pin<Box<ObjectInfo<T>>>>,
+            conversions: &HashMap<T, Conversion<T>>,
+            policy: MigrationPolicy,
+            old_ty: &T,
+            conversion: &Conversion<T>,
+            src: NonNull<u8>,
+            dest: NonNull<u8>,
+        ) where
+            T: TypeDesc + TypeMemory + TypeTrace + Clone + Eq + Hash,
+            O: Observer<Event = Event>,
+        {
+            map_fields(
+                gc,
+                new_allocations,
+                conversions,
+                policy,
+                &conversion.field_mapping,
+                src,
+                dest,
+            );
+            if let Some(migration) = &conversion.migration {
+                migration(old_ty, src, &conversion.new_ty, dest);
+            }
+        }
+
         fn map_fields<T, O>(
             gc: &MarkSweep<T, O>,
             new_allocations: &mut Vec<Pin<Box<ObjectInfo<T>>>>,
             conversions: &HashMap<T, Conversion<T>>,
+            policy: MigrationPolicy,
             mapping: &[FieldMapping<T>],
             src: NonNull<u8>,
             dest: NonNull<u8>,
@@ -330,11 +370,13 @@ where
                                     // struct(value) -> struct(value)
                                     if is_same_struct {
                                         // Map in-memory struct to in-memory struct
-                                        map_fields(
+                                        map_object(
                                             gc,
                                             new_allocations,
                                             conversions,
-                                            &conversion.as_ref().unwrap().field_mapping,
+                                            policy,
+                                            old_ty,
+                                            conversion.unwrap(),
                                             unsafe { NonNull::new_unchecked(field_src) },
                                             unsafe { NonNull::new_unchecked(field_dest) },
                                         );
@@ -351,11 +393,13 @@ where
 
                                     if is_same_struct {
                                         // Map in-memory struct to heap-allocated struct
-                                        map_fields(
+                                        map_object(
                                             gc,
                                             new_allocations,
                                             conversions,
-                                            &conversion.as_ref().unwrap().field_mapping,
+                                            policy,
+                                            old_ty,
+                                            conversion.unwrap(),
                                             unsafe { NonNull::new_unchecked(field_src) },
                                             unsafe { NonNull::new_unchecked(object.ptr) },
                                         );
@@ -420,11 +464,13 @@ where
                                     if obj.ty == *old_ty {
                                         // The object still needs to be mapped
                                         // Map heap-allocated struct to in-memory struct
-                                        map_fields(
+                                        map_object(
                                             gc,
                                             new_allocations,
                                             conversions,
-                                            &conversion.as_ref().unwrap().field_mapping,
+                                            policy,
+                                            old_ty,
+                                            conversion.unwrap(),
                                             unsafe { NonNull::new_unchecked(obj.ptr) },
                                             unsafe { NonNull::new_unchecked(field_dest) },
                                         );
@@ -451,7 +497,16 @@ where
                             unsafe { NonNull::new_unchecked(field_src) },
                             unsafe { NonNull::new_unchecked(field_dest) },
                         ) {
-                            // Failed to cast. Use the previously zero-initialized value instead
+                            if let MigrationPolicy::Lossy(narrowing) = policy {
+                                cast::try_lossy_cast_from_to(
+                                    *old_ty.guid(),
+                                    *new_ty.guid(),
+                                    narrowing,
+                                    unsafe { NonNull::new_unchecked(field_src) },
+                                    unsafe { NonNull::new_unchecked(field_dest) },
+                                );
+                            }
+                            // Otherwise, use the previously zero-initialized value instead
                         }
                     }
                     mapping::Action::Copy { old_offset } => {
@@ -1,24 +1,63 @@
 use crate::{
-    diff::{diff, Diff, FieldDiff, FieldEditKind},
+    diff::{diff, field_memory_kind_changed, Diff, FieldDiff, FieldEditKind},
     gc::GcPtr,
     TypeDesc, TypeFields, TypeMemory,
 };
 use std::{
     collections::{HashMap, HashSet},
     hash::Hash,
+    ptr::NonNull,
+    sync::Arc,
 };
 
 pub struct Mapping<T: Eq + Hash, U: TypeDesc + TypeMemory> {
     pub deletions: HashSet<T>,
     pub conversions: HashMap<T, Conversion<U>>,
     pub identical: Vec<(T, T)>,
+    /// How fields are converted that cannot be cast without loss of information
+    pub policy: MigrationPolicy,
+}
+
+/// Determines how the value of a field is migrated, when the field's type changes to a primitive
+/// type that cannot represent all values of its old type.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MigrationPolicy {
+    /// Lossy conversions are not allowed. Memory mappers reset the fields to zero, but users of a
+    /// `Mapping` are expected to reject it altogether.
+    Strict,
+    /// The values are converted lossily, narrowing integers as specified.
+    Lossy(IntegerNarrowing),
+    /// The fields are reset to zero.
+    Reset,
+}
+
+impl Default for MigrationPolicy {
+    fn default() -> Self {
+        MigrationPolicy::Reset
+    }
+}
+
+/// Determines how an integer is converted to an integer type that cannot represent its value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IntegerNarrowing {
+    /// Discards the most significant bits that do not fit the new type
+    Truncate,
+    /// Clamps the value to the minimum or maximum value of the new type
+    Saturate,
 }
 
 pub struct Conversion<T: TypeDesc + TypeMemory> {
     pub field_mapping: Vec<FieldMapping<T>>,
     pub new_ty: T,
+    /// A user-defined migration that is applied after the fields have been mapped
+    pub migration: Option<MigrationFn<T>>,
 }
 
+/// A user-defined function that migrates an object to its new type. It receives the old type, a
+/// pointer to the old object's memory, the new type, and a pointer to the new object's memory -
+/// in which all fields have already been mapped.
+pub type MigrationFn<T> = Arc<dyn Fn(&T, NonNull<u8>, &T, NonNull<u8>) + Send + Sync>;
+
 /// Description of the mapping of a single field. When stored together with the new index, this
 /// provides all information necessary for a mapping function.
 pub struct FieldMapping<T: TypeDesc + TypeMemory> {
@@ -127,6 +166,7 @@ where
             deletions,
             conversions,
             identical,
+            policy: MigrationPolicy::default(),
         }
     }
 }
@@ -261,6 +301,54 @@ pub unsafe fn field_mapping<T: Clone + TypeDesc + TypeFields<T> + TypeMemory>(
             })
             .collect(),
         new_ty,
+        migration: None,
+    }
+}
+
+impl<T> Conversion<T>
+where
+    T: Clone + Eq + TypeDesc + TypeFields<T> + TypeMemory,
+{
+    /// Maps the field `new_field` of the new type from the field `old_field` of `old_ty`,
+    /// overriding the mapping that was derived from the difference between the types. This can be
+    /// used to retain the value of a renamed field.
+    ///
+    /// Returns `false` if either of the fields does not exist.
+    pub fn map_field(&mut self, old_ty: &T, old_field: &str, new_field: &str) -> bool {
+        let old_fields = old_ty.fields();
+        let old_index = match old_fields.iter().position(|(name, _)| *name == old_field) {
+            Some(index) => index,
+            None => return false,
+        };
+        let new_index = match self
+            .new_ty
+            .fields()
+            .iter()
+            .position(|(name, _)| *name == new_field)
+        {
+            Some(index) => index,
+            None => return false,
+        };
+
+        let old_offset = usize::from(old_ty.offsets()[old_index]);
+        let old_field_ty = &old_fields[old_index].1;
+        let mapping = &mut self.field_mapping[new_index];
+        mapping.action = if old_field_ty.group().is_struct() != mapping.new_ty.group().is_struct() {
+            // A struct cannot be converted to or from a fundamental type
+            Action::Insert
+        } else if *old_field_ty == mapping.new_ty
+            && !field_memory_kind_changed(old_field_ty, &mapping.new_ty)
+        {
+            Action::Copy { old_offset }
+        } else {
+            // Like a field of which the type changed, a struct is converted by its own conversion,
+            // which also (un)boxes it when its memory kind changed.
+            Action::Cast {
+                old_offset,
+                old_ty: old_field_ty.clone(),
+            }
+        };
+        true
     }
 }
 
@@ -509,3 +509,114 @@ fn rename_field2() {
     );
     assert_eq_struct(&apply_diff(old, new, diff), &vec![struct2.clone()]);
 }
+
+#[test]
+fn change_memory_kind() {
+    let int = TypeInfo::new_fundamental::<i64>();
+
+    let struct1 = TypeInfo::new_struct(
+        STRUCT1_NAME,
+        STRUCT1_GUID,
+        StructInfo::new(&[("a", &int)]).with_memory_kind(abi::StructMemoryKind::Value),
+    );
+    let struct2 = TypeInfo::new_struct(
+        STRUCT1_NAME,
+        STRUCT1_GUID,
+        StructInfo::new(&[("a", &int)]).with_memory_kind(abi::StructMemoryKind::GC),
+    );
+
+    let old = &[&struct1];
+    let new = &[&struct2];
+
+    let diff = diff(old, new);
+    assert_eq!(
+        diff,
+        vec![Diff::Edit {
+            diff: vec![],
+            old_index: 0,
+            new_index: 0,
+        }]
+    );
+    assert_eq_struct(&apply_diff(old, new, diff), &vec![struct2.clone()]);
+}
+
+#[test]
+fn change_field_memory_kind() {
+    let int = TypeInfo::new_fundamental::<i64>();
+
+    let value_struct = TypeInfo::new_struct(
+        STRUCT1_NAME,
+        STRUCT1_GUID,
+        StructInfo::new(&[("a", &int)]).with_memory_kind(abi::StructMemoryKind::Value),
+    );
+    let gc_struct = TypeInfo::new_struct(
+        STRUCT1_NAME,
+        STRUCT1_GUID,
+        StructInfo::new(&[("a", &int)]).with_memory_kind(abi::StructMemoryKind::GC),
+    );
+    let struct1 = TypeInfo::new_struct(
+        STRUCT2_NAME,
+        STRUCT2_GUID,
+        StructInfo::new(&[("b", &int), ("c", &value_struct)])
+            .with_memory_kind(abi::StructMemoryKind::GC),
+    );
+    let struct2 = TypeInfo::new_struct(
+        STRUCT2_NAME,
+        STRUCT2_GUID,
+        StructInfo::new(&[("b", &int), ("c", &gc_struct)])
+            .with_memory_kind(abi::StructMemoryKind::GC),
+    );
+
+    // Boxes the value struct field into a garbage collected struct
+    let old = &[&value_struct, &struct1];
+    let new = &[&gc_struct, &struct2];
+
+    let diff1 = diff(old, new);
+    assert_eq!(
+        diff1,
+        vec![
+            Diff::Edit {
+                diff: vec![],
+                old_index: 0,
+                new_index: 0,
+            },
+            Diff::Edit {
+                diff: vec![FieldDiff::Edit {
+                    index: 1,
+                    kind: FieldEditKind::ConvertType,
+                }],
+                old_index: 1,
+                new_index: 1,
+            }
+        ]
+    );
+    assert_eq_struct(
+        &apply_diff(old, new, diff1),
+        &vec![gc_struct.clone(), struct2.clone()],
+    );
+
+    // Inlines the garbage collected struct field into a value struct
+    let diff2 = diff(new, old);
+    assert_eq!(
+        diff2,
+        vec![
+            Diff::Edit {
+                diff: vec![],
+                old_index: 0,
+                new_index: 0,
+            },
+            Diff::Edit {
+                diff: vec![FieldDiff::Edit {
+                    index: 1,
+                    kind: FieldEditKind::ConvertType,
+                }],
+                old_index: 1,
+                new_index: 1,
+            }
+        ]
+    );
+    assert_eq_struct(
+        &apply_diff(new, old, diff2),
+        &vec![value_struct.clone(), struct1.clone()],
+    );
+}
@@ -21,6 +21,7 @@ pub const STRUCT2_GUID: abi::Guid = abi::Guid {
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct StructInfo {
     fields: Vec<(String, TypeInfo)>,
+    memory_kind: abi::StructMemoryKind,
 }
 
 impl StructInfo {
@@ -30,9 +31,15 @@ impl StructInfo {
                 .iter()
                 .map(|(name, ty)| (name.to_string(), (*ty).clone()))
                 .collect(),
+            memory_kind: abi::StructMemoryKind::Value,
         }
     }
 
+    pub fn with_memory_kind(mut self, memory_kind: abi::StructMemoryKind) -> Self {
+        self.memory_kind = memory_kind;
+        self
+    }
+
     pub fn layout(&self) -> Layout {
         // NOTE: This implementation is naive, but it is merely a test
         let size = self.fields.iter().map(|ty| ty.1.layout.size()).sum();
@@ -112,8 +119,10 @@ impl TypeMemory for &TypeInfo {
     }
 
     fn is_stack_allocated(&self) -> bool {
-        // NOTE: This contrived test does not support structs
-        true
+        match &self.tail {
+            TypeInfoTail::Empty => true,
+            TypeInfoTail::Struct(s) => s.memory_kind == abi::StructMemoryKind::Value,
+        }
     }
 }
 
@@ -279,6 +288,7 @@ fn apply_mapping<'t>(old: &mut TypeInfo, new: &TypeInfo, mapping: &[FieldDiff])
             }
 
             old_struct.fields = combined;
+            old_struct.memory_kind = new_struct.memory_kind.clone();
             old.layout = old_struct.layout();
         } else {
             unreachable!()
@@ -63,3 +63,16 @@ fn collect_rooted() {
     assert_eq!(events.next(), Some(Event::End));
     assert_eq!(events.next(), None);
 }
+
+#[test]
+fn any_object_type() {
+    let runtime = MarkSweep::<&'static TypeInfo, EventAggregator<Event>>::default();
+    runtime.alloc(i64::type_info());
+
+    assert!(runtime.any_object_type(|ty| std::ptr::eq(*ty, i64::type_info())));
+    assert!(!runtime.any_object_type(|ty| std::ptr::eq(*ty, i32::type_info())));
+
+    // Collected objects no longer count
+    runtime.collect();
+    assert!(!runtime.any_object_type(|ty| std::ptr::eq(*ty, i64::type_info())));
+}
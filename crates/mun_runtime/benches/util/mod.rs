@@ -1,10 +1,10 @@
 use mlua::Lua;
 use mun_compiler::{Config, Driver, OptimizationLevel, PathOrInline};
 use mun_runtime::RuntimeBuilder;
-use std::cell::RefCell;
+use parking_lot::RwLock;
 use std::io::Cursor;
 use std::path::{Path, PathBuf};
-use std::rc::Rc;
+use std::sync::Arc;
 use termcolor::NoColor;
 use wasmer_runtime::{instantiate, Instance};
 
@@ -14,7 +14,7 @@ fn compute_resource_path<P: AsRef<Path>>(p: P) -> PathBuf {
         .join(p)
 }
 
-pub fn runtime_from_file<P: AsRef<Path>>(p: P) -> Rc<RefCell<mun_runtime::Runtime>> {
+pub fn runtime_from_file<P: AsRef<Path>>(p: P) -> Arc<RwLock<mun_runtime::Runtime>> {
     let path = PathOrInline::Path(compute_resource_path(p));
     let (mut driver, file_id) = Driver::with_file(
         Config {
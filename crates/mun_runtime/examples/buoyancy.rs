@@ -35,6 +35,6 @@ fn main() {
         let _: () = invoke_fn!(runtime, "sim_update", ctx.clone(), elapsed_secs).wait();
         previous = now;
 
-        runtime.borrow_mut().update();
+        runtime.write().update();
     }
 }
@@ -17,6 +17,6 @@ fn main() {
         let n: i64 = invoke_fn!(runtime, "nth").wait();
         let result: i64 = invoke_fn!(runtime, "fibonacci", n).wait();
         println!("fibonacci({}) = {}", n, result);
-        runtime.borrow_mut().update();
+        runtime.write().update();
     }
 }
@@ -1,7 +1,7 @@
 use std::io;
 use std::path::{Path, PathBuf};
 
-use crate::DispatchTable;
+use crate::{DispatchTable, LossyConversion, Migrations};
 use abi::AssemblyInfo;
 use libloading::Symbol;
 
@@ -9,16 +9,58 @@ mod temp_library;
 
 use self::temp_library::TempLibrary;
 use crate::garbage_collector::{GarbageCollector, UnsafeTypeInfo};
-use memory::mapping::{Mapping, MemoryMapper};
+use memory::{
+    mapping::{Mapping, MemoryMapper},
+    TypeDesc,
+};
 use std::{collections::HashSet, ptr::NonNull, sync::Arc};
 
+/// The changes to the memory of an assembly that result from swapping its shared library.
+#[derive(Debug, Default)]
+pub struct SwapSummary {
+    /// The names of the types whose layout changed, and whose objects were migrated
+    pub migrated_types: Vec<String>,
+    /// The number of objects that were deleted, because their type no longer exists
+    pub deleted_objects: usize,
+    /// The fields that could not be migrated to their new type without loss of information
+    pub lossy_conversions: Vec<LossyConversion>,
+}
+
+/// A swap of an assembly's shared library that has been validated, but not yet committed.
+pub struct PendingSwap {
+    new_assembly: Assembly,
+    mapping: Mapping<UnsafeTypeInfo, UnsafeTypeInfo>,
+    lossy_conversions: Vec<LossyConversion>,
+}
+
+/// The library of a previous version of an assembly, which is retained as long as objects refer to
+/// its type information.
+pub(crate) struct LegacyLibrary {
+    _library: TempLibrary,
+    types: Vec<NonNull<abi::TypeInfo>>,
+}
+
+// Safety: The type pointers are only compared to the types of allocated objects, they are never
+// dereferenced. The library itself can be shared between threads.
+unsafe impl Send for LegacyLibrary {}
+unsafe impl Sync for LegacyLibrary {}
+
+impl LegacyLibrary {
+    /// Returns `true` if the type of any object allocated by `gc` is defined by the library.
+    pub fn is_referenced(&self, gc: &GarbageCollector) -> bool {
+        gc.any_object_type(|ty| self.types.contains(&ty.into_inner()))
+    }
+}
+
 /// An assembly is a hot reloadable compilation unit, consisting of one or more Mun modules.
 pub struct Assembly {
     library_path: PathBuf,
     library: TempLibrary,
-    legacy_libs: Vec<TempLibrary>,
+    legacy_libs: Vec<LegacyLibrary>,
     info: AssemblyInfo,
     allocator: Arc<GarbageCollector>,
+    // Indices of the dispatch table entries that are filled in by the runtime
+    linked_fns: Vec<usize>,
 }
 
 impl Assembly {
@@ -29,8 +71,37 @@ impl Assembly {
         gc: Arc<GarbageCollector>,
         runtime_dispatch_table: &DispatchTable,
     ) -> Result<Self, failure::Error> {
+        let assembly = Assembly::open(library_path, gc)?;
+
+        // Ensure that any loaded `Assembly` can be linked safely.
+        assembly.ensure_linkable(runtime_dispatch_table)?;
+        Ok(assembly)
+    }
+
+    /// Loads an assembly and its information for the shared library at `library_path`, without
+    /// verifying that it can be linked. This allows its dependencies to be loaded first.
+    pub fn open(library_path: &Path, gc: Arc<GarbageCollector>) -> Result<Self, failure::Error> {
         let library = TempLibrary::new(library_path)?;
+        Assembly::from_library(library_path, library, gc)
+    }
 
+    /// Loads an assembly and its information from the contents of a shared library, without
+    /// verifying that it can be linked. The `name` identifies the assembly in the runtime; relative
+    /// paths of its dependencies are resolved relative to the directory of `name`.
+    pub fn open_bytes(
+        name: &Path,
+        bytes: &[u8],
+        gc: Arc<GarbageCollector>,
+    ) -> Result<Self, failure::Error> {
+        let library = TempLibrary::from_bytes(bytes)?;
+        Assembly::from_library(name, library, gc)
+    }
+
+    fn from_library(
+        library_path: &Path,
+        library: TempLibrary,
+        gc: Arc<GarbageCollector>,
+    ) -> Result<Self, failure::Error> {
         // Check whether the library has a symbols function
         let get_info: Symbol<'_, extern "C" fn() -> AssemblyInfo> =
             unsafe { library.library().get(b"get_info") }?;
@@ -42,21 +113,35 @@ impl Assembly {
         set_allocator_handle(allocator_ptr);
 
         let info = get_info();
-        let assembly = Assembly {
+        let linked_fns = info
+            .dispatch_table
+            .iter()
+            .enumerate()
+            .filter(|(_, (fn_ptr, _))| fn_ptr.is_null())
+            .map(|(idx, _)| idx)
+            .collect();
+
+        Ok(Assembly {
             library_path: library_path.to_path_buf(),
             library,
             legacy_libs: Vec::new(),
             info,
             allocator: gc,
-        };
+            linked_fns,
+        })
+    }
 
-        // Ensure that any loaded `Assembly` can be linked safely.
-        assembly.ensure_linkable(runtime_dispatch_table)?;
-        Ok(assembly)
+    /// Returns the paths of the assemblies that this assembly depends on. Relative paths are
+    /// resolved relative to the directory that contains the assembly.
+    pub fn dependencies(&self) -> impl Iterator<Item = PathBuf> + '_ {
+        let dir = self.library_path.parent().unwrap_or_else(|| Path::new(""));
+        self.info
+            .dependencies()
+            .map(move |dependency| dir.join(dependency))
     }
 
     /// Verifies that the `Assembly` resolves all dependencies in the `DispatchTable`.
-    fn ensure_linkable(&self, runtime_dispatch_table: &DispatchTable) -> Result<(), io::Error> {
+    pub fn ensure_linkable(&self, runtime_dispatch_table: &DispatchTable) -> Result<(), io::Error> {
         let fn_names: HashSet<&str> = self
             .info
             .symbols
@@ -95,23 +180,24 @@ impl Assembly {
             }
         }
 
+        // Ensure that the assembly still provides all functions that other assemblies depend on
         if let Some(dependencies) = runtime_dispatch_table
             .fn_dependencies
-            .get(self.info.symbols.path())
+            .get(self.library_path.to_string_lossy().as_ref())
         {
-            for fn_name in dependencies.keys() {
-                if !fn_names.contains(&fn_name.as_str()) {
-                    return Err(io::Error::new(
-                        io::ErrorKind::NotFound,
-                        format!("Failed to link: function `{}` is missing.", fn_name),
-                    ));
-                }
-            }
-
-            for fn_definition in self.info.symbols.functions().iter() {
-                let (fn_prototype, _) = dependencies
-                    .get(fn_definition.prototype.name())
-                    .expect("The dependency must exist after the previous check.");
+            for (fn_name, (fn_prototype, _)) in dependencies.iter() {
+                let fn_definition = self
+                    .info
+                    .symbols
+                    .functions()
+                    .iter()
+                    .find(|f| f.prototype.name() == fn_name)
+                    .ok_or_else(|| {
+                        io::Error::new(
+                            io::ErrorKind::NotFound,
+                            format!("Failed to link: function `{}` is missing.", fn_name),
+                        )
+                    })?;
 
                 if fn_prototype.signature != fn_definition.prototype.signature {
                     return Err(io::Error::new(
@@ -134,62 +220,99 @@ impl Assembly {
             runtime_dispatch_table.insert_fn(function.prototype.name(), function.clone());
         }
 
-        for (dispatch_ptr, fn_prototype) in self.info.dispatch_table.iter_mut() {
-            if dispatch_ptr.is_null() {
-                let fn_ptr = runtime_dispatch_table
+        self.relink(runtime_dispatch_table);
+    }
+
+    /// Updates the function pointers that the runtime filled in to the functions that are currently
+    /// in the runtime's dispatch table. This is required after an assembly that this assembly
+    /// depends on has been reloaded.
+    pub fn relink(&mut self, runtime_dispatch_table: &DispatchTable) {
+        let prototypes = self.info.dispatch_table.prototypes();
+        let fn_ptrs = self
+            .linked_fns
+            .iter()
+            .map(|idx| {
+                let fn_prototype = &prototypes[*idx];
+                runtime_dispatch_table
                     .get_fn(fn_prototype.name())
                     .unwrap_or_else(|| panic!("Function '{}' is expected to exist.", fn_prototype))
-                    .fn_ptr;
-                *dispatch_ptr = fn_ptr;
-            }
+                    .fn_ptr
+            })
+            .collect::<Vec<_>>();
+
+        let dispatch_ptrs = self.info.dispatch_table.ptrs_mut();
+        for (idx, fn_ptr) in self.linked_fns.iter().zip(fn_ptrs) {
+            dispatch_ptrs[*idx] = fn_ptr;
         }
     }
 
-    /// Swaps the assembly's shared library and its information for the library at `library_path`.
-    pub fn swap(
-        &mut self,
-        library_path: &Path,
-        runtime_dispatch_table: &mut DispatchTable,
-    ) -> Result<(), failure::Error> {
-        let mut new_assembly =
-            Assembly::load(library_path, self.allocator.clone(), runtime_dispatch_table)?;
+    /// Returns the prototypes of the functions that the runtime filled in.
+    pub fn linked_fns(&self) -> impl Iterator<Item = &abi::FunctionPrototype> {
+        let prototypes = self.info.dispatch_table.prototypes();
+        self.linked_fns.iter().map(move |idx| &prototypes[*idx])
+    }
 
-        let old_types: Vec<UnsafeTypeInfo> = self
-            .info
-            .symbols
-            .types()
-            .iter()
-            .map(|ty| {
-                // Safety: `ty` is a shared reference, so is guaranteed to not be `ptr::null()`.
-                UnsafeTypeInfo::new(unsafe {
-                    NonNull::new_unchecked(*ty as *const abi::TypeInfo as *mut _)
-                })
-            })
-            .collect();
+    /// Returns the prototypes of the functions that the runtime filled in, which are provided by
+    /// `dependency`.
+    pub fn linked_fns_from<'a>(
+        &'a self,
+        dependency: &'a Assembly,
+    ) -> impl Iterator<Item = &'a abi::FunctionPrototype> {
+        let dependency_functions = dependency.info().symbols.functions();
+        self.linked_fns().filter(move |fn_prototype| {
+            dependency_functions
+                .iter()
+                .any(|f| f.prototype.name() == fn_prototype.name())
+        })
+    }
 
-        let new_types: Vec<UnsafeTypeInfo> = new_assembly
-            .info
-            .symbols
-            .types()
-            .iter()
-            .map(|ty| {
-                // Safety: `ty` is a shared reference, so is guaranteed to not be `ptr::null()`.
-                UnsafeTypeInfo::new(unsafe {
-                    NonNull::new_unchecked(*ty as *const abi::TypeInfo as *mut _)
-                })
-            })
+    /// Prepares swapping the assembly's shared library and its information for `new_assembly`, by
+    /// computing how the objects of types whose layout changed are mapped to their new layout and
+    /// migrated using the user-defined `migrations`. No memory is modified until the swap is
+    /// committed with [`commit_swap`], so a failure leaves the assembly untouched. Returns an
+    /// error if the migration policy rejects a lossy conversion of a field.
+    ///
+    /// [`commit_swap`]: #method.commit_swap
+    pub fn prepare_swap(
+        &self,
+        new_assembly: Assembly,
+        migrations: &Migrations,
+    ) -> Result<PendingSwap, failure::Error> {
+        let old_types = self.type_infos();
+        let new_types = new_assembly.type_infos();
+
+        let mut mapping = Mapping::new(&old_types, &new_types);
+        let lossy_conversions = migrations.apply(&mut mapping)?;
+
+        Ok(PendingSwap {
+            new_assembly,
+            mapping,
+            lossy_conversions,
+        })
+    }
+
+    /// Swaps the assembly's shared library and its information for the new assembly of the
+    /// `pending` swap, and maps the objects of types whose layout changed to their new layout.
+    /// Returns a summary of the changes to the memory of the assembly.
+    ///
+    /// The functions of the new assembly have to be inserted into the runtime's dispatch table and
+    /// the assembly has to be relinked afterwards.
+    pub fn commit_swap(&mut self, pending: PendingSwap) -> SwapSummary {
+        let PendingSwap {
+            mut new_assembly,
+            mapping,
+            lossy_conversions,
+        } = pending;
+
+        let mut migrated_types: Vec<String> = mapping
+            .conversions
+            .values()
+            .map(|conversion| conversion.new_ty.name().to_string())
             .collect();
+        migrated_types.sort();
 
-        let mapping = Mapping::new(&old_types, &new_types);
         let deleted_objects = self.allocator.map_memory(mapping);
 
-        // Remove the old assembly's functions
-        for function in self.info.symbols.functions() {
-            runtime_dispatch_table.remove_fn(function.prototype.name());
-        }
-
-        new_assembly.link(runtime_dispatch_table);
-
         // Retain all existing legacy libs
         new_assembly.legacy_libs.append(&mut self.legacy_libs);
 
@@ -198,10 +321,59 @@ impl Assembly {
 
         if !deleted_objects.is_empty() {
             // Retain the previous assembly
-            self.legacy_libs.push(old_assembly.into_library());
+            self.legacy_libs.push(old_assembly.into_legacy_library());
         }
 
-        Ok(())
+        SwapSummary {
+            migrated_types,
+            deleted_objects: deleted_objects.len(),
+            lossy_conversions,
+        }
+    }
+
+    /// Unloads the assembly, deleting all objects of its types. Returns the number of deleted
+    /// objects and the libraries that have to be retained, because deleted objects still refer to
+    /// their type information.
+    pub(crate) fn unload(mut self) -> (usize, Vec<LegacyLibrary>) {
+        let old_types = self.type_infos();
+        let deleted_objects = self.allocator.map_memory(Mapping::new(&old_types, &[]));
+
+        let mut libraries = std::mem::replace(&mut self.legacy_libs, Vec::new());
+        if !deleted_objects.is_empty() {
+            libraries.push(self.into_legacy_library());
+        }
+        (deleted_objects.len(), libraries)
+    }
+
+    /// Releases the libraries of previous versions of the assembly that are no longer referred to
+    /// by any object. Returns the number of released libraries.
+    pub fn prune_legacy_libs(&mut self) -> usize {
+        let gc = &self.allocator;
+        let num_legacy_libs = self.legacy_libs.len();
+        self.legacy_libs
+            .retain(|legacy_lib| legacy_lib.is_referenced(gc));
+        num_legacy_libs - self.legacy_libs.len()
+    }
+
+    /// Returns the number of libraries of previous versions of the assembly that are retained,
+    /// because objects still refer to their type information.
+    pub fn num_legacy_libs(&self) -> usize {
+        self.legacy_libs.len()
+    }
+
+    /// Returns the information of the assembly's types.
+    fn type_infos(&self) -> Vec<UnsafeTypeInfo> {
+        self.info
+            .symbols
+            .types()
+            .iter()
+            .map(|ty| {
+                // Safety: `ty` is a shared reference, so is guaranteed to not be `ptr::null()`.
+                UnsafeTypeInfo::new(unsafe {
+                    NonNull::new_unchecked(*ty as *const abi::TypeInfo as *mut _)
+                })
+            })
+            .collect()
     }
 
     /// Returns the assembly's information.
@@ -218,4 +390,18 @@ impl Assembly {
     pub fn into_library(self) -> TempLibrary {
         self.library
     }
+
+    /// Converts the `Assembly` into a `LegacyLibrary`, which retains the library for as long as
+    /// objects refer to the assembly's types.
+    fn into_legacy_library(self) -> LegacyLibrary {
+        let types = self
+            .type_infos()
+            .into_iter()
+            .map(UnsafeTypeInfo::into_inner)
+            .collect();
+        LegacyLibrary {
+            _library: self.into_library(),
+            types,
+        }
+    }
 }
@@ -1,4 +1,5 @@
 use std::fs;
+use std::io::Write;
 use std::path::Path;
 
 use failure::Error;
@@ -26,6 +27,18 @@ impl TempLibrary {
         })
     }
 
+    /// Loads the library from its contents in memory.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        let mut tmp_file = tempfile::NamedTempFile::new()?;
+        tmp_file.write_all(bytes)?;
+        let tmp_path = tmp_file.into_temp_path();
+        let library = Library::new(&tmp_path)?;
+        Ok(TempLibrary {
+            _tmp_path: tmp_path,
+            library,
+        })
+    }
+
     /// Returns the loaded library
     pub fn library(&self) -> &Library {
         &self.library
@@ -0,0 +1,96 @@
+use failure::Error;
+use notify::{DebouncedEvent, RecommendedWatcher, RecursiveMode, Watcher};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::mpsc::{channel, Receiver},
+    time::Duration,
+};
+
+/// A source of changes to the shared libraries of assemblies, which the [`Runtime`] polls for
+/// assemblies to reload when it is updated.
+///
+/// Hosts can implement this trait to request reloads from their own asset pipelines, instead of
+/// relying on the file system.
+///
+/// [`Runtime`]: struct.Runtime.html
+pub trait ChangeSource: Send {
+    /// Notifies the source that the library at `library_path` was loaded, so changes to it should
+    /// be reported.
+    fn watch(&mut self, _library_path: &Path) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Notifies the source that the library at `library_path`, which was previously passed to
+    /// [`watch`], is no longer loaded, so changes to it no longer have to be reported.
+    ///
+    /// [`watch`]: #method.watch
+    fn unwatch(&mut self, _library_path: &Path) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Returns the paths of the libraries that changed since the last time this function was
+    /// called.
+    fn poll_changes(&mut self) -> Vec<PathBuf>;
+}
+
+/// A [`ChangeSource`] that watches the directories of loaded libraries on the file system.
+///
+/// [`ChangeSource`]: trait.ChangeSource.html
+pub struct FileWatcher {
+    watcher: RecommendedWatcher,
+    watcher_rx: Receiver<DebouncedEvent>,
+    /// The number of watched libraries in each watched directory
+    watched_dirs: HashMap<PathBuf, usize>,
+}
+
+impl FileWatcher {
+    /// Constructs a new `FileWatcher` that collects and deduplicates file system events for the
+    /// duration of `delay`, before reporting them.
+    pub fn new(delay: Duration) -> Result<Self, Error> {
+        let (tx, rx) = channel();
+        let watcher: RecommendedWatcher = Watcher::new(tx, delay)?;
+        Ok(FileWatcher {
+            watcher,
+            watcher_rx: rx,
+            watched_dirs: HashMap::new(),
+        })
+    }
+}
+
+impl ChangeSource for FileWatcher {
+    fn watch(&mut self, library_path: &Path) -> Result<(), Error> {
+        let dir = library_path.parent().unwrap();
+        if !self.watched_dirs.contains_key(dir) {
+            self.watcher.watch(dir, RecursiveMode::NonRecursive)?;
+        }
+        *self.watched_dirs.entry(dir.to_path_buf()).or_insert(0) += 1;
+        Ok(())
+    }
+
+    fn unwatch(&mut self, library_path: &Path) -> Result<(), Error> {
+        // A directory remains watched as long as it contains other watched libraries
+        let dir = library_path.parent().unwrap();
+        match self.watched_dirs.get_mut(dir) {
+            Some(count) if *count > 1 => *count -= 1,
+            Some(_) => {
+                self.watched_dirs.remove(dir);
+                self.watcher.unwatch(dir)?;
+            }
+            None => (),
+        }
+        Ok(())
+    }
+
+    fn poll_changes(&mut self) -> Vec<PathBuf> {
+        let mut paths = Vec::new();
+        while let Ok(event) = self.watcher_rx.try_recv() {
+            use notify::DebouncedEvent::*;
+            match event {
+                Write(path) | Rename(_, path) | Create(path) => paths.push(path),
+                _ => {}
+            }
+        }
+        paths
+    }
+}
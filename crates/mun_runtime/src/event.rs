@@ -0,0 +1,91 @@
+use crate::LossyConversion;
+use failure::Error;
+use std::path::PathBuf;
+
+/// An event that is emitted by [`Runtime::update`] when it (tries to) hot reload an assembly, or by
+/// [`Runtime::remove_assembly`] when it unloads an assembly.
+///
+/// [`Runtime::update`]: struct.Runtime.html#method.update
+/// [`Runtime::remove_assembly`]: struct.Runtime.html#method.remove_assembly
+#[derive(Debug)]
+pub enum ReloadEvent {
+    /// The assembly at `path` was successfully reloaded. This is emitted after all other events of
+    /// the reload.
+    AssemblyReloaded {
+        /// The path of the assembly's library
+        path: PathBuf,
+    },
+    /// The assembly at `path` could not be reloaded. The previous version of the assembly remains
+    /// loaded.
+    ReloadFailed {
+        /// The path of the assembly's library
+        path: PathBuf,
+        /// The reason why reloading failed
+        error: Error,
+    },
+    /// The assembly at `path` was unloaded. This is emitted after all other events of the unload.
+    AssemblyUnloaded {
+        /// The path of the assembly's library
+        path: PathBuf,
+    },
+    /// The layout of `types` changed, so all of their objects were migrated to the new layout.
+    TypesMigrated {
+        /// The path of the assembly's library
+        path: PathBuf,
+        /// The names of the migrated types
+        types: Vec<String>,
+    },
+    /// The types of `count` objects no longer exist in the reloaded or unloaded assembly. The objects can no
+    /// longer be accessed from Mun code.
+    ObjectsDeleted {
+        /// The path of the assembly's library
+        path: PathBuf,
+        /// The number of deleted objects
+        count: usize,
+    },
+    /// The value of a field could not be migrated to its new type without loss of information.
+    /// The field was converted or reset according to the migration policy.
+    LossyConversion {
+        /// The path of the assembly's library
+        path: PathBuf,
+        /// The converted field
+        conversion: LossyConversion,
+    },
+}
+
+/// Identifies a subscription to [`ReloadEvent`]s, which can be used to unsubscribe.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SubscriptionId(usize);
+
+type Subscriber = Box<dyn FnMut(&ReloadEvent) + Send>;
+
+/// The subscribers to [`ReloadEvent`]s of a runtime.
+#[derive(Default)]
+pub(crate) struct Subscribers {
+    subscribers: Vec<(SubscriptionId, Subscriber)>,
+    next_id: usize,
+}
+
+impl Subscribers {
+    /// Adds a `subscriber` that is called for every emitted event.
+    pub fn subscribe(&mut self, subscriber: Subscriber) -> SubscriptionId {
+        let id = SubscriptionId(self.next_id);
+        self.next_id += 1;
+        self.subscribers.push((id, subscriber));
+        id
+    }
+
+    /// Removes the subscriber with the specified `id`. Returns whether the subscriber existed.
+    pub fn unsubscribe(&mut self, id: SubscriptionId) -> bool {
+        let len = self.subscribers.len();
+        self.subscribers.retain(|(other, _)| *other != id);
+        self.subscribers.len() != len
+    }
+
+    /// Emits the `event` to all subscribers, in the order in which they subscribed.
+    pub fn emit(&mut self, event: ReloadEvent) {
+        for (_, subscriber) in self.subscribers.iter_mut() {
+            subscriber(&event);
+        }
+    }
+}
@@ -5,51 +5,67 @@
 #![warn(missing_docs)]
 
 mod assembly;
+mod change_source;
+mod event;
 #[macro_use]
 mod macros;
 #[macro_use]
 mod garbage_collector;
 mod marshal;
+mod migration;
 mod reflection;
 mod struct_ref;
+mod value;
 
+use assembly::LegacyLibrary;
+use event::Subscribers;
 use failure::Error;
 use garbage_collector::GarbageCollector;
 use memory::gc::{self, GcRuntime};
-use notify::{DebouncedEvent, RecommendedWatcher, RecursiveMode, Watcher};
+use parking_lot::{Mutex, RwLock};
 use rustc_hash::FxHashMap;
 use std::{
-    cell::RefCell,
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     ffi, io, mem,
     path::{Path, PathBuf},
     ptr::NonNull,
-    rc::Rc,
     string::ToString,
-    sync::{
-        mpsc::{channel, Receiver},
-        Arc,
-    },
+    sync::Arc,
     time::Duration,
 };
 
 pub use crate::{
-    assembly::Assembly,
+    assembly::{Assembly, PendingSwap, SwapSummary},
+    change_source::{ChangeSource, FileWatcher},
+    event::{ReloadEvent, SubscriptionId},
     garbage_collector::UnsafeTypeInfo,
     marshal::Marshal,
+    migration::{FieldRename, LossyConversion, Migration, Migrations, StructView},
     reflection::{ArgumentReflection, ReturnTypeReflection},
     struct_ref::StructRef,
+    value::Value,
 };
 pub use abi::IntoFunctionDefinition;
+pub use memory::mapping::{IntegerNarrowing, MigrationPolicy};
 
 /// Options for the construction of a [`Runtime`].
 pub struct RuntimeOptions {
     /// Path to the entry point library
     pub library_path: PathBuf,
+    /// The contents of the entry point library. If specified, the library is loaded from memory
+    /// and `library_path` only serves as its name; it is not watched for changes.
+    pub library_bytes: Option<Vec<u8>>,
     /// Delay during which filesystem events are collected, deduplicated, and after which emitted.
     pub delay: Duration,
+    /// Whether to watch the loaded libraries on the file system and reload them when they change
+    pub watch_files: bool,
+    /// Additional sources of changes to the loaded libraries
+    pub change_sources: Vec<Box<dyn ChangeSource>>,
     /// Custom user injected functions
     pub user_functions: Vec<(abi::FunctionDefinition, abi::FunctionDefinitionStorage)>,
+    /// Migrations of struct types, which are applied when their layout changes during hot
+    /// reloading
+    pub migrations: Migrations,
 }
 
 /// A builder for the [`Runtime`].
@@ -63,18 +79,45 @@ impl RuntimeBuilder {
         Self {
             options: RuntimeOptions {
                 library_path: library_path.into(),
+                library_bytes: None,
                 delay: Duration::from_millis(10),
+                watch_files: true,
+                change_sources: Vec::new(),
                 user_functions: Default::default(),
+                migrations: Default::default(),
             },
         }
     }
 
+    /// Constructs a new `RuntimeBuilder` for a shared library that is loaded from memory, e.g. the
+    /// output of `mun_compiler::compile_to_memory`. The library is identified by `name`, relative
+    /// to which the paths of its dependencies are resolved.
+    pub fn from_bytes<P: Into<PathBuf>>(name: P, bytes: Vec<u8>) -> Self {
+        let mut builder = Self::new(name);
+        builder.options.library_bytes = Some(bytes);
+        builder
+    }
+
     /// Sets the `delay`.
     pub fn set_delay(mut self, delay: Duration) -> Self {
         self.options.delay = delay;
         self
     }
 
+    /// Sets whether the loaded libraries are watched on the file system. If disabled, assemblies
+    /// are only reloaded when requested by a [`ChangeSource`] or through
+    /// [`Runtime::reload_assembly`].
+    pub fn set_watch_files(mut self, watch_files: bool) -> Self {
+        self.options.watch_files = watch_files;
+        self
+    }
+
+    /// Adds a `source` of changes to the loaded libraries, which is polled by [`Runtime::update`].
+    pub fn add_change_source<S: ChangeSource + 'static>(mut self, source: S) -> Self {
+        self.options.change_sources.push(Box::new(source));
+        self
+    }
+
     /// Adds a custom user function to the dispatch table.
     pub fn insert_fn<S: AsRef<str>, F: abi::IntoFunctionDefinition>(
         mut self,
@@ -85,9 +128,42 @@ impl RuntimeBuilder {
         self
     }
 
+    /// Adds a `migration` for objects of the struct type `type_name`. When the layout of the type
+    /// changes during hot reloading, the migration is called for every object after its fields
+    /// have been mapped to the new layout.
+    pub fn migrate<S: Into<String>, F: Fn(&StructView, &mut StructView) + Send + Sync + 'static>(
+        mut self,
+        type_name: S,
+        migration: F,
+    ) -> Self {
+        self.options
+            .migrations
+            .insert(type_name, Arc::new(migration));
+        self
+    }
+
+    /// Declares that a field of a struct type was renamed, so its value is retained when hot
+    /// reloading the renamed field.
+    pub fn rename_field(mut self, rename: FieldRename) -> Self {
+        self.options.migrations.rename_field(rename);
+        self
+    }
+
+    /// Sets the `policy` for fields of which the value cannot be migrated to their new type without
+    /// loss of information, e.g. when a field changes from `f64` to `f32`.
+    pub fn set_migration_policy(mut self, policy: MigrationPolicy) -> Self {
+        self.options.migrations.set_policy(policy);
+        self
+    }
+
     /// Spawns a [`Runtime`] with the builder's options.
-    pub fn spawn(self) -> Result<Rc<RefCell<Runtime>>, Error> {
-        Runtime::new(self.options).map(|runtime| Rc::new(RefCell::new(runtime)))
+    ///
+    /// The returned handle can be shared between threads. Functions are invoked while holding a
+    /// shared lock on the runtime, whereas [`Runtime::update`] and [`Runtime::gc_collect`] require
+    /// an exclusive lock. Mun functions do not synchronize their accesses to objects, so objects
+    /// that are shared between threads should only be modified through [`StructRef`]s.
+    pub fn spawn(self) -> Result<Arc<RwLock<Runtime>>, Error> {
+        Runtime::new(self.options).map(|runtime| Arc::new(RwLock::new(runtime)))
     }
 }
 
@@ -96,7 +172,7 @@ type Dependency<T> = (T, DependencyCounter);
 type DependencyMap<T> = FxHashMap<String, Dependency<T>>;
 
 /// A runtime dispatch table that maps full paths to function and struct information.
-#[derive(Default)]
+#[derive(Clone, Default)]
 pub struct DispatchTable {
     functions: FxHashMap<String, abi::FunctionDefinition>,
     fn_dependencies: FxHashMap<String, DependencyMap<abi::FunctionPrototype>>,
@@ -163,10 +239,18 @@ impl DispatchTable {
 /// A runtime for the Mun language.
 pub struct Runtime {
     assemblies: HashMap<PathBuf, Assembly>,
+    // Libraries of unloaded assemblies that are still referred to by deleted objects
+    legacy_libs: Vec<LegacyLibrary>,
     dispatch_table: DispatchTable,
-    watcher: RecommendedWatcher,
-    watcher_rx: Receiver<DebouncedEvent>,
+    // The change sources and subscribers are only required to be `Send`. They are only accessed
+    // through a mutable reference to the `Runtime`, so the mutexes are never contended.
+    change_sources: Mutex<Vec<Box<dyn ChangeSource>>>,
     gc: Arc<GarbageCollector>,
+    subscribers: Mutex<Subscribers>,
+    // Serializes writes to the fields of objects through `StructRef`s with other accesses to them,
+    // as `StructRef`s to the same object can be used from multiple threads.
+    field_lock: RwLock<()>,
+    migrations: Migrations,
     _user_functions: Vec<abi::FunctionDefinitionStorage>,
 }
 
@@ -200,11 +284,9 @@ extern "C" fn new(
 
 impl Runtime {
     /// Constructs a new `Runtime` that loads the library at `library_path` and its
-    /// dependencies. The `Runtime` contains a file watcher that is triggered with an interval
-    /// of `dur`.
+    /// dependencies. Unless disabled, the `Runtime` contains a file watcher that is triggered with
+    /// an interval of `delay`.
     pub fn new(mut options: RuntimeOptions) -> Result<Runtime, Error> {
-        let (tx, rx) = channel();
-
         let mut dispatch_table = DispatchTable::default();
 
         // Add internal functions
@@ -219,41 +301,223 @@ impl Runtime {
             storages.push(storage)
         }
 
-        let watcher: RecommendedWatcher = Watcher::new(tx, options.delay)?;
+        let mut change_sources = options.change_sources;
+        if options.watch_files {
+            change_sources.insert(0, Box::new(FileWatcher::new(options.delay)?));
+        }
+
         let mut runtime = Runtime {
             assemblies: HashMap::new(),
+            legacy_libs: Vec::new(),
             dispatch_table,
-            watcher,
-            watcher_rx: rx,
+            change_sources: Mutex::new(change_sources),
             gc: Arc::new(self::garbage_collector::GarbageCollector::default()),
+            subscribers: Mutex::new(Subscribers::default()),
+            field_lock: RwLock::new(()),
+            migrations: options.migrations,
             _user_functions: storages,
         };
 
-        runtime.add_assembly(&options.library_path)?;
+        match options.library_bytes {
+            Some(bytes) => runtime.add_assembly_from_bytes(&options.library_path, &bytes)?,
+            None => runtime.load_assembly(&options.library_path)?,
+        }
         Ok(runtime)
     }
 
-    /// Adds an assembly corresponding to the library at `library_path`.
-    fn add_assembly(&mut self, library_path: &Path) -> Result<(), Error> {
+    /// Adds an assembly corresponding to the library at `library_path`, after loading the
+    /// assemblies that it depends on.
+    ///
+    /// Returns an error if an assembly was already loaded from `library_path` or if the assembly
+    /// cannot be linked. In that case, the dependencies that were loaded for the assembly are
+    /// unloaded again and the runtime is left unchanged.
+    pub fn add_assembly<P: AsRef<Path>>(&mut self, library_path: P) -> Result<(), Error> {
+        let dispatch_table = self.dispatch_table.clone();
+        let loaded_assemblies: HashSet<PathBuf> = self.assemblies.keys().cloned().collect();
+
+        let result = self.load_assembly(library_path.as_ref());
+        if result.is_err() {
+            // Newly loaded assemblies have not been called yet, so no objects refer to their types
+            self.dispatch_table = dispatch_table;
+            let new_assemblies = self
+                .assemblies
+                .keys()
+                .filter(|path| !loaded_assemblies.contains(*path))
+                .cloned()
+                .collect::<Vec<_>>();
+            for path in new_assemblies {
+                self.assemblies.remove(&path);
+                self.unwatch(&path);
+            }
+        }
+        result
+    }
+
+    /// Loads the assembly corresponding to the library at `library_path` and its dependencies.
+    /// On failure, the runtime might be left with partially loaded dependencies.
+    fn load_assembly(&mut self, library_path: &Path) -> Result<(), Error> {
         let library_path = library_path.canonicalize()?;
-        if self.assemblies.contains_key(&library_path) {
+        self.ensure_unique(&library_path)?;
+
+        let mut assembly = Assembly::open(&library_path, self.gc.clone())?;
+        self.link_assembly(&mut assembly)?;
+
+        // If a change source fails to watch the library, the sources that already watch it stop
+        // watching it again
+        let change_sources = self.change_sources.get_mut();
+        let mut watched = 0;
+        let mut result = Ok(());
+        for source in change_sources.iter_mut() {
+            result = source.watch(&library_path);
+            if result.is_err() {
+                break;
+            }
+            watched += 1;
+        }
+        if result.is_err() {
+            for source in change_sources.iter_mut().take(watched) {
+                let _ = source.unwatch(&library_path);
+            }
+        }
+        result?;
+
+        self.assemblies.insert(library_path, assembly);
+        Ok(())
+    }
+
+    /// Notifies all change sources that the library at `library_path` is no longer loaded. Errors
+    /// are ignored, as the library is unloaded regardless.
+    fn unwatch(&mut self, library_path: &Path) {
+        for source in self.change_sources.get_mut().iter_mut() {
+            let _ = source.unwatch(library_path);
+        }
+    }
+
+    /// Unloads the assembly corresponding to the library at `library_path`. Its functions are
+    /// removed from the dispatch table and all objects of its types are deleted. Existing
+    /// references to deleted objects remain valid, but the objects can no longer be used by Mun
+    /// code.
+    ///
+    /// Returns an error if the assembly is not loaded, or if other assemblies still depend on its
+    /// functions. Assemblies that the unloaded assembly depends on remain loaded.
+    pub fn remove_assembly<P: AsRef<Path>>(&mut self, library_path: P) -> Result<(), Error> {
+        let library_path = library_path.as_ref();
+        let library_path = library_path
+            .canonicalize()
+            .unwrap_or_else(|_| library_path.to_path_buf());
+        let assembly = self.assemblies.get(&library_path).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!(
+                    "Failed to unload: assembly '{}' is not loaded.",
+                    library_path.display()
+                ),
+            )
+        })?;
+
+        if let Some(fn_name) = self
+            .dispatch_table
+            .fn_dependencies
+            .get(library_path.to_string_lossy().as_ref())
+            .and_then(|dependencies| dependencies.keys().min())
+        {
             return Err(io::Error::new(
-                io::ErrorKind::AlreadyExists,
-                "An assembly with the same name already exists.",
+                io::ErrorKind::Other,
+                format!(
+                    "Failed to unload: function `{}` of assembly '{}' is used by other assemblies.",
+                    fn_name,
+                    library_path.display()
+                ),
             )
             .into());
         }
 
-        let mut assembly = Assembly::load(&library_path, self.gc.clone(), &self.dispatch_table)?;
-        for dependency in assembly.info().dependencies() {
-            self.add_assembly(Path::new(dependency))?;
+        for function in assembly.info().symbols.functions() {
+            self.dispatch_table.remove_fn(function.prototype.name());
         }
-        assembly.link(&mut self.dispatch_table);
 
-        self.watcher
-            .watch(library_path.parent().unwrap(), RecursiveMode::NonRecursive)?;
+        // Release the functions that the assembly uses from its dependencies
+        for dependency in assembly.dependencies() {
+            let dependency = dependency
+                .canonicalize()
+                .ok()
+                .and_then(|path| self.assemblies.get(&path));
+            if let Some(dependency) = dependency {
+                let dependency_path = dependency.library_path().to_string_lossy();
+                for fn_prototype in assembly.linked_fns_from(dependency) {
+                    self.dispatch_table
+                        .remove_fn_dependency(dependency_path.as_ref(), fn_prototype.name());
+                }
+            }
+        }
+        self.dispatch_table
+            .fn_dependencies
+            .remove(library_path.to_string_lossy().as_ref());
+
+        let assembly = self.assemblies.remove(&library_path).unwrap();
+        let (deleted_objects, libraries) = assembly.unload();
+        self.legacy_libs.extend(libraries);
+
+        if deleted_objects > 0 {
+            self.subscribers
+                .get_mut()
+                .emit(ReloadEvent::ObjectsDeleted {
+                    path: library_path.clone(),
+                    count: deleted_objects,
+                });
+        }
+        self.subscribers
+            .get_mut()
+            .emit(ReloadEvent::AssemblyUnloaded { path: library_path });
+        Ok(())
+    }
+
+    /// Adds an assembly from the contents of its shared library. The assembly is identified by
+    /// `name` and is not hot reloaded.
+    fn add_assembly_from_bytes(&mut self, name: &Path, bytes: &[u8]) -> Result<(), Error> {
+        self.ensure_unique(name)?;
 
-        self.assemblies.insert(library_path, assembly);
+        let mut assembly = Assembly::open_bytes(name, bytes, self.gc.clone())?;
+        self.link_assembly(&mut assembly)?;
+
+        self.assemblies.insert(name.to_path_buf(), assembly);
+        Ok(())
+    }
+
+    /// Returns an error if an assembly was already loaded from `library_path`.
+    fn ensure_unique(&self, library_path: &Path) -> Result<(), io::Error> {
+        if self.assemblies.contains_key(library_path) {
+            Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                "An assembly with the same name already exists.",
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Loads the dependencies of the assembly and links the assembly with the dispatch table.
+    fn link_assembly(&mut self, assembly: &mut Assembly) -> Result<(), Error> {
+        // Dependencies have to be loaded before the assembly can be linked
+        for dependency in assembly.dependencies().collect::<Vec<_>>() {
+            let dependency = dependency.canonicalize()?;
+            if !self.assemblies.contains_key(&dependency) {
+                self.load_assembly(&dependency)?;
+            }
+
+            // Record which functions this assembly uses from the dependency, so the dependency
+            // cannot be reloaded without them.
+            let dependency_path = dependency.to_string_lossy();
+            for fn_prototype in assembly.linked_fns_from(&self.assemblies[&dependency]) {
+                self.dispatch_table.add_fn_dependency(
+                    dependency_path.as_ref(),
+                    fn_prototype.name(),
+                    fn_prototype.clone(),
+                );
+            }
+        }
+        assembly.ensure_linkable(&self.dispatch_table)?;
+        assembly.link(&mut self.dispatch_table);
         Ok(())
     }
 
@@ -262,33 +526,250 @@ impl Runtime {
         self.dispatch_table.get_fn(function_name)
     }
 
-    /// Updates the state of the runtime. This includes checking for file changes, and reloading
-    /// compiled assemblies.
+    /// Updates the state of the runtime. This includes polling the change sources for changed
+    /// libraries, and reloading compiled assemblies. Returns `true` if assemblies were reloaded.
+    ///
+    /// All assemblies that changed since the last update are reloaded together, so assemblies that
+    /// depend on each other can change at the same time. If any of them fails to reload, none of
+    /// them are and the previous versions remain loaded.
+    ///
+    /// The outcome of every attempt to reload an assembly is reported to the subscribers of
+    /// [`ReloadEvent`]s.
     pub fn update(&mut self) -> bool {
-        while let Ok(event) = self.watcher_rx.try_recv() {
-            use notify::DebouncedEvent::*;
-            match event {
-                Write(ref path) | Rename(_, ref path) | Create(ref path) => {
-                    if let Some(assembly) = self.assemblies.get_mut(path) {
-                        if let Err(e) = assembly.swap(path, &mut self.dispatch_table) {
-                            println!(
-                                "An error occured while reloading assembly '{}': {:?}",
-                                path.to_string_lossy(),
-                                e
-                            );
-                        } else {
-                            println!(
-                                "Succesfully reloaded assembly: '{}'",
-                                path.to_string_lossy()
-                            );
-                            return true;
-                        }
+        let mut paths = Vec::new();
+        for source in self.change_sources.get_mut().iter_mut() {
+            for path in source.poll_changes() {
+                let path = path.canonicalize().unwrap_or(path);
+                if self.assemblies.contains_key(&path) && !paths.contains(&path) {
+                    paths.push(path);
+                }
+            }
+        }
+
+        if paths.is_empty() {
+            false
+        } else {
+            self.reload_assemblies(&paths)
+        }
+    }
+
+    /// Reloads the assembly corresponding to the library at `library_path`, irrespective of whether
+    /// a change to the library was detected.
+    ///
+    /// Unlike [`update`], which reports failures to the subscribers of [`ReloadEvent`]s, an error
+    /// is returned if the assembly cannot be reloaded. The previous version of the assembly then
+    /// remains loaded.
+    ///
+    /// [`update`]: #method.update
+    pub fn reload_assembly<P: AsRef<Path>>(&mut self, library_path: P) -> Result<(), Error> {
+        let library_path = library_path.as_ref().canonicalize()?;
+        if !self.assemblies.contains_key(&library_path) {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!(
+                    "Failed to reload: assembly '{}' is not loaded.",
+                    library_path.display()
+                ),
+            )
+            .into());
+        }
+
+        let paths = [library_path];
+        let summaries = self
+            .try_reload_assemblies(&paths)
+            .map_err(|(_, error)| error)?;
+        for (path, summary) in paths.iter().zip(summaries) {
+            self.emit_reload_events(path, summary);
+        }
+        Ok(())
+    }
+
+    /// Reloads the assemblies at `paths` in a single transaction and emits the corresponding
+    /// [`ReloadEvent`]s. Returns `true` if the assemblies were reloaded.
+    fn reload_assemblies(&mut self, paths: &[PathBuf]) -> bool {
+        match self.try_reload_assemblies(paths) {
+            Ok(summaries) => {
+                for (path, summary) in paths.iter().zip(summaries) {
+                    self.emit_reload_events(path, summary);
+                }
+                true
+            }
+            Err((failed_path, error)) => {
+                let mut error = Some(error);
+                for path in paths {
+                    let error = if *path == failed_path {
+                        error.take()
+                    } else {
+                        None
+                    };
+                    let error = error.unwrap_or_else(|| {
+                        failure::err_msg(format!(
+                            "The reload was rolled back, because reloading assembly '{}' failed.",
+                            failed_path.display()
+                        ))
+                    });
+                    self.subscribers.get_mut().emit(ReloadEvent::ReloadFailed {
+                        path: path.clone(),
+                        error,
+                    });
+                }
+                false
+            }
+        }
+    }
+
+    /// Loads new versions of the assemblies at `paths`, validates them together and only then
+    /// swaps all of them. On failure, no assembly is modified and the path of the assembly that
+    /// failed to reload is returned with the error.
+    fn try_reload_assemblies(
+        &mut self,
+        paths: &[PathBuf],
+    ) -> Result<Vec<SwapSummary>, (PathBuf, Error)> {
+        let mut new_assemblies = Vec::with_capacity(paths.len());
+        for path in paths {
+            let assembly =
+                Assembly::open(path, self.gc.clone()).map_err(|error| (path.clone(), error))?;
+            new_assemblies.push(assembly);
+        }
+
+        // The new assemblies might depend on each other, so they are validated against the
+        // dispatch table that results from reloading all of them.
+        let mut dispatch_table = self.dispatch_table.clone();
+        self.stage_reload(&mut dispatch_table, &new_assemblies);
+        for (path, assembly) in paths.iter().zip(new_assemblies.iter()) {
+            assembly
+                .ensure_linkable(&dispatch_table)
+                .map_err(|error| (path.clone(), error.into()))?;
+        }
+
+        // Compute the memory mappings of all assemblies before modifying any memory
+        let mut pending_swaps = Vec::with_capacity(paths.len());
+        for (path, new_assembly) in paths.iter().zip(new_assemblies) {
+            let pending_swap = self.assemblies[path]
+                .prepare_swap(new_assembly, &self.migrations)
+                .map_err(|error| (path.clone(), error))?;
+            pending_swaps.push(pending_swap);
+        }
+
+        // Nothing can fail from here on, so commit the reload
+        self.dispatch_table = dispatch_table;
+        let summaries = paths
+            .iter()
+            .zip(pending_swaps)
+            .map(|(path, pending_swap)| {
+                self.assemblies
+                    .get_mut(path)
+                    .unwrap()
+                    .commit_swap(pending_swap)
+            })
+            .collect();
+
+        // Other assemblies might call functions of the reloaded assemblies
+        for assembly in self.assemblies.values_mut() {
+            assembly.relink(&self.dispatch_table);
+        }
+        Ok(summaries)
+    }
+
+    /// Updates `dispatch_table` to replace the currently loaded versions of `new_assemblies` with
+    /// `new_assemblies`; both their functions and the functions they use from their dependencies.
+    fn stage_reload(&self, dispatch_table: &mut DispatchTable, new_assemblies: &[Assembly]) {
+        for new_assembly in new_assemblies {
+            let old_assembly = &self.assemblies[new_assembly.library_path()];
+            for function in old_assembly.info().symbols.functions() {
+                dispatch_table.remove_fn(function.prototype.name());
+            }
+            for dependency in old_assembly.dependencies() {
+                let dependency = dependency
+                    .canonicalize()
+                    .ok()
+                    .and_then(|path| self.assemblies.get(&path));
+                if let Some(dependency) = dependency {
+                    let dependency_path = dependency.library_path().to_string_lossy();
+                    for fn_prototype in old_assembly.linked_fns_from(dependency) {
+                        dispatch_table
+                            .remove_fn_dependency(dependency_path.as_ref(), fn_prototype.name());
                     }
                 }
-                _ => {}
             }
         }
-        false
+
+        // Dependencies resolve to their new version, if they are reloaded as well
+        let find_assembly = |path: &Path| {
+            new_assemblies
+                .iter()
+                .find(|assembly| assembly.library_path() == path)
+                .or_else(|| self.assemblies.get(path))
+        };
+        for new_assembly in new_assemblies {
+            for function in new_assembly.info().symbols.functions() {
+                dispatch_table.insert_fn(function.prototype.name(), function.clone());
+            }
+            for dependency in new_assembly.dependencies() {
+                let dependency = dependency
+                    .canonicalize()
+                    .ok()
+                    .and_then(|path| find_assembly(&path));
+                if let Some(dependency) = dependency {
+                    let dependency_path = dependency.library_path().to_string_lossy();
+                    for fn_prototype in new_assembly.linked_fns_from(dependency) {
+                        dispatch_table.add_fn_dependency(
+                            dependency_path.as_ref(),
+                            fn_prototype.name(),
+                            fn_prototype.clone(),
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    /// Emits the events that describe the successful reload of the assembly at `path`.
+    fn emit_reload_events(&mut self, path: &Path, summary: SwapSummary) {
+        if !summary.migrated_types.is_empty() {
+            self.subscribers.get_mut().emit(ReloadEvent::TypesMigrated {
+                path: path.to_path_buf(),
+                types: summary.migrated_types,
+            });
+        }
+        if summary.deleted_objects > 0 {
+            self.subscribers
+                .get_mut()
+                .emit(ReloadEvent::ObjectsDeleted {
+                    path: path.to_path_buf(),
+                    count: summary.deleted_objects,
+                });
+        }
+        for conversion in summary.lossy_conversions {
+            self.subscribers
+                .get_mut()
+                .emit(ReloadEvent::LossyConversion {
+                    path: path.to_path_buf(),
+                    conversion,
+                });
+        }
+        self.subscribers
+            .get_mut()
+            .emit(ReloadEvent::AssemblyReloaded {
+                path: path.to_path_buf(),
+            });
+    }
+
+    /// Subscribes `callback` to the [`ReloadEvent`]s that are emitted by [`Runtime::update`].
+    /// Returns an id that can be used to [`unsubscribe`].
+    ///
+    /// [`unsubscribe`]: #method.unsubscribe
+    pub fn subscribe<F: FnMut(&ReloadEvent) + Send + 'static>(
+        &mut self,
+        callback: F,
+    ) -> SubscriptionId {
+        self.subscribers.get_mut().subscribe(Box::new(callback))
+    }
+
+    /// Unsubscribes the callback with the specified `id`. Returns `false` if the subscription did
+    /// not exist.
+    pub fn unsubscribe(&mut self, id: SubscriptionId) -> bool {
+        self.subscribers.get_mut().unsubscribe(id)
     }
 
     /// Returns a shared reference to the runtime's garbage collector.
@@ -301,8 +782,46 @@ impl Runtime {
 
     /// Collects all memory that is no longer referenced by rooted objects. Returns `true` if memory
     /// was reclaimed, `false` otherwise. This behavior will likely change in the future.
-    pub fn gc_collect(&self) -> bool {
-        self.gc.collect()
+    ///
+    /// Mun functions hold references to objects that are not rooted, so collecting requires
+    /// exclusive access to the runtime; i.e. no functions can be in-flight.
+    ///
+    /// Libraries of previous versions of assemblies that are no longer referred to by any object
+    /// are released afterwards.
+    pub fn gc_collect(&mut self) -> bool {
+        let collected = self.gc.collect();
+
+        // Libraries of previous versions of assemblies can only become unreferenced when objects
+        // are collected
+        if collected {
+            self.prune_legacy_libs();
+        }
+        collected
+    }
+
+    /// Releases the libraries of previous versions of assemblies and of unloaded assemblies that
+    /// are no longer referred to by any object.
+    fn prune_legacy_libs(&mut self) {
+        for assembly in self.assemblies.values_mut() {
+            assembly.prune_legacy_libs();
+        }
+
+        let gc = &self.gc;
+        self.legacy_libs
+            .retain(|legacy_lib| legacy_lib.is_referenced(gc));
+    }
+
+    /// Returns the number of libraries that are retained after hot reloading or unloading
+    /// assemblies, because objects still refer to their type information. These libraries are
+    /// released by [`gc_collect`] once the objects have been collected.
+    ///
+    /// [`gc_collect`]: #method.gc_collect
+    pub fn num_legacy_libs(&self) -> usize {
+        self.assemblies
+            .values()
+            .map(Assembly::num_legacy_libs)
+            .sum::<usize>()
+            + self.legacy_libs.len()
     }
 
     /// Returns statistics about the garbage collector.
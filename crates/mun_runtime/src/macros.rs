@@ -18,7 +18,7 @@ macro_rules! invoke_fn_impl {
             /// the function invocation using the `Retriable` trait.
             pub struct $ErrName<'s, $($T: ArgumentReflection,)* Output: ReturnTypeReflection> {
                 msg: String,
-                runtime: std::rc::Rc<core::cell::RefCell<Runtime>>,
+                runtime: std::sync::Arc<parking_lot::RwLock<Runtime>>,
                 function_name: &'s str,
                 $($Arg: $T,)*
                 output: core::marker::PhantomData<Output>,
@@ -45,7 +45,7 @@ macro_rules! invoke_fn_impl {
             impl<'s, $($T: ArgumentReflection,)* Output: ReturnTypeReflection> $ErrName<'s, $($T,)* Output> {
                 /// Constructs a new invocation error.
                 #[allow(clippy::too_many_arguments)]
-                pub fn new(err_msg: String, runtime: std::rc::Rc<core::cell::RefCell<Runtime>>, function_name: &'s str, $($Arg: $T),*) -> Self {
+                pub fn new(err_msg: String, runtime: std::sync::Arc<parking_lot::RwLock<Runtime>>, function_name: &'s str, $($Arg: $T),*) -> Self {
                     Self {
                         msg: err_msg,
                         runtime,
@@ -64,7 +64,7 @@ macro_rules! invoke_fn_impl {
                         Ok(output) => Ok(output),
                         Err(err) => {
                             eprintln!("{}", err.msg);
-                            while !err.runtime.borrow_mut().update() {
+                            while !err.runtime.write().update() {
                                 // Wait until there has been an update that might fix the error
                             }
                             $crate::Runtime::$FnName(&err.runtime, err.function_name, $(err.$Arg,)*)
@@ -89,13 +89,17 @@ macro_rules! invoke_fn_impl {
                 ///
                 /// If an error occurs when invoking the method, an error message is logged. The
                 /// runtime continues looping until the cause of the error has been resolved.
+                ///
+                /// The runtime is locked for shared access for the duration of the invocation, so
+                /// functions can be invoked from multiple threads at the same time, while reloads
+                /// and garbage collection wait for in-flight invocations to finish.
                 #[allow(clippy::too_many_arguments, unused_assignments)]
                 pub fn $FnName<'s, $($T: ArgumentReflection,)* Output: ReturnTypeReflection>(
-                    runtime: &std::rc::Rc<core::cell::RefCell<Runtime>>,
+                    runtime: &std::sync::Arc<parking_lot::RwLock<Runtime>>,
                     function_name: &'s str,
                     $($Arg: $T,)*
                 ) -> core::result::Result<Output, $ErrName<'s, $($T,)* Output>> {
-                    let runtime_ref = runtime.borrow();
+                    let runtime_ref = runtime.read_recursive();
                     match runtime_ref
                         .get_function_definition(function_name)
                         .ok_or_else(|| format!("Failed to obtain function '{}'", function_name))
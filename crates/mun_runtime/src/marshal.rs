@@ -1,7 +1,7 @@
 use crate::Runtime;
-use std::cell::RefCell;
+use parking_lot::RwLock;
 use std::ptr::NonNull;
-use std::rc::Rc;
+use std::sync::Arc;
 
 /// Used to do value-to-value conversions that require runtime type information while consuming the
 /// input value.
@@ -9,12 +9,12 @@ use std::rc::Rc;
 /// If no `TypeInfo` is provided, the type is `()`.
 pub trait Marshal<T>: Sized {
     /// Marshals itself into a `T`.
-    fn marshal_value(self, runtime: Rc<RefCell<Runtime>>) -> T;
+    fn marshal_value(self, runtime: Arc<RwLock<Runtime>>) -> T;
 
     /// Marshals the value at memory location `ptr` into a `T`.
     fn marshal_from_ptr(
         ptr: NonNull<Self>,
-        runtime: Rc<RefCell<Runtime>>,
+        runtime: Arc<RwLock<Runtime>>,
         type_info: Option<&abi::TypeInfo>,
     ) -> T;
 
@@ -23,13 +23,13 @@ pub trait Marshal<T>: Sized {
 }
 
 impl<T> Marshal<T> for T {
-    fn marshal_value(self, _runtime: Rc<RefCell<Runtime>>) -> T {
+    fn marshal_value(self, _runtime: Arc<RwLock<Runtime>>) -> T {
         self
     }
 
     fn marshal_from_ptr(
         ptr: NonNull<Self>,
-        _runtime: Rc<RefCell<Runtime>>,
+        _runtime: Arc<RwLock<Runtime>>,
         _type_info: Option<&abi::TypeInfo>,
     ) -> T {
         // TODO: Avoid unsafe `read` fn by using adding `Clone` trait to T.
@@ -0,0 +1,261 @@
+use crate::{
+    garbage_collector::UnsafeTypeInfo, reflection::equals_return_type, ReturnTypeReflection,
+};
+use memory::{
+    cast,
+    mapping::{Action, Mapping, MigrationFn, MigrationPolicy},
+    TypeDesc, TypeFields,
+};
+use rustc_hash::FxHashMap;
+use std::{io, ptr::NonNull, sync::Arc};
+
+/// A user-defined function that migrates an object of a struct type of which the layout changed.
+/// It receives the old object and the new object, of which all fields have already been mapped.
+pub type Migration = dyn Fn(&StructView, &mut StructView) + Send + Sync;
+
+/// A view of the memory of a struct object during its migration.
+///
+/// Only fields of fundamental types can be accessed, as other objects might not have been migrated
+/// yet.
+pub struct StructView<'a> {
+    type_info: &'a abi::TypeInfo,
+    ptr: NonNull<u8>,
+}
+
+impl<'a> StructView<'a> {
+    /// Returns the type information of the struct.
+    pub fn type_info(&self) -> &abi::TypeInfo {
+        self.type_info
+    }
+
+    /// Returns a pointer to the field corresponding to `field_name`, if it exists and is of type
+    /// `T`.
+    fn field_ptr<T: ReturnTypeReflection>(&self, field_name: &str) -> Result<NonNull<T>, String> {
+        // Safety: Only struct types can be migrated.
+        let struct_info = self.type_info.as_struct().unwrap();
+        let field_idx =
+            abi::StructInfo::find_field_index(self.type_info.name(), struct_info, field_name)?;
+
+        // Safety: If we found the `field_idx`, we are guaranteed to also have the `field_type` and
+        // `field_offset`.
+        let field_type = unsafe { struct_info.field_types().get_unchecked(field_idx) };
+        equals_return_type::<T>(field_type).map_err(|(expected, found)| {
+            format!(
+                "Mismatched types for `{}::{}`. Expected: `{}`. Found: `{}`.",
+                self.type_info.name(),
+                field_name,
+                expected,
+                found,
+            )
+        })?;
+
+        let offset = unsafe { *struct_info.field_offsets().get_unchecked(field_idx) };
+        Ok(unsafe { NonNull::new_unchecked(self.ptr.as_ptr().add(offset as usize).cast::<T>()) })
+    }
+
+    /// Retrieves the value of the field corresponding to the specified `field_name`.
+    pub fn get<T: ReturnTypeReflection<Marshalled = T> + Copy>(
+        &self,
+        field_name: &str,
+    ) -> Result<T, String> {
+        self.field_ptr::<T>(field_name)
+            .map(|ptr| unsafe { ptr.as_ptr().read_unaligned() })
+    }
+
+    /// Sets the value of the field corresponding to the specified `field_name`.
+    pub fn set<T: ReturnTypeReflection<Marshalled = T> + Copy>(
+        &mut self,
+        field_name: &str,
+        value: T,
+    ) -> Result<(), String> {
+        self.field_ptr::<T>(field_name)
+            .map(|ptr| unsafe { ptr.as_ptr().write_unaligned(value) })
+    }
+}
+
+/// A field of a struct type that was renamed, of which the value is retained when hot reloading.
+///
+/// Renames can also be declared in Mun code, using the `#[migrate_from(old_name)]` attribute.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FieldRename {
+    /// The name of the struct type
+    pub type_name: String,
+    /// The previous name of the field
+    pub old_name: String,
+    /// The new name of the field
+    pub new_name: String,
+}
+
+/// The migrations of a single struct type.
+#[derive(Clone, Default)]
+struct TypeMigrations {
+    /// Pairs of `(old_field, new_field)` names of renamed fields
+    renamed_fields: Vec<(String, String)>,
+    /// User-defined migrations, in order of registration
+    migrations: Vec<Arc<Migration>>,
+}
+
+/// A field of which the value cannot be migrated to its new type without loss of information.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LossyConversion {
+    /// The name of the struct type
+    pub type_name: String,
+    /// The name of the field
+    pub field_name: String,
+    /// The name of the old type of the field
+    pub old_type: String,
+    /// The name of the new type of the field
+    pub new_type: String,
+    /// The policy that was applied to the field
+    pub policy: MigrationPolicy,
+}
+
+/// User-defined migrations of struct types, which are applied when the layout of a struct type
+/// changes during hot reloading.
+#[derive(Clone, Default)]
+pub struct Migrations {
+    types: FxHashMap<String, TypeMigrations>,
+    policy: MigrationPolicy,
+}
+
+impl Migrations {
+    /// Sets the `policy` for fields of which the value cannot be migrated to their new type
+    /// without loss of information. Defaults to [`MigrationPolicy::Reset`].
+    ///
+    /// [`MigrationPolicy::Reset`]: enum.MigrationPolicy.html#variant.Reset
+    pub fn set_policy(&mut self, policy: MigrationPolicy) {
+        self.policy = policy;
+    }
+
+    /// Adds a `migration` for objects of the struct type `type_name`.
+    pub fn insert<S: Into<String>>(&mut self, type_name: S, migration: Arc<Migration>) {
+        self.types
+            .entry(type_name.into())
+            .or_default()
+            .migrations
+            .push(migration);
+    }
+
+    /// Declares that a field of a struct type was renamed.
+    pub fn rename_field(&mut self, rename: FieldRename) {
+        self.types
+            .entry(rename.type_name)
+            .or_default()
+            .renamed_fields
+            .push((rename.old_name, rename.new_name));
+    }
+
+    /// Adds the migrations of the new types of the `mapping` to their conversions and applies the
+    /// migration policy. Returns the fields that are converted lossily, or an error if lossy
+    /// conversions are not allowed.
+    ///
+    /// Renames that are declared in Mun code are applied before those of the host, so the host can
+    /// override them.
+    pub(crate) fn apply(
+        &self,
+        mapping: &mut Mapping<UnsafeTypeInfo, UnsafeTypeInfo>,
+    ) -> Result<Vec<LossyConversion>, io::Error> {
+        for (old_ty, conversion) in mapping.conversions.iter_mut() {
+            // Renames that refer to fields that do not exist (anymore) are ignored, as they are
+            // only relevant to the reload in which the field was renamed.
+            let new_ty = conversion.new_ty.into_inner();
+            // Safety: The new type is guaranteed to be loaded during the migration.
+            if let Some(struct_info) = unsafe { new_ty.as_ref() }.as_struct() {
+                for (new_name, old_name) in struct_info
+                    .field_names()
+                    .zip(struct_info.field_previous_names())
+                {
+                    if let Some(old_name) = old_name {
+                        conversion.map_field(old_ty, old_name, new_name);
+                    }
+                }
+            }
+
+            let type_migrations = match self.types.get(conversion.new_ty.name()) {
+                Some(type_migrations) => type_migrations,
+                None => continue,
+            };
+
+            for (old_name, new_name) in type_migrations.renamed_fields.iter() {
+                conversion.map_field(old_ty, old_name, new_name);
+            }
+
+            if !type_migrations.migrations.is_empty() {
+                let migrations = type_migrations.migrations.clone();
+                let migration: MigrationFn<UnsafeTypeInfo> = Arc::new(
+                    move |old_ty: &UnsafeTypeInfo,
+                          old_ptr: NonNull<u8>,
+                          new_ty: &UnsafeTypeInfo,
+                          new_ptr: NonNull<u8>| {
+                        // Safety: The old and new types are guaranteed to be loaded during the
+                        // migration of their objects.
+                        let old = StructView {
+                            type_info: unsafe { old_ty.into_inner().as_ref() },
+                            ptr: old_ptr,
+                        };
+                        let mut new = StructView {
+                            type_info: unsafe { new_ty.into_inner().as_ref() },
+                            ptr: new_ptr,
+                        };
+                        for migration in migrations.iter() {
+                            migration(&old, &mut new);
+                        }
+                    },
+                );
+                conversion.migration = Some(migration);
+            }
+        }
+
+        let lossy_conversions = self.lossy_conversions(mapping);
+        if self.policy == MigrationPolicy::Strict {
+            if let Some(conversion) = lossy_conversions.first() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "Failed to migrate: field `{}::{}` cannot be converted from `{}` to `{}` without loss of information.",
+                        conversion.type_name,
+                        conversion.field_name,
+                        conversion.old_type,
+                        conversion.new_type
+                    ),
+                ));
+            }
+        }
+
+        mapping.policy = self.policy;
+        Ok(lossy_conversions)
+    }
+
+    /// Returns the fields of the `mapping` that cannot be cast without loss of information.
+    fn lossy_conversions(
+        &self,
+        mapping: &Mapping<UnsafeTypeInfo, UnsafeTypeInfo>,
+    ) -> Vec<LossyConversion> {
+        let mut lossy_conversions = Vec::new();
+        for conversion in mapping.conversions.values() {
+            let new_fields = conversion.new_ty.fields();
+            for (field_mapping, (field_name, _)) in
+                conversion.field_mapping.iter().zip(new_fields.iter())
+            {
+                if let Action::Cast { old_ty, .. } = &field_mapping.action {
+                    if !old_ty.group().is_struct()
+                        && !cast::is_lossless_cast(*old_ty.guid(), *field_mapping.new_ty.guid())
+                    {
+                        lossy_conversions.push(LossyConversion {
+                            type_name: conversion.new_ty.name().to_string(),
+                            field_name: field_name.to_string(),
+                            old_type: old_ty.name().to_string(),
+                            new_type: field_mapping.new_ty.name().to_string(),
+                            policy: self.policy,
+                        });
+                    }
+                }
+            }
+        }
+
+        // Conversions are stored in a hash map, so sort them to report them deterministically
+        lossy_conversions
+            .sort_by(|a, b| (&a.type_name, &a.field_name).cmp(&(&b.type_name, &b.field_name)));
+        lossy_conversions
+    }
+}
@@ -7,10 +7,10 @@ use crate::{
     Runtime,
 };
 use memory::gc::{GcRuntime, HasIndirectionPtr};
-use std::cell::RefCell;
+use parking_lot::RwLock;
 use std::{
     ptr::{self, NonNull},
-    rc::Rc,
+    sync::Arc,
 };
 
 /// Represents a Mun struct pointer.
@@ -29,14 +29,14 @@ impl RawStruct {
 #[derive(Clone)]
 pub struct StructRef {
     handle: GcRootPtr,
-    runtime: Rc<RefCell<Runtime>>,
+    runtime: Arc<RwLock<Runtime>>,
 }
 
 impl StructRef {
     /// Creates a `StructRef` that wraps a raw Mun struct.
-    fn new(runtime: Rc<RefCell<Runtime>>, raw: RawStruct) -> Self {
+    fn new(runtime: Arc<RwLock<Runtime>>, raw: RawStruct) -> Self {
         let handle = {
-            let runtime_ref = runtime.borrow();
+            let runtime_ref = runtime.read_recursive();
             // Safety: The type returned from `ptr_type` is guaranteed to live at least as long as
             // `Runtime` does not change. As we hold a shared reference to `Runtime`, this is safe.
             assert!(unsafe {
@@ -60,6 +60,11 @@ impl StructRef {
         RawStruct(self.handle.handle())
     }
 
+    /// Returns a raw Mun struct, without releasing the root held by the `StructRef`.
+    pub(crate) fn as_raw(&self) -> RawStruct {
+        RawStruct(self.handle.handle())
+    }
+
     /// Returns the type information of the struct.
     pub fn type_info<'r>(struct_ref: &Self, runtime_ref: &'r Runtime) -> &'r abi::TypeInfo {
         // Safety: The type returned from `ptr_type` is guaranteed to live at least as long as
@@ -90,8 +95,11 @@ impl StructRef {
     }
 
     /// Retrieves the value of the field corresponding to the specified `field_name`.
+    ///
+    /// The field is not modified through other `StructRef`s while it is being read.
     pub fn get<T: ReturnTypeReflection>(&self, field_name: &str) -> Result<T, String> {
-        let runtime_ref = self.runtime.borrow();
+        let runtime_ref = self.runtime.read_recursive();
+        let _fields = runtime_ref.field_lock.read();
         let type_info = Self::type_info(self, &runtime_ref);
 
         // Safety: `as_struct` is guaranteed to return `Some` for `StructRef`s.
@@ -124,12 +132,15 @@ impl StructRef {
 
     /// Replaces the value of the field corresponding to the specified `field_name` and returns the
     /// old value.
+    ///
+    /// Other `StructRef`s cannot access any field while it is being replaced.
     pub fn replace<T: ArgumentReflection>(
         &mut self,
         field_name: &str,
         value: T,
     ) -> Result<T, String> {
-        let runtime_ref = self.runtime.borrow();
+        let runtime_ref = self.runtime.read_recursive();
+        let _fields = runtime_ref.field_lock.write();
         let type_info = Self::type_info(self, &runtime_ref);
 
         // Safety: `as_struct` is guaranteed to return `Some` for `StructRef`s.
@@ -158,8 +169,11 @@ impl StructRef {
     }
 
     /// Sets the value of the field corresponding to the specified `field_name`.
+    ///
+    /// Other `StructRef`s cannot access any field while it is being set.
     pub fn set<T: ArgumentReflection>(&mut self, field_name: &str, value: T) -> Result<(), String> {
-        let runtime_ref = self.runtime.borrow();
+        let runtime_ref = self.runtime.read_recursive();
+        let _fields = runtime_ref.field_lock.write();
         let type_info = Self::type_info(self, &runtime_ref);
 
         // Safety: `as_struct` is guaranteed to return `Some` for `StructRef`s.
@@ -230,13 +244,13 @@ impl ReturnTypeReflection for StructRef {
 }
 
 impl Marshal<StructRef> for RawStruct {
-    fn marshal_value(self, runtime: Rc<RefCell<Runtime>>) -> StructRef {
+    fn marshal_value(self, runtime: Arc<RwLock<Runtime>>) -> StructRef {
         StructRef::new(runtime, self)
     }
 
     fn marshal_from_ptr(
         ptr: NonNull<Self>,
-        runtime: Rc<RefCell<Runtime>>,
+        runtime: Arc<RwLock<Runtime>>,
         type_info: Option<&abi::TypeInfo>,
     ) -> StructRef {
         // `type_info` is only `None` for the `()` type
@@ -249,7 +263,7 @@ impl Marshal<StructRef> for RawStruct {
 
             // Create a new object using the runtime's intrinsic
             let mut gc_handle = {
-                let runtime_ref = runtime.borrow();
+                let runtime_ref = runtime.read_recursive();
                 runtime_ref.gc().alloc(
                     // Safety: `ty` is a shared reference, so is guaranteed to not be `ptr::null()`.
                     UnsafeTypeInfo::new(unsafe {
@@ -0,0 +1,368 @@
+use crate::{
+    marshal::Marshal,
+    reflection::{ArgumentReflection, ReturnTypeReflection},
+    struct_ref::RawStruct,
+    Runtime, StructRef,
+};
+use libffi::middle::{Arg, Cif, CodePtr, Type};
+use parking_lot::RwLock;
+use std::{fmt, sync::Arc};
+
+/// A dynamically typed Mun value, used to invoke functions whose signature is only known at
+/// runtime.
+#[derive(Clone)]
+pub enum Value {
+    /// The value of the empty type `()`, returned by functions without a return type
+    Empty,
+    /// A `bool`
+    Bool(bool),
+    /// An `i8`
+    I8(i8),
+    /// An `i16`
+    I16(i16),
+    /// An `i32`
+    I32(i32),
+    /// An `i64`
+    I64(i64),
+    /// An `i128`
+    I128(i128),
+    /// An `isize`
+    Isize(isize),
+    /// A `u8`
+    U8(u8),
+    /// A `u16`
+    U16(u16),
+    /// A `u32`
+    U32(u32),
+    /// A `u64`
+    U64(u64),
+    /// A `u128`
+    U128(u128),
+    /// A `usize`
+    Usize(usize),
+    /// An `f32`
+    F32(f32),
+    /// An `f64`
+    F64(f64),
+    /// A struct, irrespective of its memory kind
+    Struct(StructRef),
+}
+
+macro_rules! impl_from_primitive {
+    ($($ty:ty => $Variant:ident),+) => {
+        $(
+            impl From<$ty> for Value {
+                fn from(value: $ty) -> Self {
+                    Value::$Variant(value)
+                }
+            }
+        )+
+    }
+}
+
+impl_from_primitive!(
+    bool => Bool,
+    i8 => I8,
+    i16 => I16,
+    i32 => I32,
+    i64 => I64,
+    i128 => I128,
+    isize => Isize,
+    u8 => U8,
+    u16 => U16,
+    u32 => U32,
+    u64 => U64,
+    u128 => U128,
+    usize => Usize,
+    f32 => F32,
+    f64 => F64,
+    StructRef => Struct
+);
+
+impl From<()> for Value {
+    fn from(_: ()) -> Self {
+        Value::Empty
+    }
+}
+
+impl fmt::Debug for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Empty => write!(f, "Empty"),
+            Value::Bool(v) => write!(f, "Bool({:?})", v),
+            Value::I8(v) => write!(f, "I8({:?})", v),
+            Value::I16(v) => write!(f, "I16({:?})", v),
+            Value::I32(v) => write!(f, "I32({:?})", v),
+            Value::I64(v) => write!(f, "I64({:?})", v),
+            Value::I128(v) => write!(f, "I128({:?})", v),
+            Value::Isize(v) => write!(f, "Isize({:?})", v),
+            Value::U8(v) => write!(f, "U8({:?})", v),
+            Value::U16(v) => write!(f, "U16({:?})", v),
+            Value::U32(v) => write!(f, "U32({:?})", v),
+            Value::U64(v) => write!(f, "U64({:?})", v),
+            Value::U128(v) => write!(f, "U128({:?})", v),
+            Value::Usize(v) => write!(f, "Usize({:?})", v),
+            Value::F32(v) => write!(f, "F32({:?})", v),
+            Value::F64(v) => write!(f, "F64({:?})", v),
+            Value::Struct(_) => write!(f, "Struct(..)"),
+        }
+    }
+}
+
+impl Value {
+    /// Retrieves the `Guid` of the value's type.
+    pub fn type_guid(&self, runtime: &Runtime) -> abi::Guid {
+        match self {
+            Value::Empty => <() as ReturnTypeReflection>::type_guid(),
+            Value::Bool(v) => v.type_guid(runtime),
+            Value::I8(v) => v.type_guid(runtime),
+            Value::I16(v) => v.type_guid(runtime),
+            Value::I32(v) => v.type_guid(runtime),
+            Value::I64(v) => v.type_guid(runtime),
+            Value::I128(v) => v.type_guid(runtime),
+            Value::Isize(v) => v.type_guid(runtime),
+            Value::U8(v) => v.type_guid(runtime),
+            Value::U16(v) => v.type_guid(runtime),
+            Value::U32(v) => v.type_guid(runtime),
+            Value::U64(v) => v.type_guid(runtime),
+            Value::U128(v) => v.type_guid(runtime),
+            Value::Usize(v) => v.type_guid(runtime),
+            Value::F32(v) => v.type_guid(runtime),
+            Value::F64(v) => v.type_guid(runtime),
+            Value::Struct(v) => v.type_guid(runtime),
+        }
+    }
+
+    /// Retrieves the name of the value's type.
+    pub fn type_name<'r>(&'r self, runtime: &'r Runtime) -> &'r str {
+        match self {
+            Value::Empty => <() as ReturnTypeReflection>::type_name(),
+            Value::Bool(v) => v.type_name(runtime),
+            Value::I8(v) => v.type_name(runtime),
+            Value::I16(v) => v.type_name(runtime),
+            Value::I32(v) => v.type_name(runtime),
+            Value::I64(v) => v.type_name(runtime),
+            Value::I128(v) => v.type_name(runtime),
+            Value::Isize(v) => v.type_name(runtime),
+            Value::U8(v) => v.type_name(runtime),
+            Value::U16(v) => v.type_name(runtime),
+            Value::U32(v) => v.type_name(runtime),
+            Value::U64(v) => v.type_name(runtime),
+            Value::U128(v) => v.type_name(runtime),
+            Value::Usize(v) => v.type_name(runtime),
+            Value::F32(v) => v.type_name(runtime),
+            Value::F64(v) => v.type_name(runtime),
+            Value::Struct(v) => v.type_name(runtime),
+        }
+    }
+}
+
+/// The representation of a type in the C calling convention used to call Mun functions.
+#[derive(Clone, Copy)]
+enum CallType {
+    Bool,
+    I8,
+    I16,
+    I32,
+    I64,
+    /// 128-bit integers are passed as a struct of two `u64`s, as libffi has no type for them.
+    /// This matches their representation in the C calling convention of 64-bit targets, as long
+    /// as they are passed in registers.
+    I128,
+    Isize,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    Usize,
+    F32,
+    F64,
+    /// Structs are passed as handles to garbage collected objects, irrespective of their memory
+    /// kind.
+    Struct,
+}
+
+impl CallType {
+    /// Returns the `CallType` of `type_info`, or an error if values of the type cannot be passed
+    /// through a dynamic call.
+    fn new(type_info: &abi::TypeInfo) -> Result<CallType, String> {
+        if type_info.group.is_struct() {
+            return Ok(CallType::Struct);
+        }
+
+        let guid = type_info.guid;
+        let call_types = [
+            (<bool as ReturnTypeReflection>::type_guid(), CallType::Bool),
+            (<i8 as ReturnTypeReflection>::type_guid(), CallType::I8),
+            (<i16 as ReturnTypeReflection>::type_guid(), CallType::I16),
+            (<i32 as ReturnTypeReflection>::type_guid(), CallType::I32),
+            (<i64 as ReturnTypeReflection>::type_guid(), CallType::I64),
+            (<i128 as ReturnTypeReflection>::type_guid(), CallType::I128),
+            (
+                <isize as ReturnTypeReflection>::type_guid(),
+                CallType::Isize,
+            ),
+            (<u8 as ReturnTypeReflection>::type_guid(), CallType::U8),
+            (<u16 as ReturnTypeReflection>::type_guid(), CallType::U16),
+            (<u32 as ReturnTypeReflection>::type_guid(), CallType::U32),
+            (<u64 as ReturnTypeReflection>::type_guid(), CallType::U64),
+            (<u128 as ReturnTypeReflection>::type_guid(), CallType::U128),
+            (
+                <usize as ReturnTypeReflection>::type_guid(),
+                CallType::Usize,
+            ),
+            (<f32 as ReturnTypeReflection>::type_guid(), CallType::F32),
+            (<f64 as ReturnTypeReflection>::type_guid(), CallType::F64),
+        ];
+        call_types
+            .iter()
+            .find(|(call_guid, _)| *call_guid == guid)
+            .map(|(_, call_type)| *call_type)
+            .ok_or_else(|| {
+                format!(
+                    "Type `{}` is not supported by dynamic invocation.",
+                    type_info.name()
+                )
+            })
+    }
+
+    fn ffi_type(self) -> Type {
+        match self {
+            CallType::Bool | CallType::U8 => Type::u8(),
+            CallType::I8 => Type::i8(),
+            CallType::I16 => Type::i16(),
+            CallType::I32 => Type::i32(),
+            CallType::I64 => Type::i64(),
+            CallType::Isize => Type::isize(),
+            CallType::U16 => Type::u16(),
+            CallType::U32 => Type::u32(),
+            CallType::U64 => Type::u64(),
+            CallType::Usize => Type::usize(),
+            CallType::I128 | CallType::U128 => Type::structure(vec![Type::u64(), Type::u64()]),
+            CallType::F32 => Type::f32(),
+            CallType::F64 => Type::f64(),
+            CallType::Struct => Type::pointer(),
+        }
+    }
+}
+
+impl Runtime {
+    /// Invokes the function `function_name` with the dynamically typed arguments `args` and
+    /// returns its result as a dynamically typed value.
+    ///
+    /// The arguments are validated against the signature of the function. Like with
+    /// [`invoke_fn!`], the runtime is locked for shared access for the duration of the invocation.
+    pub fn invoke_dynamic(
+        runtime: &Arc<RwLock<Runtime>>,
+        function_name: &str,
+        args: &[Value],
+    ) -> Result<Value, String> {
+        let runtime_ref = runtime.read_recursive();
+        let function_info = runtime_ref
+            .get_function_definition(function_name)
+            .ok_or_else(|| format!("Failed to obtain function '{}'", function_name))?;
+
+        // Validate function signature
+        let signature = &function_info.prototype.signature;
+        let arg_types = signature.arg_types();
+        if arg_types.len() != args.len() {
+            return Err(format!(
+                "Invalid number of arguments. Expected: {}. Found: {}.",
+                arg_types.len(),
+                args.len(),
+            ));
+        }
+
+        for (idx, (arg_type, arg)) in arg_types.iter().zip(args.iter()).enumerate() {
+            if arg_type.guid != arg.type_guid(&runtime_ref) {
+                return Err(format!(
+                    "Invalid argument type at index {}. Expected: {}. Found: {}.",
+                    idx,
+                    arg_type.name(),
+                    arg.type_name(&runtime_ref),
+                ));
+            }
+        }
+
+        let arg_call_types = arg_types
+            .iter()
+            .map(|ty| CallType::new(ty))
+            .collect::<Result<Vec<_>, _>>()?;
+        let return_call_type = signature.return_type().map(CallType::new).transpose()?;
+
+        let cif = Cif::new(
+            arg_call_types.iter().map(|ty| ty.ffi_type()),
+            return_call_type.map_or_else(Type::void, CallType::ffi_type),
+        );
+
+        // Structs are passed as raw handles, which have to outlive the call. They are kept alive by
+        // the `StructRef`s in `args`.
+        let raw_structs = args
+            .iter()
+            .map(|arg| match arg {
+                Value::Struct(s) => Some(s.as_raw()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        let ffi_args = args
+            .iter()
+            .zip(raw_structs.iter())
+            .map(|(arg, raw_struct)| match (arg, raw_struct) {
+                (Value::Bool(v), _) => Arg::new(v),
+                (Value::I8(v), _) => Arg::new(v),
+                (Value::I16(v), _) => Arg::new(v),
+                (Value::I32(v), _) => Arg::new(v),
+                (Value::I64(v), _) => Arg::new(v),
+                (Value::I128(v), _) => Arg::new(v),
+                (Value::Isize(v), _) => Arg::new(v),
+                (Value::U8(v), _) => Arg::new(v),
+                (Value::U16(v), _) => Arg::new(v),
+                (Value::U32(v), _) => Arg::new(v),
+                (Value::U64(v), _) => Arg::new(v),
+                (Value::U128(v), _) => Arg::new(v),
+                (Value::Usize(v), _) => Arg::new(v),
+                (Value::F32(v), _) => Arg::new(v),
+                (Value::F64(v), _) => Arg::new(v),
+                (Value::Struct(_), Some(raw)) => Arg::new(raw),
+                // The types of all other values were rejected by `CallType::new`
+                _ => unreachable!(),
+            })
+            .collect::<Vec<_>>();
+
+        let fn_ptr = CodePtr::from_ptr(function_info.fn_ptr);
+
+        // Safety: The call interface is constructed from the signature of the function, which the
+        // arguments were validated against. Integers that are smaller than a register are returned
+        // widened to a full register by libffi.
+        let result = unsafe {
+            match return_call_type {
+                None => {
+                    cif.call::<()>(fn_ptr, &ffi_args);
+                    Value::Empty
+                }
+                Some(CallType::Bool) => Value::Bool(cif.call::<u64>(fn_ptr, &ffi_args) as u8 != 0),
+                Some(CallType::I8) => Value::I8(cif.call::<i64>(fn_ptr, &ffi_args) as i8),
+                Some(CallType::I16) => Value::I16(cif.call::<i64>(fn_ptr, &ffi_args) as i16),
+                Some(CallType::I32) => Value::I32(cif.call::<i64>(fn_ptr, &ffi_args) as i32),
+                Some(CallType::I64) => Value::I64(cif.call::<i64>(fn_ptr, &ffi_args)),
+                Some(CallType::I128) => Value::I128(cif.call::<i128>(fn_ptr, &ffi_args)),
+                Some(CallType::Isize) => Value::Isize(cif.call::<isize>(fn_ptr, &ffi_args)),
+                Some(CallType::U8) => Value::U8(cif.call::<u64>(fn_ptr, &ffi_args) as u8),
+                Some(CallType::U16) => Value::U16(cif.call::<u64>(fn_ptr, &ffi_args) as u16),
+                Some(CallType::U32) => Value::U32(cif.call::<u64>(fn_ptr, &ffi_args) as u32),
+                Some(CallType::U64) => Value::U64(cif.call::<u64>(fn_ptr, &ffi_args)),
+                Some(CallType::U128) => Value::U128(cif.call::<u128>(fn_ptr, &ffi_args)),
+                Some(CallType::Usize) => Value::Usize(cif.call::<usize>(fn_ptr, &ffi_args)),
+                Some(CallType::F32) => Value::F32(cif.call::<f32>(fn_ptr, &ffi_args)),
+                Some(CallType::F64) => Value::F64(cif.call::<f64>(fn_ptr, &ffi_args)),
+                Some(CallType::Struct) => {
+                    let raw: RawStruct = cif.call(fn_ptr, &ffi_args);
+                    Value::Struct(raw.marshal_value(runtime.clone()))
+                }
+            }
+        };
+
+        Ok(result)
+    }
+}
@@ -20,6 +20,34 @@ fn hotreloadable() {
     assert_invoke_eq!(i32, 10, driver, "main");
 }
 
+#[test]
+fn hotreloadable_with_debug_info() {
+    let context = codegen::Context::create();
+    let mut driver = TestDriver::with_debug_info(&context,
+        r"
+    struct Foo { a: i32 }
+
+    pub fn main() -> i32 {
+        let foo = Foo { a: 5 };
+        foo.a
+    }
+    ",
+        true,
+    );
+    assert_invoke_eq!(i32, 5, driver, "main");
+    driver.update(&context,
+        r"
+    struct Foo { a: i32 }
+
+    pub fn main() -> i32 {
+        let foo = Foo { a: 10 };
+        foo.a
+    }
+    ",
+    );
+    assert_invoke_eq!(i32, 10, driver, "main");
+}
+
 #[test]
 fn hotreload_struct_decl() {
     let context = codegen::Context::create();
@@ -56,3 +84,408 @@ fn hotreload_struct_decl() {
     "#,
     );
 }
+
+#[test]
+fn reload_events() {
+    use mun_runtime::{invoke_fn, StructRef};
+    use parking_lot::Mutex;
+    use std::sync::Arc;
+
+    let context = codegen::Context::create();
+    let mut driver = TestDriver::new(&context,
+        r#"
+    struct(gc) Foo { a: i32 }
+
+    pub fn foo_new() -> Foo { Foo { a: 5 } }
+    "#,
+    );
+
+    let foo: StructRef = invoke_fn!(driver.runtime_mut(), "foo_new").unwrap();
+
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let subscription = {
+        let events = events.clone();
+        driver
+            .runtime_mut()
+            .write()
+            .subscribe(move |event| events.lock().push(format!("{:?}", event)))
+    };
+
+    driver.update(&context,
+        r#"
+    struct(gc) Foo { a: i32, b: f64 }
+
+    pub fn foo_new() -> Foo { Foo { a: 5, b: 1.0 } }
+    "#,
+    );
+    assert_eq!(foo.get::<i32>("a"), Ok(5));
+
+    {
+        let events = events.lock();
+        assert_eq!(events.len(), 2);
+        assert!(events[0].starts_with("TypesMigrated"));
+        assert!(events[0].ends_with("types: [\"Foo\"] }"));
+        assert!(events[1].starts_with("AssemblyReloaded"));
+    }
+
+    // Unsubscribed callbacks no longer receive events
+    assert!(driver.runtime_mut().write().unsubscribe(subscription));
+    assert!(!driver.runtime_mut().write().unsubscribe(subscription));
+    driver.update(&context,
+        r#"
+    struct(gc) Foo { a: i32, b: f64 }
+
+    pub fn foo_new() -> Foo { Foo { a: 6, b: 1.0 } }
+    "#,
+    );
+    assert_eq!(events.lock().len(), 2);
+}
+
+#[test]
+fn migrate_struct_fields() {
+    use mun_runtime::{invoke_fn, FieldRename, StructRef};
+
+    let context = codegen::Context::create();
+    let mut driver = TestDriver::new(&context,
+        r#"
+    struct(gc) Player { hp: i32, pos: f64 }
+
+    pub fn player_new() -> Player { Player { hp: 100, pos: 1.5 } }
+    "#,
+    )
+    .rename_field(FieldRename {
+        type_name: "Player".to_owned(),
+        old_name: "hp".to_owned(),
+        new_name: "health".to_owned(),
+    })
+    .migrate("Player", |old, new| {
+        let pos: f64 = old.get("pos").unwrap();
+        new.set("x", pos as f32).unwrap();
+        new.set("y", pos as f32 * 2.0).unwrap();
+    });
+
+    let player: StructRef = invoke_fn!(driver.runtime_mut(), "player_new").unwrap();
+    driver.update(&context,
+        r#"
+    struct(gc) Player { health: i64, x: f32, y: f32 }
+
+    pub fn player_new() -> Player { Player { health: 100, x: 0.0, y: 0.0 } }
+    "#,
+    );
+
+    // The renamed field is cast to its new type
+    assert_eq!(player.get::<i64>("health"), Ok(100));
+    assert_eq!(player.get::<f32>("x"), Ok(1.5));
+    assert_eq!(player.get::<f32>("y"), Ok(3.0));
+}
+
+#[test]
+fn migrate_renamed_fields_from_source() {
+    use mun_runtime::{invoke_fn, StructRef};
+
+    let context = codegen::Context::create();
+    let mut driver = TestDriver::new(&context,
+        r#"
+    struct(gc) Player { hp: i32, mana: i32 }
+
+    pub fn player_new() -> Player { Player { hp: 100, mana: 50 } }
+    "#,
+    );
+
+    let player: StructRef = invoke_fn!(driver.runtime_mut(), "player_new").unwrap();
+    driver.update(&context,
+        r#"
+    struct(gc) Player {
+        #[migrate_from(hp)]
+        health: i32,
+        #[migrate_from(mana)]
+        energy: i64,
+    }
+
+    pub fn player_new() -> Player { Player { health: 100, energy: 50 } }
+    "#,
+    );
+
+    assert_eq!(player.get::<i32>("health"), Ok(100));
+    assert_eq!(player.get::<i64>("energy"), Ok(50));
+
+    // The attributes no longer apply once the fields have been renamed
+    driver.update(&context,
+        r#"
+    struct(gc) Player {
+        #[migrate_from(hp)]
+        health: i32,
+        #[migrate_from(mana)]
+        energy: i64,
+        armor: i32,
+    }
+
+    pub fn player_new() -> Player { Player { health: 100, energy: 50, armor: 10 } }
+    "#,
+    );
+
+    assert_eq!(player.get::<i32>("health"), Ok(100));
+    assert_eq!(player.get::<i64>("energy"), Ok(50));
+    assert_eq!(player.get::<i32>("armor"), Ok(0));
+}
+
+#[test]
+fn migrate_renamed_struct_fields_from_source() {
+    use mun_runtime::{invoke_fn, StructRef};
+
+    let context = codegen::Context::create();
+    let mut driver = TestDriver::new(&context,
+        r#"
+    struct(value) Vec2 { x: f32, y: f32 }
+    struct(gc) Player { pos: Vec2, target: Vec2 }
+
+    pub fn player_new() -> Player {
+        Player { pos: Vec2 { x: 1.0, y: 2.0 }, target: Vec2 { x: 3.0, y: 4.0 } }
+    }
+    "#,
+    );
+
+    let player: StructRef = invoke_fn!(driver.runtime_mut(), "player_new").unwrap();
+
+    // The renamed struct fields are boxed along with the change of memory kind of their type
+    driver.update(&context,
+        r#"
+    struct(gc) Vec2 { x: f32, y: f32 }
+    struct(gc) Player {
+        #[migrate_from(pos)]
+        position: Vec2,
+        #[migrate_from(target)]
+        destination: Vec2,
+    }
+
+    pub fn player_new() -> Player {
+        Player { position: Vec2 { x: 5.0, y: 6.0 }, destination: Vec2 { x: 7.0, y: 8.0 } }
+    }
+    "#,
+    );
+
+    let position = player.get::<StructRef>("position").unwrap();
+    assert_eq!(position.get::<f32>("x"), Ok(1.0));
+    assert_eq!(position.get::<f32>("y"), Ok(2.0));
+    let destination = player.get::<StructRef>("destination").unwrap();
+    assert_eq!(destination.get::<f32>("x"), Ok(3.0));
+    assert_eq!(destination.get::<f32>("y"), Ok(4.0));
+
+    // A struct field that is renamed to a field of a fundamental type is reset
+    driver.update(&context,
+        r#"
+    struct(gc) Vec2 { x: f32, y: f32 }
+    struct(gc) Player {
+        #[migrate_from(position)]
+        pos: Vec2,
+        #[migrate_from(destination)]
+        distance: f32,
+    }
+
+    pub fn player_new() -> Player { Player { pos: Vec2 { x: 5.0, y: 6.0 }, distance: 1.0 } }
+    "#,
+    );
+
+    let pos = player.get::<StructRef>("pos").unwrap();
+    assert_eq!(pos.get::<f32>("x"), Ok(1.0));
+    assert_eq!(pos.get::<f32>("y"), Ok(2.0));
+    assert_eq!(player.get::<f32>("distance"), Ok(0.0));
+}
+
+#[test]
+fn lossy_field_conversions() {
+    use mun_runtime::{invoke_fn, IntegerNarrowing, MigrationPolicy, StructRef};
+    use parking_lot::Mutex;
+    use std::sync::Arc;
+
+    let context = codegen::Context::create();
+    let mut driver = TestDriver::new(&context,
+        r#"
+    struct(gc) Foo { a: i64, b: f64, c: i32 }
+
+    pub fn foo_new() -> Foo { Foo { a: 300, b: 1.5, c: 2 } }
+    "#,
+    )
+    .set_migration_policy(MigrationPolicy::Lossy(IntegerNarrowing::Saturate));
+
+    let foo: StructRef = invoke_fn!(driver.runtime_mut(), "foo_new").unwrap();
+
+    let events = Arc::new(Mutex::new(Vec::new()));
+    {
+        let events = events.clone();
+        driver
+            .runtime_mut()
+            .write()
+            .subscribe(move |event| events.lock().push(format!("{:?}", event)));
+    }
+
+    driver.update(&context,
+        r#"
+    struct(gc) Foo { a: i8, b: f32, c: i64 }
+
+    pub fn foo_new() -> Foo { Foo { a: 3, b: 1.0, c: 2 } }
+    "#,
+    );
+    assert_eq!(foo.get::<i8>("a"), Ok(127));
+    assert_eq!(foo.get::<f32>("b"), Ok(1.5));
+    assert_eq!(foo.get::<i64>("c"), Ok(2));
+
+    // Only the narrowing conversions are reported
+    let events = events.lock();
+    assert_eq!(events.len(), 4);
+    assert!(events[1].starts_with("LossyConversion"));
+    assert!(events[1].contains(
+        "field_name: \"a\", old_type: \"core::i64\", new_type: \"core::i8\""
+    ));
+    assert!(events[2].starts_with("LossyConversion"));
+    assert!(events[2].contains(
+        "field_name: \"b\", old_type: \"core::f64\", new_type: \"core::f32\""
+    ));
+}
+
+#[test]
+fn prune_legacy_libraries() {
+    use mun_runtime::{invoke_fn, StructRef};
+
+    let context = codegen::Context::create();
+    let mut driver = TestDriver::new(&context,
+        r#"
+    struct(gc) Foo { a: i32 }
+
+    pub fn foo_new() -> Foo { Foo { a: 5 } }
+    "#,
+    );
+
+    let foo: StructRef = invoke_fn!(driver.runtime_mut(), "foo_new").unwrap();
+    assert_eq!(driver.runtime_mut().read().num_legacy_libs(), 0);
+
+    // The previous library is retained, as the deleted object still refers to its type
+    driver.update(&context,
+        r#"
+    struct(gc) Bar { b: i32 }
+
+    pub fn bar_new() -> Bar { Bar { b: 3 } }
+    "#,
+    );
+    assert_eq!(driver.runtime_mut().read().num_legacy_libs(), 1);
+
+    // Collecting an unrelated object prunes legacy libraries, but not the one still in use
+    let bar: StructRef = invoke_fn!(driver.runtime_mut(), "bar_new").unwrap();
+    drop(bar);
+    assert!(driver.runtime_mut().write().gc_collect());
+    assert_eq!(driver.runtime_mut().read().num_legacy_libs(), 1);
+    assert_eq!(foo.get::<i32>("a"), Ok(5));
+
+    // Once the object has been collected, the library is released
+    drop(foo);
+    assert!(driver.runtime_mut().write().gc_collect());
+    assert_eq!(driver.runtime_mut().read().num_legacy_libs(), 0);
+}
+
+#[test]
+fn hotreload_struct_memory_kind() {
+    use mun_runtime::{invoke_fn, StructRef};
+
+    let context = codegen::Context::create();
+    let mut driver = TestDriver::new(&context,
+        r#"
+    struct(value) Foo { a: i32 }
+    struct(gc) Bar { b: f64, foo: Foo }
+
+    pub fn bar_new() -> Bar { Bar { b: 2.0, foo: Foo { a: 5 } } }
+    "#,
+    );
+
+    let bar: StructRef = invoke_fn!(driver.runtime_mut(), "bar_new").unwrap();
+
+    // The value struct field is boxed into a new garbage collected object
+    driver.update(&context,
+        r#"
+    struct(gc) Foo { a: i32 }
+    struct(gc) Bar { b: f64, foo: Foo }
+
+    pub fn bar_new() -> Bar { Bar { b: 3.0, foo: Foo { a: 6 } } }
+    "#,
+    );
+    assert_eq!(bar.get::<f64>("b"), Ok(2.0));
+    assert_eq!(bar.get::<StructRef>("foo").unwrap().get::<i32>("a"), Ok(5));
+
+    // The garbage collected struct field is inlined into the value struct
+    driver.update(&context,
+        r#"
+    struct(value) Foo { a: i32 }
+    struct(gc) Bar { b: f64, foo: Foo }
+
+    pub fn bar_new() -> Bar { Bar { b: 4.0, foo: Foo { a: 7 } } }
+    "#,
+    );
+    assert_eq!(bar.get::<f64>("b"), Ok(2.0));
+    assert_eq!(bar.get::<StructRef>("foo").unwrap().get::<i32>("a"), Ok(5));
+}
+
+#[test]
+fn manual_reload() {
+    let context = codegen::Context::create();
+    let mut driver = TestDriver::new(&context,
+        r#"
+    pub fn main() -> i32 { 5 }
+    "#,
+    )
+    .set_watch_files(false);
+    assert_invoke_eq!(i32, 5, driver, "main");
+
+    // Without a file watcher, changes to the assembly are not detected
+    driver.recompile(&context,
+        r#"
+    pub fn main() -> i32 { 6 }
+    "#,
+    );
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    assert!(!driver.runtime_mut().write().update());
+    assert_invoke_eq!(i32, 5, driver, "main");
+
+    let out_path = driver.out_path().to_path_buf();
+    driver
+        .runtime_mut()
+        .write()
+        .reload_assembly(&out_path)
+        .unwrap();
+    assert_invoke_eq!(i32, 6, driver, "main");
+}
+
+#[test]
+fn custom_change_source() {
+    use mun_runtime::ChangeSource;
+    use std::{
+        path::PathBuf,
+        sync::mpsc::{channel, Receiver},
+    };
+
+    struct ChannelSource(Receiver<PathBuf>);
+
+    impl ChangeSource for ChannelSource {
+        fn poll_changes(&mut self) -> Vec<PathBuf> {
+            self.0.try_iter().collect()
+        }
+    }
+
+    let (tx, rx) = channel();
+    let context = codegen::Context::create();
+    let mut driver = TestDriver::new(&context,
+        r#"
+    pub fn main() -> i32 { 5 }
+    "#,
+    )
+    .set_watch_files(false)
+    .add_change_source(ChannelSource(rx));
+    assert_invoke_eq!(i32, 5, driver, "main");
+
+    driver.recompile(&context,
+        r#"
+    pub fn main() -> i32 { 6 }
+    "#,
+    );
+    tx.send(driver.out_path().to_path_buf()).unwrap();
+    assert!(driver.runtime_mut().write().update());
+    assert_invoke_eq!(i32, 6, driver, "main");
+}
@@ -1,5 +1,6 @@
 use mun_runtime::{
     invoke_fn, ArgumentReflection, RetryResultExt, ReturnTypeReflection, Runtime, StructRef,
+    Value,
 };
 
 #[macro_use]
@@ -242,7 +243,7 @@ fn compiler_valid_utf8() {
     "#,
     );
 
-    let borrowed = driver.runtime_mut().borrow();
+    let borrowed = driver.runtime_mut().read();
     let foo_func = borrowed.get_function_definition("foo").unwrap();
     assert_eq!(
         unsafe { CStr::from_ptr(foo_func.prototype.name) }
@@ -399,14 +400,14 @@ fn marshal_struct() {
         invoke_fn!(driver.runtime_mut(), "foo_new", int_data.0, bool_data.0).unwrap();
     let c2: StructRef =
         invoke_fn!(driver.runtime_mut(), "foo_new", int_data.1, bool_data.1).unwrap();
-    test_struct(&driver.runtime_mut().borrow(), &mut baz, c1, c2);
+    test_struct(&driver.runtime_mut().read(), &mut baz, c1, c2);
 
     let mut qux: StructRef = invoke_fn!(driver.runtime_mut(), "qux_new", bar).unwrap();
     let c1: StructRef =
         invoke_fn!(driver.runtime_mut(), "bar_new", int_data.0, bool_data.0).unwrap();
     let c2: StructRef =
         invoke_fn!(driver.runtime_mut(), "bar_new", int_data.1, bool_data.1).unwrap();
-    test_struct(&driver.runtime_mut().borrow(), &mut qux, c1, c2);
+    test_struct(&driver.runtime_mut().read(), &mut qux, c1, c2);
 
     // Verify the dispatch table works when a marshallable wrapper function exists alongside the
     // original function.
@@ -421,7 +422,7 @@ fn marshal_struct() {
         invoke_fn!(driver.runtime_mut(), "foo_new", int_data.0, bool_data.0).unwrap();
     let c2: StructRef =
         invoke_fn!(driver.runtime_mut(), "foo_new", int_data.1, bool_data.1).unwrap();
-    test_struct(&driver.runtime_mut().borrow(), &mut baz2, c1, c2);
+    test_struct(&driver.runtime_mut().read(), &mut baz2, c1, c2);
 
     fn test_shallow_copy<
         T: Copy + std::fmt::Debug + PartialEq + ArgumentReflection + ReturnTypeReflection,
@@ -666,3 +667,121 @@ fn signed_and_unsigned_rem() {
     assert_invoke_eq!(i32, -2, driver, "signed");
     assert_invoke_eq!(i32, 2, driver, "unsigned");
 }
+
+#[test]
+fn invoke_dynamic() {
+    let context = codegen::Context::create();
+    let mut driver = TestDriver::new(&context,
+        r#"
+    struct(value) Foo { a: i32, b: bool };
+    struct(gc) Bar(f64, u8);
+
+    pub fn add(a: i32, b: i32) -> i32 { a + b }
+    pub fn negate(a: bool) -> bool { !a }
+    pub fn half(a: f32) -> f32 { a / 2.0 }
+    pub fn small(a: u8, b: i16) -> i16 { b - 1 }
+    pub fn foo_new(a: i32, b: bool) -> Foo { Foo { a, b } }
+    pub fn foo_a(foo: Foo) -> i32 { foo.a }
+    pub fn bar_new(a: f64, b: u8) -> Bar { Bar(a, b) }
+    pub fn nothing() {}
+    "#,
+    );
+    let runtime = driver.runtime_mut();
+
+    let result = Runtime::invoke_dynamic(runtime, "add", &[3i32.into(), 4i32.into()]).unwrap();
+    assert_eq!(format!("{:?}", result), "I32(7)");
+
+    let result = Runtime::invoke_dynamic(runtime, "negate", &[true.into()]).unwrap();
+    assert_eq!(format!("{:?}", result), "Bool(false)");
+
+    let result = Runtime::invoke_dynamic(runtime, "half", &[5f32.into()]).unwrap();
+    assert_eq!(format!("{:?}", result), "F32(2.5)");
+
+    let result = Runtime::invoke_dynamic(runtime, "small", &[1u8.into(), (-3i16).into()]).unwrap();
+    assert_eq!(format!("{:?}", result), "I16(-4)");
+
+    let result = Runtime::invoke_dynamic(runtime, "nothing", &[]).unwrap();
+    assert_eq!(format!("{:?}", result), "Empty");
+
+    // Value structs are marshalled as `StructRef`s, just like gc structs
+    let foo = match Runtime::invoke_dynamic(runtime, "foo_new", &[5i32.into(), true.into()]) {
+        Ok(Value::Struct(foo)) => foo,
+        result => panic!("expected a struct, found: {:?}", result),
+    };
+    assert_eq!(foo.get::<i32>("a"), Ok(5));
+    assert_eq!(foo.get::<bool>("b"), Ok(true));
+
+    let result = Runtime::invoke_dynamic(runtime, "foo_a", &[foo.clone().into()]).unwrap();
+    assert_eq!(format!("{:?}", result), "I32(5)");
+
+    let bar = match Runtime::invoke_dynamic(runtime, "bar_new", &[1.5f64.into(), 2u8.into()]) {
+        Ok(Value::Struct(bar)) => bar,
+        result => panic!("expected a struct, found: {:?}", result),
+    };
+    assert_eq!(bar.get::<f64>("0"), Ok(1.5));
+    assert_eq!(bar.get::<u8>("1"), Ok(2));
+
+    // Arguments are validated against the function signature
+    assert_eq!(
+        Runtime::invoke_dynamic(runtime, "add", &[3i32.into()]).unwrap_err(),
+        "Invalid number of arguments. Expected: 2. Found: 1."
+    );
+    assert_eq!(
+        Runtime::invoke_dynamic(runtime, "add", &[3i32.into(), 4i64.into()]).unwrap_err(),
+        "Invalid argument type at index 1. Expected: core::i32. Found: core::i64."
+    );
+    assert!(Runtime::invoke_dynamic(runtime, "foo_a", &[bar.into()]).is_err());
+    assert_eq!(
+        Runtime::invoke_dynamic(runtime, "missing", &[]).unwrap_err(),
+        "Failed to obtain function 'missing'"
+    );
+}
+
+#[test]
+fn invoke_dynamic_pointer_sized_and_128_bit_integers() {
+    let context = codegen::Context::create();
+    let mut driver = TestDriver::new(&context,
+        r#"
+    pub fn isize_id(a: isize) -> isize { a }
+    pub fn usize_id(a: usize) -> usize { a }
+    pub fn i128_id(a: i128) -> i128 { a }
+    pub fn u128_id(a: u128) -> u128 { a }
+    pub fn i128_second(a: i8, b: i128) -> i128 { b }
+    pub fn u128_sum(a: u128, b: u64, c: u128) -> u128 { a + c }
+    "#,
+    );
+    let runtime = driver.runtime_mut();
+
+    let result = Runtime::invoke_dynamic(runtime, "isize_id", &[isize::min_value().into()]);
+    assert_eq!(
+        format!("{:?}", result.unwrap()),
+        format!("Isize({})", isize::min_value())
+    );
+
+    let result = Runtime::invoke_dynamic(runtime, "usize_id", &[usize::max_value().into()]);
+    assert_eq!(
+        format!("{:?}", result.unwrap()),
+        format!("Usize({})", usize::max_value())
+    );
+
+    let result = Runtime::invoke_dynamic(runtime, "i128_id", &[i128::min_value().into()]);
+    assert_eq!(
+        format!("{:?}", result.unwrap()),
+        format!("I128({})", i128::min_value())
+    );
+
+    let result = Runtime::invoke_dynamic(runtime, "u128_id", &[u128::max_value().into()]);
+    assert_eq!(
+        format!("{:?}", result.unwrap()),
+        format!("U128({})", u128::max_value())
+    );
+
+    // Both halves of a 128-bit integer are passed, irrespective of the arguments around it
+    let b = -(1i128 << 100) - 7;
+    let result = Runtime::invoke_dynamic(runtime, "i128_second", &[1i8.into(), b.into()]);
+    assert_eq!(format!("{:?}", result.unwrap()), format!("I128({})", b));
+
+    let (a, c) = ((1u128 << 64) + 1, (3u128 << 96) + 2);
+    let result = Runtime::invoke_dynamic(runtime, "u128_sum", &[a.into(), 5u64.into(), c.into()]);
+    assert_eq!(format!("{:?}", result.unwrap()), format!("U128({})", a + c));
+}
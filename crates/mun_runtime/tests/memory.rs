@@ -32,13 +32,13 @@ fn gc_trace() {
 
     let value: StructRef = invoke_fn!(driver.runtime_mut(), "new_foo").unwrap();
 
-    assert_eq!(driver.runtime_mut().borrow().gc_collect(), false);
-    assert!(driver.runtime_mut().borrow().gc_stats().allocated_memory > 0);
+    assert_eq!(driver.runtime_mut().write().gc_collect(), false);
+    assert!(driver.runtime_mut().read().gc_stats().allocated_memory > 0);
 
     drop(value);
 
-    assert_eq!(driver.runtime_mut().borrow().gc_collect(), true);
-    assert_eq!(driver.runtime_mut().borrow().gc_stats().allocated_memory, 0);
+    assert_eq!(driver.runtime_mut().write().gc_collect(), true);
+    assert_eq!(driver.runtime_mut().read().gc_stats().allocated_memory, 0);
 }
 
 #[test]
@@ -535,12 +535,12 @@ fn delete_used_struct() {
 
     assert!(driver
         .runtime_mut()
-        .borrow()
+        .read()
         .get_function_definition("foo_new")
         .is_none());
     assert!(driver
         .runtime_mut()
-        .borrow()
+        .read()
         .get_function_definition("bar_new")
         .is_some());
     assert_eq!(foo.get::<i64>("a").unwrap(), a);
@@ -0,0 +1,360 @@
+use mun_compiler::{compile_package, Config, DisplayColor, Package, MANIFEST_FILENAME};
+use mun_runtime::{invoke_fn, RuntimeBuilder, StructRef};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Writes a package with a single source file to `root`.
+fn write_package(root: &Path, manifest: &str, source: &str) -> Package {
+    std::fs::create_dir_all(root.join("src")).unwrap();
+    std::fs::write(root.join(MANIFEST_FILENAME), manifest).unwrap();
+    std::fs::write(root.join("src").join("main.mun"), source).unwrap();
+    Package::discover(root).unwrap()
+}
+
+/// Returns the configuration described by the manifest of `package`, which compiles the package
+/// into its own output directory.
+fn config(package: &Package) -> Config {
+    Config {
+        display_color: DisplayColor::Disable,
+        ..package.config().unwrap()
+    }
+}
+
+#[test]
+fn call_dependency() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let math = write_package(
+        &temp_dir.path().join("math"),
+        "[package]\nname = \"math\"\n",
+        "pub fn add(a: i32, b: i32) -> i32 { a + b }",
+    );
+    let app = write_package(
+        &temp_dir.path().join("app"),
+        "[package]\nname = \"app\"\n\n[dependencies]\nmath = { path = \"../math\" }\n",
+        "pub fn main() -> i32 { add(2, 3) }\n\nfn private() -> i32 { add(1, 1) }\n\npub fn via_private() -> i32 { private() }",
+    );
+
+    let assembly_path = compile_package(&app, config(&app))
+        .unwrap()
+        .expect("compiler errors");
+    assert_eq!(assembly_path, app.assembly_path());
+    assert!(math.assembly_path().is_file());
+
+    let runtime = RuntimeBuilder::new(&assembly_path).spawn().unwrap();
+    let result: i32 = invoke_fn!(runtime, "main").unwrap();
+    assert_eq!(result, 5);
+    let result: i32 = invoke_fn!(runtime, "via_private").unwrap();
+    assert_eq!(result, 2);
+
+    // Reloading the dependency changes the behavior of the dependent assembly
+    std::fs::write(
+        math.source_dir().join("main.mun"),
+        "pub fn add(a: i32, b: i32) -> i32 { a * b }",
+    )
+    .unwrap();
+    compile_package(&math, config(&math))
+        .unwrap()
+        .expect("compiler errors");
+
+    let start_time = Instant::now();
+    while !runtime.write().update() {
+        if start_time.elapsed() > Duration::from_secs(10) {
+            panic!("runtime did not update after recompilation within 10secs");
+        }
+        std::thread::sleep(Duration::from_millis(1));
+    }
+
+    let result: i32 = invoke_fn!(runtime, "main").unwrap();
+    assert_eq!(result, 6);
+}
+
+#[test]
+fn reload_dependent_assemblies_together() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let math = write_package(
+        &temp_dir.path().join("math"),
+        "[package]\nname = \"math\"\n",
+        "pub fn add(a: i32, b: i32) -> i32 { a + b }",
+    );
+    let app = write_package(
+        &temp_dir.path().join("app"),
+        "[package]\nname = \"app\"\n\n[dependencies]\nmath = { path = \"../math\" }\n",
+        "pub fn main() -> i32 { add(2, 3) }",
+    );
+
+    let assembly_path = compile_package(&app, config(&app))
+        .unwrap()
+        .expect("compiler errors");
+
+    // Both assemblies are written into the temporary directory, which only this test's runtime
+    // watches
+    assert_eq!(assembly_path, app.assembly_path());
+    assert!(assembly_path.starts_with(temp_dir.path()));
+    assert!(math.assembly_path().is_file());
+
+    let delay = Duration::from_millis(100);
+    let runtime = RuntimeBuilder::new(&assembly_path)
+        .set_delay(delay)
+        .spawn()
+        .unwrap();
+    let result: i32 = invoke_fn!(runtime, "main").unwrap();
+    assert_eq!(result, 5);
+
+    // Renaming a function requires both assemblies to be reloaded at the same time
+    std::fs::write(
+        math.source_dir().join("main.mun"),
+        "pub fn mul(a: i32, b: i32) -> i32 { a * b }",
+    )
+    .unwrap();
+    std::fs::write(
+        app.source_dir().join("main.mun"),
+        "pub fn main() -> i32 { mul(2, 3) }",
+    )
+    .unwrap();
+    compile_package(&app, config(&app))
+        .unwrap()
+        .expect("compiler errors");
+
+    // Wait for the changes of both assemblies to be reported
+    std::thread::sleep(delay * 5);
+
+    let start_time = Instant::now();
+    while !runtime.write().update() {
+        if start_time.elapsed() > Duration::from_secs(10) {
+            panic!("runtime did not update after recompilation within 10secs");
+        }
+        std::thread::sleep(Duration::from_millis(1));
+    }
+
+    let result: i32 = invoke_fn!(runtime, "main").unwrap();
+    assert_eq!(result, 6);
+}
+
+#[test]
+fn load_and_unload_assemblies() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let math = write_package(
+        &temp_dir.path().join("math"),
+        "[package]\nname = \"math\"\n",
+        "pub fn add(a: i32, b: i32) -> i32 { a + b }",
+    );
+    let app = write_package(
+        &temp_dir.path().join("app"),
+        "[package]\nname = \"app\"\n\n[dependencies]\nmath = { path = \"../math\" }\n",
+        "pub fn main() -> i32 { add(2, 3) }",
+    );
+    let plugin = write_package(
+        &temp_dir.path().join("plugin"),
+        "[package]\nname = \"plugin\"\n\n[dependencies]\nmath = { path = \"../math\" }\n",
+        "struct(gc) Foo { a: i32 }\n\npub fn foo_new() -> Foo { Foo { a: add(3, 4) } }",
+    );
+
+    let assembly_path = compile_package(&app, config(&app))
+        .unwrap()
+        .expect("compiler errors");
+    let plugin_path = compile_package(&plugin, config(&plugin))
+        .unwrap()
+        .expect("compiler errors");
+    let math_path = math.assembly_path();
+
+    // The assemblies are written into the temporary directory, so they cannot be picked up by the
+    // runtimes of other tests
+    assert_eq!(assembly_path, app.assembly_path());
+    assert_eq!(plugin_path, plugin.assembly_path());
+    assert!(plugin_path.starts_with(temp_dir.path()));
+
+    let runtime = RuntimeBuilder::new(&assembly_path).spawn().unwrap();
+    let result: Result<StructRef, _> = invoke_fn!(runtime, "foo_new");
+    assert!(result.is_err());
+
+    // Assemblies can be loaded after the runtime was spawned
+    runtime.write().add_assembly(&plugin_path).unwrap();
+    assert!(runtime.write().add_assembly(&plugin_path).is_err());
+    let foo: StructRef = invoke_fn!(runtime, "foo_new").unwrap();
+    assert_eq!(foo.get::<i32>("a"), Ok(7));
+
+    // Assemblies cannot be unloaded while other assemblies depend on them
+    assert!(runtime.write().remove_assembly(&math_path).is_err());
+
+    // Objects of unloaded types remain accessible from the host
+    runtime.write().remove_assembly(&plugin_path).unwrap();
+    assert!(runtime.write().remove_assembly(&plugin_path).is_err());
+    let result: Result<StructRef, _> = invoke_fn!(runtime, "foo_new");
+    assert!(result.is_err());
+    assert_eq!(foo.get::<i32>("a"), Ok(7));
+    let result: i32 = invoke_fn!(runtime, "main").unwrap();
+    assert_eq!(result, 5);
+
+    // Once no assembly depends on it anymore, the dependency can be unloaded
+    assert!(runtime.write().remove_assembly(&math_path).is_err());
+    runtime.write().remove_assembly(&assembly_path).unwrap();
+    runtime.write().remove_assembly(&math_path).unwrap();
+}
+
+#[test]
+fn load_unlinkable_assembly() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let math = write_package(
+        &temp_dir.path().join("math"),
+        "[package]\nname = \"math\"\n",
+        "pub fn add(a: i32, b: i32) -> i32 { a + b }",
+    );
+    let util = write_package(
+        &temp_dir.path().join("util"),
+        "[package]\nname = \"util\"\n",
+        "pub fn one() -> i32 { 1 }",
+    );
+    let plugin = write_package(
+        &temp_dir.path().join("plugin"),
+        "[package]\nname = \"plugin\"\n\n[dependencies]\nmath = { path = \"../math\" }\nutil = { path = \"../util\" }\n",
+        "extern fn missing() -> i32;\n\npub fn main() -> i32 { add(one(), missing()) }",
+    );
+
+    let math_path = compile_package(&math, config(&math))
+        .unwrap()
+        .expect("compiler errors");
+    let plugin_path = compile_package(&plugin, config(&plugin))
+        .unwrap()
+        .expect("compiler errors");
+
+    let runtime = RuntimeBuilder::new(&math_path).spawn().unwrap();
+    assert!(runtime.write().add_assembly(&plugin_path).is_err());
+
+    // The dependency that was loaded for the plugin is unloaded again
+    let error = runtime
+        .write()
+        .remove_assembly(util.assembly_path())
+        .unwrap_err();
+    assert!(error.to_string().contains("is not loaded"));
+
+    // The plugin does not keep its dependencies from being unloaded
+    runtime.write().remove_assembly(&math_path).unwrap();
+}
+
+#[test]
+fn load_unlinkable_assembly_unwatches_dependencies() {
+    use mun_runtime::ChangeSource;
+    use std::path::PathBuf;
+    use std::sync::{Arc, Mutex};
+
+    /// Records the libraries that are watched
+    struct RecordingSource(Arc<Mutex<Vec<PathBuf>>>);
+
+    impl ChangeSource for RecordingSource {
+        fn watch(&mut self, library_path: &Path) -> Result<(), failure::Error> {
+            self.0.lock().unwrap().push(library_path.to_path_buf());
+            Ok(())
+        }
+
+        fn unwatch(&mut self, library_path: &Path) -> Result<(), failure::Error> {
+            let mut watched = self.0.lock().unwrap();
+            let index = watched
+                .iter()
+                .position(|path| path == library_path)
+                .expect("unwatched a library that is not watched");
+            watched.remove(index);
+            Ok(())
+        }
+
+        fn poll_changes(&mut self) -> Vec<PathBuf> {
+            Vec::new()
+        }
+    }
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let math = write_package(
+        &temp_dir.path().join("math"),
+        "[package]\nname = \"math\"\n",
+        "pub fn add(a: i32, b: i32) -> i32 { a + b }",
+    );
+    write_package(
+        &temp_dir.path().join("util"),
+        "[package]\nname = \"util\"\n",
+        "pub fn one() -> i32 { 1 }",
+    );
+    let plugin = write_package(
+        &temp_dir.path().join("plugin"),
+        "[package]\nname = \"plugin\"\n\n[dependencies]\nmath = { path = \"../math\" }\nutil = { path = \"../util\" }\n",
+        "extern fn missing() -> i32;\n\npub fn main() -> i32 { add(one(), missing()) }",
+    );
+
+    let math_path = compile_package(&math, config(&math))
+        .unwrap()
+        .expect("compiler errors");
+    let plugin_path = compile_package(&plugin, config(&plugin))
+        .unwrap()
+        .expect("compiler errors");
+
+    let watched = Arc::new(Mutex::new(Vec::new()));
+    let runtime = RuntimeBuilder::new(&math_path)
+        .set_watch_files(false)
+        .add_change_source(RecordingSource(watched.clone()))
+        .spawn()
+        .unwrap();
+    assert!(runtime.write().add_assembly(&plugin_path).is_err());
+
+    // Only the library that remains loaded is still watched
+    assert_eq!(
+        *watched.lock().unwrap(),
+        vec![math_path.canonicalize().unwrap()]
+    );
+}
+
+#[test]
+fn cyclic_dependency() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    write_package(
+        &temp_dir.path().join("a"),
+        "[package]\nname = \"a\"\n\n[dependencies]\nb = { path = \"../b\" }\n",
+        "pub fn a() {}",
+    );
+    let b = write_package(
+        &temp_dir.path().join("b"),
+        "[package]\nname = \"b\"\n\n[dependencies]\na = { path = \"../a\" }\n",
+        "pub fn b() {}",
+    );
+    assert!(compile_package(&b, config(&b)).is_err());
+}
+
+#[test]
+fn call_between_files_of_a_package() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let app = write_package(
+        &temp_dir.path().join("app"),
+        "[package]\nname = \"app\"\n",
+        "pub fn main() -> i32 { add(2, 3) }\n\nfn helper() -> i32 { sum(Pair { a: 1, b: add(1, 1) }) * 10 }\n\npub fn via_private() -> i32 { helper() }",
+    );
+    // Private functions of different files may have the same name
+    std::fs::write(
+        app.source_dir().join("util.mun"),
+        "struct Pair { a: i32, b: i32 }\n\nfn helper(a: i32) -> i32 { a }\n\npub fn add(a: i32, b: i32) -> i32 { helper(a) + b }\n\npub fn sum(pair: Pair) -> i32 { pair.a + pair.b }",
+    )
+    .unwrap();
+
+    let assembly_path = compile_package(&app, config(&app))
+        .unwrap()
+        .expect("compiler errors");
+
+    let runtime = RuntimeBuilder::new(&assembly_path).spawn().unwrap();
+    let result: i32 = invoke_fn!(runtime, "main").unwrap();
+    assert_eq!(result, 5);
+    let result: i32 = invoke_fn!(runtime, "via_private").unwrap();
+    assert_eq!(result, 30);
+}
+
+#[test]
+fn duplicate_public_function_in_package() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let app = write_package(
+        &temp_dir.path().join("app"),
+        "[package]\nname = \"app\"\n",
+        "pub fn add(a: i32, b: i32) -> i32 { a + b }",
+    );
+    std::fs::write(
+        app.source_dir().join("util.mun"),
+        "pub fn add(a: i32, b: i32) -> i32 { a * b }",
+    )
+    .unwrap();
+
+    assert!(compile_package(&app, config(&app)).unwrap().is_none());
+}
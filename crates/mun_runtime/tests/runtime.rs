@@ -1,6 +1,8 @@
 mod util;
 
-use std::io;
+use mun_runtime::{invoke_fn, Runtime, StructRef};
+use parking_lot::RwLock;
+use std::{io, sync::Arc, thread};
 use util::*;
 
 #[test]
@@ -26,6 +28,24 @@ fn error_assembly_not_linkable() {
     );
 }
 
+#[test]
+fn load_from_memory() {
+    let options =
+        mun_compiler::CompilerOptions::with_file("in_memory.mun", "pub fn main() -> i32 { 5 }");
+    let bytes = mun_compiler::compile_to_memory(options).unwrap();
+    assert!(!std::path::Path::new("in_memory.munlib").exists());
+
+    let runtime = mun_runtime::RuntimeBuilder::from_bytes("in_memory.munlib", bytes)
+        .spawn()
+        .unwrap();
+    let result: i32 = mun_runtime::invoke_fn!(runtime, "main").unwrap();
+    assert_eq!(result, 5);
+
+    let options =
+        mun_compiler::CompilerOptions::with_file("in_memory.mun", "pub fn main() -> i32 { true }");
+    assert!(mun_compiler::compile_to_memory(options).is_err());
+}
+
 #[test]
 fn arg_missing_bug() {
     let context = codegen::Context::create();
@@ -51,3 +71,108 @@ fn arg_missing_bug() {
 
     driver.spawn().unwrap()
 }
+
+#[test]
+fn invoke_from_multiple_threads() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Arc<RwLock<Runtime>>>();
+    assert_send_sync::<StructRef>();
+
+    let context = codegen::Context::create();
+    let mut driver = TestDriver::new(
+        &context,
+        r"
+    pub fn fibonacci(n: i64) -> i64 {
+        if n <= 1 {
+            n
+        } else {
+            fibonacci(n - 1) + fibonacci(n - 2)
+        }
+    }
+
+    struct Foo {
+        a: i64,
+    }
+
+    pub fn new_foo(a: i64) -> Foo {
+        Foo { a: a }
+    }",
+    );
+    let runtime = driver.runtime_mut().clone();
+
+    let threads = (0..4)
+        .map(|i| {
+            let runtime = runtime.clone();
+            thread::spawn(move || {
+                for n in 0..20i64 {
+                    let result: i64 = invoke_fn!(runtime, "fibonacci", 10i64).unwrap();
+                    assert_eq!(result, 55);
+
+                    let foo: StructRef = invoke_fn!(runtime, "new_foo", i * 100 + n).unwrap();
+                    assert_eq!(foo.get::<i64>("a").unwrap(), i * 100 + n);
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+
+    // Garbage collection waits for in-flight invocations
+    for _ in 0..10 {
+        runtime.write().gc_collect();
+    }
+
+    for thread in threads {
+        thread.join().unwrap();
+    }
+
+    runtime.write().gc_collect();
+    assert_eq!(runtime.read().gc_stats().allocated_memory, 0);
+}
+
+#[test]
+fn get_and_set_fields_from_multiple_threads() {
+    let context = codegen::Context::create();
+    let mut driver = TestDriver::new(
+        &context,
+        r"
+    struct(value) Bar { x: i64, y: i64 }
+    struct(gc) Foo { a: i64, bar: Bar }
+
+    pub fn new_bar(v: i64) -> Bar {
+        Bar { x: v, y: v }
+    }
+
+    pub fn new_foo() -> Foo {
+        Foo { a: 0, bar: Bar { x: 0, y: 0 } }
+    }",
+    );
+    let runtime = driver.runtime_mut().clone();
+    let foo: StructRef = invoke_fn!(runtime, "new_foo").unwrap();
+
+    let threads = (0..4)
+        .map(|i| {
+            let runtime = runtime.clone();
+            let mut foo = foo.clone();
+            thread::spawn(move || {
+                for n in 0..100i64 {
+                    let v = i * 1000 + n;
+                    let bar: StructRef = invoke_fn!(runtime, "new_bar", v).unwrap();
+                    foo.set("bar", bar).unwrap();
+                    foo.set("a", v).unwrap();
+
+                    // Values written by other threads are never observed partially
+                    let bar = foo.get::<StructRef>("bar").unwrap();
+                    assert_eq!(bar.get::<i64>("x").unwrap(), bar.get::<i64>("y").unwrap());
+                    let a = foo.get::<i64>("a").unwrap();
+                    assert!(a / 1000 < 4 && a % 1000 < 100);
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+
+    for thread in threads {
+        thread.join().unwrap();
+    }
+
+    let bar = foo.get::<StructRef>("bar").unwrap();
+    assert_eq!(bar.get::<i64>("x").unwrap(), bar.get::<i64>("y").unwrap());
+}
@@ -2,9 +2,18 @@
 
 use codegen::Context;
 use mun_compiler::{Config, DisplayColor, Driver, FileId, PathOrInline, RelativePathBuf};
-use mun_runtime::{IntoFunctionDefinition, Runtime, RuntimeBuilder};
+use mun_runtime::{
+    ChangeSource, FieldRename, IntoFunctionDefinition, MigrationPolicy, Runtime, RuntimeBuilder,
+    StructView,
+};
+use parking_lot::RwLock;
 use std::io::Cursor;
-use std::{cell::RefCell, path::PathBuf, rc::Rc, thread::sleep, time::Duration};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    thread::sleep,
+    time::Duration,
+};
 
 /// Implements a compiler and runtime in one that can invoke functions. Use of the TestDriver
 /// enables quick testing of Mun constructs in the runtime with hot-reloading support.
@@ -17,7 +26,7 @@ pub(crate) struct TestDriver<'a> {
 }
 
 enum RuntimeOrBuilder {
-    Runtime(Rc<RefCell<Runtime>>),
+    Runtime(Arc<RwLock<Runtime>>),
     Builder(RuntimeBuilder),
     Pending,
 }
@@ -38,10 +47,17 @@ impl RuntimeOrBuilder {
 impl<'a> TestDriver<'a> {
     /// Construct a new TestDriver from a single Mun source
     pub fn new(context: &'a Context, text: &str) -> Self {
+        Self::with_debug_info(context, text, false)
+    }
+
+    /// Construct a new TestDriver from a single Mun source, optionally generating debug
+    /// information for the assembly.
+    pub fn with_debug_info(context: &'a Context, text: &str, debug_info: bool) -> Self {
         let temp_dir = tempfile::TempDir::new().unwrap();
         let config = Config {
             out_dir: Some(temp_dir.path().to_path_buf()),
             display_color: DisplayColor::Disable,
+            debug_info,
             ..Config::default()
         };
         let input = PathOrInline::Inline {
@@ -76,8 +92,28 @@ impl<'a> TestDriver<'a> {
         self.runtime.spawn().map(|_| ())
     }
 
+    /// Returns the path of the generated assembly.
+    pub fn out_path(&self) -> &Path {
+        &self.out_path
+    }
+
     /// Updates the text of the Mun source and ensures that the generated assembly has been reloaded.
     pub fn update(&mut self, context: &'a Context, text: &str) {
+        self.recompile(context, text);
+        let start_time = std::time::Instant::now();
+        while !self.runtime_mut().write().update() {
+            let now = std::time::Instant::now();
+            if now - start_time > std::time::Duration::from_secs(10) {
+                panic!("runtime did not update after recompilation within 10secs");
+            } else {
+                sleep(Duration::from_millis(1));
+            }
+        }
+    }
+
+    /// Updates the text of the Mun source and regenerates the assembly, without waiting for the
+    /// runtime to reload it.
+    pub fn recompile(&mut self, context: &'a Context, text: &str) {
         self.runtime_mut(); // Ensures that the runtime is spawned prior to the update
         self.driver.set_file_text(self.file_id, text);
         let mut compiler_errors: Vec<u8> = Vec::new();
@@ -97,15 +133,6 @@ impl<'a> TestDriver<'a> {
             &out_path, &self.out_path,
             "recompiling did not result in the same assembly"
         );
-        let start_time = std::time::Instant::now();
-        while !self.runtime_mut().borrow_mut().update() {
-            let now = std::time::Instant::now();
-            if now - start_time > std::time::Duration::from_secs(10) {
-                panic!("runtime did not update after recompilation within 10secs");
-            } else {
-                sleep(Duration::from_millis(1));
-            }
-        }
     }
 
     /// Adds a custom user function to the dispatch table.
@@ -119,8 +146,66 @@ impl<'a> TestDriver<'a> {
         self
     }
 
+    /// Adds a migration for objects of the struct type `type_name`.
+    pub fn migrate<S: Into<String>, F>(mut self, type_name: S, migration: F) -> Self
+    where
+        F: Fn(&StructView, &mut StructView) + Send + Sync + 'static,
+    {
+        self.runtime = match self.runtime {
+            RuntimeOrBuilder::Builder(builder) => {
+                RuntimeOrBuilder::Builder(builder.migrate(type_name, migration))
+            }
+            _ => unreachable!(),
+        };
+        self
+    }
+
+    /// Declares that a field of a struct type was renamed.
+    pub fn rename_field(mut self, rename: FieldRename) -> Self {
+        self.runtime = match self.runtime {
+            RuntimeOrBuilder::Builder(builder) => {
+                RuntimeOrBuilder::Builder(builder.rename_field(rename))
+            }
+            _ => unreachable!(),
+        };
+        self
+    }
+
+    /// Sets the `policy` for fields that cannot be migrated without loss of information.
+    pub fn set_migration_policy(mut self, policy: MigrationPolicy) -> Self {
+        self.runtime = match self.runtime {
+            RuntimeOrBuilder::Builder(builder) => {
+                RuntimeOrBuilder::Builder(builder.set_migration_policy(policy))
+            }
+            _ => unreachable!(),
+        };
+        self
+    }
+
+    /// Sets whether the generated assembly is watched on the file system.
+    pub fn set_watch_files(mut self, watch_files: bool) -> Self {
+        self.runtime = match self.runtime {
+            RuntimeOrBuilder::Builder(builder) => {
+                RuntimeOrBuilder::Builder(builder.set_watch_files(watch_files))
+            }
+            _ => unreachable!(),
+        };
+        self
+    }
+
+    /// Adds a `source` of changes to the generated assembly.
+    pub fn add_change_source<S: ChangeSource + 'static>(mut self, source: S) -> Self {
+        self.runtime = match self.runtime {
+            RuntimeOrBuilder::Builder(builder) => {
+                RuntimeOrBuilder::Builder(builder.add_change_source(source))
+            }
+            _ => unreachable!(),
+        };
+        self
+    }
+
     /// Returns the `Runtime` used by this instance
-    pub fn runtime_mut(&mut self) -> &mut Rc<RefCell<Runtime>> {
+    pub fn runtime_mut(&mut self) -> &mut Arc<RwLock<Runtime>> {
         self.runtime.spawn().unwrap();
         match &mut self.runtime {
             RuntimeOrBuilder::Runtime(r) => r,
@@ -154,7 +154,7 @@ pub unsafe extern "C" fn mun_gc_collect(
     handle: RuntimeHandle,
     reclaimed: *mut bool,
 ) -> ErrorHandle {
-    let runtime = match (handle.0 as *mut Runtime).as_ref() {
+    let runtime = match (handle.0 as *mut Runtime).as_mut() {
         Some(runtime) => runtime,
         None => {
             return HUB
@@ -7,6 +7,7 @@
 pub mod error;
 pub mod gc;
 pub mod hub;
+pub mod migration;
 
 #[cfg(test)]
 mod tests;
@@ -16,6 +17,7 @@ use std::{os::raw::c_char, time::Duration};
 
 use crate::error::ErrorHandle;
 use crate::hub::HUB;
+use crate::migration::{FieldRename, MigrationPolicy};
 use failure::err_msg;
 use runtime::Runtime;
 
@@ -49,6 +51,10 @@ pub struct RuntimeOptions {
     /// default.
     pub delay_ms: u32,
 
+    /// Whether to watch the loaded libraries on the file system and reload them when they change.
+    /// If disabled, assemblies are only reloaded through [`mun_runtime_reload_assembly`].
+    pub watch_files: bool,
+
     /// Function definitions that should be inserted in the runtime before a mun library is loaded.
     /// This is useful to initialize `extern` functions used in a mun library.
     ///
@@ -58,14 +64,32 @@ pub struct RuntimeOptions {
 
     /// The number of functions in the [`functions`] array.
     pub num_functions: u32,
+
+    /// The policy for fields of which the value cannot be migrated to their new type without loss
+    /// of information when hot reloading.
+    pub migration_policy: MigrationPolicy,
+
+    /// Fields of struct types that were renamed, of which the values are retained when hot
+    /// reloading.
+    ///
+    /// If the [`num_field_renames`] field is non-zero this field must contain a pointer to an
+    /// array of [`FieldRename`]s.
+    pub field_renames: *const FieldRename,
+
+    /// The number of renames in the [`field_renames`] array.
+    pub num_field_renames: u32,
 }
 
 impl Default for RuntimeOptions {
     fn default() -> Self {
         RuntimeOptions {
             delay_ms: 0,
+            watch_files: true,
             functions: std::ptr::null(),
             num_functions: 0,
+            migration_policy: MigrationPolicy::default(),
+            field_renames: std::ptr::null(),
+            num_field_renames: 0,
         }
     }
 }
@@ -100,6 +124,12 @@ pub unsafe extern "C" fn mun_runtime_create(
             .register(err_msg("Invalid argument: 'functions' is null pointer."));
     }
 
+    if options.num_field_renames > 0 && options.field_renames.is_null() {
+        return HUB.errors.register(err_msg(
+            "Invalid argument: 'field_renames' is null pointer.",
+        ));
+    }
+
     let library_path = match CStr::from_ptr(library_path).to_str() {
         Ok(path) => path,
         Err(_) => {
@@ -137,10 +167,27 @@ pub unsafe extern "C" fn mun_runtime_create(
             })
             .collect();
 
+    let mut migrations = runtime::Migrations::default();
+    migrations.set_policy(options.migration_policy.into());
+    if options.num_field_renames > 0 {
+        let field_renames =
+            std::slice::from_raw_parts(options.field_renames, options.num_field_renames as usize);
+        for rename in field_renames {
+            match rename.to_runtime() {
+                Ok(rename) => migrations.rename_field(rename),
+                Err(e) => return HUB.errors.register(e),
+            }
+        }
+    }
+
     let runtime_options = runtime::RuntimeOptions {
         library_path: library_path.into(),
+        library_bytes: None,
         delay: Duration::from_millis(delay_ms.into()),
+        watch_files: options.watch_files,
+        change_sources: Vec::new(),
         user_functions,
+        migrations,
     };
 
     let runtime = match Runtime::new(runtime_options) {
@@ -268,6 +315,53 @@ pub unsafe extern "C" fn mun_runtime_update(
     ErrorHandle::default()
 }
 
+/// Reloads the assembly corresponding to the library at `library_path` in the runtime
+/// corresponding to `handle`, irrespective of whether a change to the library was detected. If
+/// the assembly cannot be reloaded, a non-zero error handle is returned and the previous version
+/// of the assembly remains loaded.
+///
+/// If a non-zero error handle is returned, it must be manually destructed using
+/// [`mun_error_destroy`].
+///
+/// # Safety
+///
+/// This function receives raw pointers as parameters. If any of the arguments is a null pointer,
+/// an error will be returned. Passing pointers to invalid data, will lead to undefined behavior.
+#[no_mangle]
+pub unsafe extern "C" fn mun_runtime_reload_assembly(
+    handle: RuntimeHandle,
+    library_path: *const c_char,
+) -> ErrorHandle {
+    let runtime = match (handle.0 as *mut Runtime).as_mut() {
+        Some(runtime) => runtime,
+        None => {
+            return HUB
+                .errors
+                .register(err_msg("Invalid argument: 'runtime' is null pointer."))
+        }
+    };
+
+    if library_path.is_null() {
+        return HUB
+            .errors
+            .register(err_msg("Invalid argument: 'library_path' is null pointer."));
+    }
+
+    let library_path = match CStr::from_ptr(library_path).to_str() {
+        Ok(path) => path,
+        Err(_) => {
+            return HUB.errors.register(err_msg(
+                "Invalid argument: 'library_path' is not UTF-8 encoded.",
+            ))
+        }
+    };
+
+    match runtime.reload_assembly(library_path) {
+        Ok(()) => ErrorHandle::default(),
+        Err(e) => HUB.errors.register(e),
+    }
+}
+
 /// Deallocates a string that was allocated by the runtime.
 ///
 /// # Safety
@@ -0,0 +1,92 @@
+//! Exposes the configuration of struct migrations using the C ABI.
+
+use failure::err_msg;
+use std::ffi::CStr;
+use std::os::raw::c_char;
+
+/// Determines how the value of a field is migrated when hot reloading, if the field's type changes
+/// to a primitive type that cannot represent all values of its old type.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MigrationPolicy {
+    /// The fields are reset to zero.
+    Reset,
+    /// Lossy conversions are not allowed, so the reload fails.
+    Strict,
+    /// The values are converted lossily, discarding the most significant bits of integers that do
+    /// not fit the new type.
+    LossyTruncate,
+    /// The values are converted lossily, clamping integers to the minimum or maximum value of the
+    /// new type.
+    LossySaturate,
+}
+
+impl Default for MigrationPolicy {
+    fn default() -> Self {
+        MigrationPolicy::Reset
+    }
+}
+
+impl From<MigrationPolicy> for runtime::MigrationPolicy {
+    fn from(policy: MigrationPolicy) -> Self {
+        match policy {
+            MigrationPolicy::Reset => runtime::MigrationPolicy::Reset,
+            MigrationPolicy::Strict => runtime::MigrationPolicy::Strict,
+            MigrationPolicy::LossyTruncate => {
+                runtime::MigrationPolicy::Lossy(runtime::IntegerNarrowing::Truncate)
+            }
+            MigrationPolicy::LossySaturate => {
+                runtime::MigrationPolicy::Lossy(runtime::IntegerNarrowing::Saturate)
+            }
+        }
+    }
+}
+
+/// A field of a struct type that was renamed, of which the value is retained when hot reloading.
+///
+/// # Safety
+///
+/// This struct contains raw pointers as parameters. Passing pointers to invalid data, will lead to
+/// undefined behavior.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct FieldRename {
+    /// The name of the struct type
+    pub type_name: *const c_char,
+    /// The previous name of the field
+    pub old_name: *const c_char,
+    /// The new name of the field
+    pub new_name: *const c_char,
+}
+
+impl FieldRename {
+    /// Converts the rename to its runtime equivalent, or returns an error if any of its names is a
+    /// null pointer or not UTF-8 encoded.
+    pub(crate) unsafe fn to_runtime(&self) -> Result<runtime::FieldRename, failure::Error> {
+        Ok(runtime::FieldRename {
+            type_name: to_string(self.type_name, "type_name")?,
+            old_name: to_string(self.old_name, "old_name")?,
+            new_name: to_string(self.new_name, "new_name")?,
+        })
+    }
+}
+
+/// Copies the string at `ptr`, of which the argument is called `name` in error messages.
+unsafe fn to_string(ptr: *const c_char, name: &str) -> Result<String, failure::Error> {
+    if ptr.is_null() {
+        return Err(err_msg(format!(
+            "Invalid argument: '{}' is null pointer.",
+            name
+        )));
+    }
+
+    CStr::from_ptr(ptr)
+        .to_str()
+        .map(ToOwned::to_owned)
+        .map_err(|_| {
+            err_msg(format!(
+                "Invalid argument: '{}' is not UTF-8 encoded.",
+                name
+            ))
+        })
+}
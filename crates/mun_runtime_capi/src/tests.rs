@@ -1,4 +1,4 @@
-use crate::{error::*, gc::*, *};
+use crate::{error::*, gc::*, migration::*, *};
 use compiler::{Config, Driver, PathOrInline, RelativePathBuf};
 use memory::gc::{GcPtr, HasIndirectionPtr, RawGcPtr};
 use runtime::UnsafeTypeInfo;
@@ -6,7 +6,7 @@ use std::{
     ffi::CString,
     io::stderr,
     mem::{self, MaybeUninit},
-    path::Path,
+    path::{Path, PathBuf},
     ptr::{self, NonNull},
 };
 
@@ -14,12 +14,19 @@ use std::{
 /// constructs in the runtime with hot-reloading support.
 struct TestDriver {
     _temp_dir: tempfile::TempDir,
+    out_path: PathBuf,
     runtime: RuntimeHandle,
 }
 
 impl TestDriver {
     /// Constructs a new `TestDriver` from Mun source
     fn new(context: &codegen::Context, text: &str) -> Self {
+        Self::with_options(context, text, RuntimeOptions::default())
+    }
+
+    /// Constructs a new `TestDriver` from Mun source, of which the runtime is created with
+    /// `options`
+    fn with_options(context: &codegen::Context, text: &str, options: RuntimeOptions) -> Self {
         let temp_dir = tempfile::TempDir::new().unwrap();
         let config = Config {
             out_dir: Some(temp_dir.path().to_path_buf()),
@@ -34,9 +41,10 @@ impl TestDriver {
             panic!("compiler errors..")
         }
         let out_path = driver.write_assembly(context, file_id).unwrap();
-        let runtime = make_runtime(&out_path);
+        let runtime = make_runtime(&out_path, options);
         TestDriver {
             _temp_dir: temp_dir,
+            out_path,
             runtime,
         }
     }
@@ -48,18 +56,12 @@ impl Drop for TestDriver {
     }
 }
 
-fn make_runtime(lib_path: &Path) -> RuntimeHandle {
+fn make_runtime(lib_path: &Path, options: RuntimeOptions) -> RuntimeHandle {
     let lib_path = lib_path.to_str().expect("Invalid lib path");
     let lib_path = CString::new(lib_path).unwrap();
 
     let mut handle = RuntimeHandle(ptr::null_mut());
-    let error = unsafe {
-        mun_runtime_create(
-            lib_path.as_ptr(),
-            RuntimeOptions::default(),
-            &mut handle as *mut _,
-        )
-    };
+    let error = unsafe { mun_runtime_create(lib_path.as_ptr(), options, &mut handle as *mut _) };
     assert_eq!(error.token(), 0, "Failed to create runtime");
     handle
 }
@@ -92,6 +94,7 @@ macro_rules! test_invalid_runtime {
 test_invalid_runtime!(
     runtime_get_function_definition(ptr::null(), ptr::null_mut(), ptr::null_mut()),
     runtime_update(ptr::null_mut()),
+    runtime_reload_assembly(ptr::null()),
     gc_alloc(UnsafeTypeInfo::new(NonNull::dangling()), ptr::null_mut()),
     gc_ptr_type(mem::zeroed::<GcPtr>(), ptr::null_mut()),
     gc_root(mem::zeroed::<GcPtr>()),
@@ -158,6 +161,57 @@ fn test_runtime_create_invalid_handle() {
     unsafe { mun_destroy_string(message.as_ptr()) };
 }
 
+#[test]
+fn test_runtime_create_invalid_field_renames() {
+    let lib_path = CString::new("some/path").expect("Invalid library path");
+
+    let options = RuntimeOptions {
+        num_field_renames: 1,
+        ..RuntimeOptions::default()
+    };
+    let mut runtime = RuntimeHandle(ptr::null_mut());
+    let handle = unsafe { mun_runtime_create(lib_path.as_ptr(), options, &mut runtime as *mut _) };
+    assert_ne!(handle.token(), 0);
+
+    let message = unsafe { CStr::from_ptr(mun_error_message(handle)) };
+    assert_eq!(
+        message.to_str().unwrap(),
+        "Invalid argument: 'field_renames' is null pointer."
+    );
+
+    unsafe { mun_destroy_string(message.as_ptr()) };
+}
+
+#[test]
+fn test_runtime_create_invalid_field_rename_type_name() {
+    let lib_path = CString::new("some/path").expect("Invalid library path");
+    let old_name = CString::new("hp").expect("Invalid field name");
+    let new_name = CString::new("health").expect("Invalid field name");
+
+    let field_renames = [FieldRename {
+        type_name: ptr::null(),
+        old_name: old_name.as_ptr(),
+        new_name: new_name.as_ptr(),
+    }];
+    let options = RuntimeOptions {
+        migration_policy: MigrationPolicy::Strict,
+        field_renames: field_renames.as_ptr(),
+        num_field_renames: field_renames.len() as u32,
+        ..RuntimeOptions::default()
+    };
+    let mut runtime = RuntimeHandle(ptr::null_mut());
+    let handle = unsafe { mun_runtime_create(lib_path.as_ptr(), options, &mut runtime as *mut _) };
+    assert_ne!(handle.token(), 0);
+
+    let message = unsafe { CStr::from_ptr(mun_error_message(handle)) };
+    assert_eq!(
+        message.to_str().unwrap(),
+        "Invalid argument: 'type_name' is null pointer."
+    );
+
+    unsafe { mun_destroy_string(message.as_ptr()) };
+}
+
 #[test]
 fn test_runtime_get_function_info_invalid_fn_name() {
     let context = codegen::Context::create();
@@ -329,6 +383,67 @@ fn test_runtime_update() {
     assert_eq!(handle.token(), 0);
 }
 
+#[test]
+fn test_runtime_reload_assembly_invalid_library_path() {
+    let context = codegen::Context::create();
+    let driver = TestDriver::new(&context,
+        r#"
+        pub fn main() -> i32 { 3 }
+    "#,
+    );
+
+    let handle = unsafe { mun_runtime_reload_assembly(driver.runtime, ptr::null()) };
+
+    let message = unsafe { CStr::from_ptr(mun_error_message(handle)) };
+    assert_eq!(
+        message.to_str().unwrap(),
+        "Invalid argument: 'library_path' is null pointer."
+    );
+
+    unsafe { mun_destroy_string(message.as_ptr()) };
+}
+
+#[test]
+fn test_runtime_reload_assembly_invalid_library_path_encoding() {
+    let context = codegen::Context::create();
+    let driver = TestDriver::new(&context,
+        r#"
+        pub fn main() -> i32 { 3 }
+    "#,
+    );
+
+    let invalid_encoding = ['�', '\0'];
+    let handle = unsafe {
+        mun_runtime_reload_assembly(driver.runtime, invalid_encoding.as_ptr() as *const _)
+    };
+
+    let message = unsafe { CStr::from_ptr(mun_error_message(handle)) };
+    assert_eq!(
+        message.to_str().unwrap(),
+        "Invalid argument: 'library_path' is not UTF-8 encoded."
+    );
+
+    unsafe { mun_destroy_string(message.as_ptr()) };
+}
+
+#[test]
+fn test_runtime_reload_assembly() {
+    let context = codegen::Context::create();
+    let driver = TestDriver::with_options(&context,
+        r#"
+        pub fn main() -> i32 { 3 }
+    "#,
+        RuntimeOptions {
+            watch_files: false,
+            ..RuntimeOptions::default()
+        },
+    );
+
+    let library_path = CString::new(driver.out_path.to_str().unwrap()).unwrap();
+    let handle = unsafe { mun_runtime_reload_assembly(driver.runtime, library_path.as_ptr()) };
+    assert_eq!(handle.token(), 0);
+}
+
 #[test]
 fn test_gc_alloc_invalid_obj() {
     let context = codegen::Context::create();
@@ -46,6 +46,72 @@ impl ArgList {
     }
 }
 
+// Attr
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Attr {
+    pub(crate) syntax: SyntaxNode,
+}
+
+impl AstNode for Attr {
+    fn can_cast(kind: SyntaxKind) -> bool {
+        match kind {
+            ATTR => true,
+            _ => false,
+        }
+    }
+    fn cast(syntax: SyntaxNode) -> Option<Self> {
+        if Self::can_cast(syntax.kind()) {
+            Some(Attr { syntax })
+        } else {
+            None
+        }
+    }
+    fn syntax(&self) -> &SyntaxNode {
+        &self.syntax
+    }
+}
+impl Attr {
+    pub fn name_ref(&self) -> Option<NameRef> {
+        super::child_opt(self)
+    }
+
+    pub fn attr_arg_list(&self) -> Option<AttrArgList> {
+        super::child_opt(self)
+    }
+}
+
+// AttrArgList
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AttrArgList {
+    pub(crate) syntax: SyntaxNode,
+}
+
+impl AstNode for AttrArgList {
+    fn can_cast(kind: SyntaxKind) -> bool {
+        match kind {
+            ATTR_ARG_LIST => true,
+            _ => false,
+        }
+    }
+    fn cast(syntax: SyntaxNode) -> Option<Self> {
+        if Self::can_cast(syntax.kind()) {
+            Some(AttrArgList { syntax })
+        } else {
+            None
+        }
+    }
+    fn syntax(&self) -> &SyntaxNode {
+        &self.syntax
+    }
+}
+impl AttrArgList {
+    pub fn args(&self) -> impl Iterator<Item = NameRef> {
+        super::children(self)
+    }
+}
+
 // BinExpr
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -1170,6 +1236,7 @@ impl ast::NameOwner for RecordFieldDef {}
 impl ast::VisibilityOwner for RecordFieldDef {}
 impl ast::DocCommentsOwner for RecordFieldDef {}
 impl ast::TypeAscriptionOwner for RecordFieldDef {}
+impl ast::AttrsOwner for RecordFieldDef {}
 impl RecordFieldDef {}
 
 // RecordFieldDefList
@@ -44,6 +44,12 @@ pub trait ArgListOwner: AstNode {
     }
 }
 
+pub trait AttrsOwner: AstNode {
+    fn attrs(&self) -> AstChildren<ast::Attr> {
+        children(self)
+    }
+}
+
 pub trait DocCommentsOwner: AstNode {
     fn doc_comments(&self) -> CommentIter {
         CommentIter {
@@ -17,6 +17,7 @@ mod parsing;
 mod ptr;
 mod syntax_error;
 mod syntax_node;
+mod text_edit;
 
 #[cfg(test)]
 mod tests;
@@ -30,6 +31,7 @@ pub use crate::{
     syntax_error::{SyntaxError, SyntaxErrorKind},
     syntax_kind::SyntaxKind,
     syntax_node::{Direction, SyntaxElement, SyntaxNode, SyntaxToken, SyntaxTreeBuilder},
+    text_edit::TextEdit,
 };
 pub use rowan::{SmolStr, TextRange, TextUnit};
 
@@ -117,6 +119,24 @@ impl Parse<SourceFile> {
         }
         buf
     }
+
+    /// Returns the result of parsing the text after applying `edit`. Only the token or the smallest
+    /// block that encloses the edit is relexed and reparsed, the rest of the tree is reused. If that
+    /// is not possible, the whole text is parsed again.
+    pub fn reparse(&self, edit: &TextEdit) -> Parse<SourceFile> {
+        self.incremental_reparse(edit)
+            .unwrap_or_else(|| self.full_reparse(edit))
+    }
+
+    fn incremental_reparse(&self, edit: &TextEdit) -> Option<Parse<SourceFile>> {
+        parsing::incremental_reparse(&self.syntax_node(), edit, self.errors.to_vec())
+            .map(|(green, errors, _reparsed_range)| Parse::new(green, errors))
+    }
+
+    fn full_reparse(&self, edit: &TextEdit) -> Parse<SourceFile> {
+        let text = edit.apply(&self.syntax_node().text().to_string());
+        SourceFile::parse(&text)
+    }
 }
 
 /// `SourceFile` represents a parse tree for a single Mun file.
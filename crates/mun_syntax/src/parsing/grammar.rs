@@ -1,4 +1,5 @@
 mod adt;
+mod attributes;
 mod declarations;
 mod expressions;
 mod params;
@@ -30,6 +31,17 @@ pub(crate) fn root(p: &mut Parser) {
     m.complete(p, SOURCE_FILE);
 }
 
+/// Returns the grammar function that parses a node of the given kind in isolation, if such a node
+/// can be reparsed without its surrounding context.
+pub(crate) fn reparser(node: SyntaxKind) -> Option<fn(&mut Parser)> {
+    let res = match node {
+        BLOCK_EXPR => expressions::block,
+        RECORD_FIELD_DEF_LIST => adt::record_field_def_list,
+        _ => return None,
+    };
+    Some(res)
+}
+
 //pub(crate) fn pattern(p: &mut Parser) {
 //    patterns::pattern(p)
 //}
@@ -0,0 +1,39 @@
+use super::*;
+
+pub(super) fn outer_attributes(p: &mut Parser) {
+    while p.at(T![#]) {
+        attribute(p);
+    }
+}
+
+fn attribute(p: &mut Parser) {
+    assert!(p.at(T![#]));
+    let m = p.start();
+    p.bump(T![#]);
+    if p.expect(T!['[']) {
+        name_ref(p);
+        if p.at(T!['(']) {
+            attr_arg_list(p);
+        }
+        p.expect(T![']']);
+    }
+    m.complete(p, ATTR);
+}
+
+fn attr_arg_list(p: &mut Parser) {
+    assert!(p.at(T!['(']));
+    let m = p.start();
+    p.bump(T!['(']);
+    while !p.at(T![')']) && !p.at(EOF) {
+        if !p.at(IDENT) {
+            p.error_and_bump("expected an identifier");
+            break;
+        }
+        name_ref(p);
+        if !p.at(T![')']) {
+            p.expect(T![,]);
+        }
+    }
+    p.expect(T![')']);
+    m.complete(p, ATTR_ARG_LIST);
+}
@@ -0,0 +1,202 @@
+//! Implementation of incremental reparsing.
+//!
+//! An edit is first applied to the token that covers it, which only works if the edited text still
+//! lexes to a single token of the same kind. Otherwise the smallest enclosing block is relexed and
+//! reparsed. If neither is possible, the caller has to parse the whole file again.
+
+use super::{
+    grammar,
+    lexer::{tokenize, Token},
+    parse_from_tokens,
+    parser::Parser,
+    text_token_source::TextTokenSource,
+    text_tree_sink::TextTreeSink,
+};
+use crate::{
+    syntax_node::{GreenNode, MunLanguage, NodeOrToken, SyntaxNode},
+    SyntaxError,
+    SyntaxKind::*,
+    TextEdit, TextRange, TextUnit,
+};
+use rowan::{GreenToken, Language};
+
+/// Applies `edit` to the tree of `root` by reparsing only the part of the tree that is affected by
+/// the edit. Returns the new tree, its errors and the range of the old tree that was reparsed, or
+/// `None` if the edit cannot be applied incrementally.
+pub(crate) fn incremental_reparse(
+    root: &SyntaxNode,
+    edit: &TextEdit,
+    errors: Vec<SyntaxError>,
+) -> Option<(GreenNode, Vec<SyntaxError>, TextRange)> {
+    if let Some((green, old_range)) = reparse_token(root, edit) {
+        return Some((
+            green,
+            merge_errors(errors, Vec::new(), old_range, edit),
+            old_range,
+        ));
+    }
+
+    if let Some((green, new_errors, old_range)) = reparse_block(root, edit) {
+        return Some((
+            green,
+            merge_errors(errors, new_errors, old_range, edit),
+            old_range,
+        ));
+    }
+
+    None
+}
+
+/// Tries to replace the token that covers the edit with a relexed token.
+fn reparse_token(root: &SyntaxNode, edit: &TextEdit) -> Option<(GreenNode, TextRange)> {
+    let token = match root.covering_element(edit.delete) {
+        NodeOrToken::Token(token) => token,
+        NodeOrToken::Node(_) => return None,
+    };
+    match token.kind() {
+        WHITESPACE | COMMENT | IDENT | STRING => (),
+        _ => return None,
+    }
+
+    let text = text_after_edit(token.text_range(), token.text(), edit);
+    match tokenize(&text)[..] {
+        [new_token] if new_token.kind == token.kind() => (),
+        _ => return None,
+    }
+
+    // An empty line between a comment and a function detaches the comment from the function
+    if token.kind() == WHITESPACE && token.text().contains("\n\n") != text.contains("\n\n") {
+        return None;
+    }
+
+    // Contextual keywords are remapped by the parser
+    if token.kind() == IDENT && is_contextual_kw(&text) {
+        return None;
+    }
+
+    // The edited token must not merge with its neighbours
+    if let Some(next_char) = token.next_token().and_then(|it| it.text().chars().next()) {
+        if tokenize(&format!("{}{}", text, next_char)).len() == 1 {
+            return None;
+        }
+    }
+    if let Some(prev_char) = token.prev_token().and_then(|it| it.text().chars().last()) {
+        let tokens = tokenize(&format!("{}{}", prev_char, text));
+        if tokens.last().map(|it| it.len) != Some(TextUnit::of_str(&text)) {
+            return None;
+        }
+    }
+
+    let new_token = GreenToken::new(MunLanguage::kind_to_raw(token.kind()), text.into());
+    Some((token.replace_with(new_token), token.text_range()))
+}
+
+/// Tries to reparse the smallest block that encloses the edit.
+fn reparse_block(
+    root: &SyntaxNode,
+    edit: &TextEdit,
+) -> Option<(GreenNode, Vec<SyntaxError>, TextRange)> {
+    let ancestors = match root.covering_element(edit.delete) {
+        NodeOrToken::Token(token) => token.parent().ancestors(),
+        NodeOrToken::Node(node) => node.ancestors(),
+    };
+    ancestors
+        .filter_map(|node| grammar::reparser(node.kind()).map(|reparser| (node, reparser)))
+        .find_map(|(node, reparser)| {
+            reparse_node(&node, reparser, edit)
+                .map(|(green, new_errors)| (green, new_errors, node.text_range()))
+        })
+}
+
+/// Reparses the text of `node` after applying `edit` with the grammar function `reparser`.
+fn reparse_node(
+    node: &SyntaxNode,
+    reparser: fn(&mut Parser),
+    edit: &TextEdit,
+) -> Option<(GreenNode, Vec<SyntaxError>)> {
+    let text = text_after_edit(node.text_range(), &node.text().to_string(), edit);
+    let tokens = tokenize(&text);
+    if !is_balanced(&tokens) {
+        return None;
+    }
+
+    let mut token_source = TextTokenSource::new(&text, &tokens);
+    let mut tree_sink = TextTreeSink::new(&text, &tokens);
+    parse_from_tokens(&mut token_source, &mut tree_sink, reparser);
+    let (green, new_errors) = tree_sink.finish();
+
+    // The closing brace has to end the reparsed node, otherwise the parser would have continued
+    // after it when parsing the whole file.
+    let new_node = SyntaxNode::new_root(green.clone());
+    if new_node.kind() != node.kind()
+        || new_node.text_range().len() != TextUnit::of_str(&text)
+        || new_node.last_child_or_token().map(|it| it.kind()) != Some(T!['}'])
+    {
+        return None;
+    }
+
+    Some((node.replace_with(green), new_errors))
+}
+
+/// Returns the text of an element in `range` after applying `edit` to it.
+fn text_after_edit(range: TextRange, text: &str, edit: &TextEdit) -> String {
+    let edit = TextEdit::replace(edit.delete - range.start(), edit.insert.clone());
+    edit.apply(text)
+}
+
+fn is_contextual_kw(text: &str) -> bool {
+    text == "gc" || text == "value"
+}
+
+/// Returns true if the tokens start with an opening brace that is closed by the last token.
+fn is_balanced(tokens: &[Token]) -> bool {
+    if tokens.len() < 2
+        || tokens.first().unwrap().kind != T!['{']
+        || tokens.last().unwrap().kind != T!['}']
+    {
+        return false;
+    }
+    let mut balance = 0usize;
+    for token in tokens[1..tokens.len() - 1].iter() {
+        match token.kind {
+            T!['{'] => balance += 1,
+            T!['}'] => {
+                balance = match balance.checked_sub(1) {
+                    Some(balance) => balance,
+                    None => return false,
+                }
+            }
+            _ => (),
+        }
+    }
+    balance == 0
+}
+
+/// Combines the errors of the old tree outside of the reparsed range with the errors of the
+/// reparsed part of the tree.
+fn merge_errors(
+    old_errors: Vec<SyntaxError>,
+    new_errors: Vec<SyntaxError>,
+    old_range: TextRange,
+    edit: &TextEdit,
+) -> Vec<SyntaxError> {
+    let mut before = Vec::new();
+    let mut after = Vec::new();
+    for error in old_errors {
+        if error.location().end_offset() <= old_range.start() {
+            before.push(error);
+        } else if error.location().offset() >= old_range.end() {
+            after.push(error.add_offset(TextUnit::of_str(&edit.insert), edit.delete.len()));
+        }
+    }
+
+    before
+        .into_iter()
+        .chain(
+            new_errors
+                .into_iter()
+                .map(|error| error.add_offset(old_range.start(), 0.into())),
+        )
+        .chain(after)
+        .collect()
+}
@@ -65,6 +65,11 @@ impl SyntaxError {
     pub fn location(&self) -> Location {
         self.location.clone()
     }
+
+    pub fn add_offset(mut self, plus_offset: TextUnit, minus_offset: TextUnit) -> SyntaxError {
+        self.location = self.location.add_offset(plus_offset, minus_offset);
+        self
+    }
 }
 
 impl fmt::Display for SyntaxError {
@@ -110,6 +110,8 @@ pub enum SyntaxKind {
     RECORD_FIELD_DEF,
     TUPLE_FIELD_DEF_LIST,
     TUPLE_FIELD_DEF,
+    ATTR,
+    ATTR_ARG_LIST,
     PATH_TYPE,
     NEVER_TYPE,
     LET_STMT,
@@ -421,6 +423,8 @@ impl SyntaxKind {
                 RECORD_FIELD_DEF => &SyntaxInfo { name: "RECORD_FIELD_DEF" },
                 TUPLE_FIELD_DEF_LIST => &SyntaxInfo { name: "TUPLE_FIELD_DEF_LIST" },
                 TUPLE_FIELD_DEF => &SyntaxInfo { name: "TUPLE_FIELD_DEF" },
+                ATTR => &SyntaxInfo { name: "ATTR" },
+                ATTR_ARG_LIST => &SyntaxInfo { name: "ATTR_ARG_LIST" },
                 PATH_TYPE => &SyntaxInfo { name: "PATH_TYPE" },
                 NEVER_TYPE => &SyntaxInfo { name: "NEVER_TYPE" },
                 LET_STMT => &SyntaxInfo { name: "LET_STMT" },
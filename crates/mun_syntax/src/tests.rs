@@ -1,2 +1,3 @@
 mod lexer;
 mod parser;
+mod reparsing;
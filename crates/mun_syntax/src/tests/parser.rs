@@ -334,6 +334,19 @@ fn memory_type_specifier() {
     )
 }
 
+#[test]
+fn attributes() {
+    snapshot_test(
+        r#"
+    struct Foo {
+        #[migrate_from(a)]
+        b: f64,
+        c: i32,
+    }
+    "#,
+    )
+}
+
 #[test]
 fn visibility() {
     snapshot_test(
@@ -0,0 +1,237 @@
+use crate::{parsing::incremental_reparse, SourceFile, TextEdit, TextRange, TextUnit};
+
+/// Applies an edit that replaces the first occurrence of `replace` in `text` with `with` and
+/// checks that the incrementally reparsed tree equals the tree of a full parse. Returns the length
+/// of the text that was reparsed incrementally, if any.
+fn check_reparse(text: &str, replace: &str, with: &str) -> Option<TextUnit> {
+    let start = text.find(replace).expect("text to replace is missing");
+    let range = TextRange::offset_len(TextUnit::from_usize(start), TextUnit::of_str(replace));
+    let edit = TextEdit::replace(range, with.to_owned());
+
+    let parse = SourceFile::parse(text);
+    let reparsed = parse.reparse(&edit);
+    let full = SourceFile::parse(&edit.apply(text));
+    assert_eq!(reparsed.debug_dump(), full.debug_dump());
+
+    incremental_reparse(&parse.syntax_node(), &edit, parse.errors().to_vec())
+        .map(|(_, _, reparsed_range)| reparsed_range.len())
+}
+
+#[test]
+fn reparse_token() {
+    let text = "fn foo() {\n    let abc = 1;\n}\n";
+    assert_eq!(check_reparse(text, "abc", "abcd"), Some(3.into()));
+    assert_eq!(check_reparse(text, "    ", "  "), Some(5.into()));
+
+    let text = "// a comment\nfn foo() {}\n";
+    assert_eq!(
+        check_reparse(text, "a comment", "the comment"),
+        Some(12.into())
+    );
+}
+
+#[test]
+fn reparse_token_rejects_changed_tokens() {
+    let text = "fn foo() {\n    let abc = 1;\n}\n";
+    // Turns the identifier into a keyword
+    assert_ne!(check_reparse(text, "abc", "fn"), Some(3.into()));
+    // Merges the identifier with the next token
+    assert_ne!(check_reparse(text, "abc = 1", "abc"), Some(3.into()));
+
+    // Detaches the comment from the function
+    let text = "// a comment\nfn foo() {}\n";
+    assert_eq!(check_reparse(text, "\n", "\n\n"), None);
+
+    // `gc` is a contextual keyword
+    let text = "struct(foo) Foo;";
+    assert_eq!(check_reparse(text, "foo", "gc"), None);
+}
+
+#[test]
+fn reparse_block() {
+    let text = "fn foo() {\n    let a = 1;\n}\n\nfn bar() {}\n";
+    assert_eq!(
+        check_reparse(text, "let a = 1;", "let a = 1;\n    let b = a + 2;"),
+        Some(TextUnit::of_str("{\n    let a = 1;\n}"))
+    );
+    assert_eq!(
+        check_reparse(text, "{}", "{ foo() }"),
+        Some(TextUnit::of_str("{}"))
+    );
+
+    // Only the innermost block is reparsed
+    let text = "fn foo() {\n    if true {\n        1\n    }\n}\n";
+    assert_eq!(
+        check_reparse(text, "1", "2 + 3"),
+        Some(TextUnit::of_str("{\n        1\n    }"))
+    );
+
+    // Edits that are not contained in the innermost block reparse the enclosing block
+    assert_eq!(
+        check_reparse(
+            text,
+            "true {\n        1",
+            "true {\n        1\n    } else {\n        2"
+        ),
+        Some(TextUnit::of_str("{\n    if true {\n        1\n    }\n}"))
+    );
+
+    let text = "struct Foo {\n    a: i32,\n}\n";
+    assert_eq!(
+        check_reparse(text, "a: i32", "a: i32, b: f64"),
+        Some(TextUnit::of_str("{\n    a: i32,\n}"))
+    );
+}
+
+#[test]
+fn reparse_falls_back_to_full_parse() {
+    let text = "fn foo() {\n    if true {\n        1\n    }\n}\n";
+    assert_eq!(check_reparse(text, "fn foo()", "fn foo("), None);
+    assert_eq!(check_reparse(text, "1", "1 }"), None);
+    assert_eq!(check_reparse(text, "{", ""), None);
+    assert_eq!(check_reparse(text, text, ""), None);
+    assert_eq!(check_reparse("", "", "fn main() {}"), None);
+}
+
+#[test]
+fn reparse_updates_errors() {
+    // Errors before, inside and after the edited block
+    let text = "fn foo() { let x = ; }\n\nfn bar() {\n    let a = ;\n}\n\nfn baz() { let y = ; }\n";
+    assert_eq!(
+        check_reparse(
+            text,
+            "let a = ;",
+            "let a = 1;\n    let b = ;\n    let c = ;"
+        ),
+        Some(TextUnit::of_str("{\n    let a = ;\n}"))
+    );
+    assert_eq!(
+        check_reparse(text, "let a = ;", "let a = 1;"),
+        Some(TextUnit::of_str("{\n    let a = ;\n}"))
+    );
+    assert_eq!(check_reparse(text, "bar", "barbaz"), Some(3.into()));
+}
+
+/// A small deterministic pseudo random number generator (xorshift), so failures are reproducible.
+struct Rng(u64);
+
+impl Rng {
+    fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+}
+
+const FUZZ_SOURCES: &[&str] = &[
+    r#"// A comment that belongs to the function
+fn fibonacci(n: i32) -> i32 {
+    if n <= 1 {
+        n
+    } else {
+        fibonacci(n - 1) + fibonacci(n - 2)
+    }
+}
+
+pub fn main() -> bool {
+    let a = fibonacci(5);
+    let mut i = 0;
+    loop {
+        if i > 10 { break; }
+        i += 1;
+    }
+    while a < 10 { a = a * 2; };
+    a == 10
+}
+"#,
+    r#"struct(value) Vec2 {
+    x: f32,
+    y: f32,
+}
+
+struct(gc) Foo(i32, f64);
+
+struct Bar;
+
+/* A block comment */
+pub fn new_vec(x: f32, y: f32) -> Vec2 {
+    let v = Vec2 { x: x, y };
+    let foo = Foo(1, 2.0);
+    { { v } }
+}
+
+fn string() { "a {string}"; }
+"#,
+];
+
+const FUZZ_INSERTIONS: &[&str] = &[
+    "",
+    "a",
+    "foo",
+    "gc",
+    "value",
+    "fn",
+    "let",
+    "1",
+    "2.0",
+    " ",
+    "\n",
+    "\n\n",
+    "{",
+    "}",
+    "{}",
+    "(",
+    ")",
+    ";",
+    ",",
+    ":",
+    "=",
+    "==",
+    "+",
+    "//",
+    "/*",
+    "*/",
+    "\"",
+    "let x = 1;",
+    "{ a }",
+    "struct",
+    "if a { b } else { c }",
+];
+
+#[test]
+fn reparse_fuzz() {
+    let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+    for source in FUZZ_SOURCES {
+        let mut text = source.to_string();
+        let mut parse = SourceFile::parse(&text);
+        for _ in 0..500 {
+            let start = rng.below(text.len() + 1);
+            let end = (start + rng.below(8)).min(text.len());
+            let insert = FUZZ_INSERTIONS[rng.below(FUZZ_INSERTIONS.len())];
+            let edit = TextEdit::replace(
+                TextRange::from_to(TextUnit::from_usize(start), TextUnit::from_usize(end)),
+                insert.to_owned(),
+            );
+
+            let edited_text = edit.apply(&text);
+            let reparsed = parse.reparse(&edit);
+            let full = SourceFile::parse(&edited_text);
+            assert_eq!(
+                reparsed.debug_dump(),
+                full.debug_dump(),
+                "reparse of {:?} differs from a full parse of:\n{}",
+                edit,
+                edited_text
+            );
+
+            // Keep editing the reparsed tree to also verify the reuse of reparsed trees
+            text = edited_text;
+            parse = reparsed;
+        }
+    }
+}
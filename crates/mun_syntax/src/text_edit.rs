@@ -0,0 +1,39 @@
+use crate::{TextRange, TextUnit};
+
+/// A single edit of a text: the text in the `delete` range is replaced by `insert`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TextEdit {
+    pub delete: TextRange,
+    pub insert: String,
+}
+
+impl TextEdit {
+    /// Constructs an edit that replaces the text in `range` with `replace_with`.
+    pub fn replace(range: TextRange, replace_with: String) -> TextEdit {
+        TextEdit {
+            delete: range,
+            insert: replace_with,
+        }
+    }
+
+    /// Constructs an edit that deletes the text in `range`.
+    pub fn delete(range: TextRange) -> TextEdit {
+        TextEdit::replace(range, String::new())
+    }
+
+    /// Constructs an edit that inserts `text` at `offset`.
+    pub fn insert(offset: TextUnit, text: String) -> TextEdit {
+        TextEdit::replace(TextRange::offset_len(offset, 0.into()), text)
+    }
+
+    /// Returns the result of applying this edit to `text`.
+    pub fn apply(&self, text: &str) -> String {
+        let start = self.delete.start().to_usize();
+        let end = self.delete.end().to_usize();
+        let mut buf = String::with_capacity(text.len() - (end - start) + self.insert.len());
+        buf.push_str(&text[..start]);
+        buf.push_str(&self.insert);
+        buf.push_str(&text[end..]);
+        buf
+    }
+}